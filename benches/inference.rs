@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use myrrh_rs::{stages, RenderOptions};
+use serde_json::{json, Value};
+
+/// An orders export with `records` orders, each nesting a customer, their address and line
+/// items, so there are repeated and nested objects to squash
+fn fixture(records: usize) -> Value {
+    Value::Array(
+        (0..records)
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "status": if id % 3 == 0 { "paid" } else { "pending" },
+                    "createdAt": "2024-01-02T03:04:05Z",
+                    "customer": {
+                        "id": id * 7,
+                        "name": "Ada Lovelace",
+                        "email": "ada@example.com",
+                        "billing": { "line1": "1 Main St", "city": "London", "postcode": "N1" },
+                        "shipping": { "line1": "1 Main St", "city": "London", "postcode": "N1" },
+                    },
+                    "items": (0..id % 5 + 1)
+                        .map(|item| json!({ "sku": format!("SKU-{}", item), "quantity": item, "price": 9.99 }))
+                        .collect::<Vec<_>>(),
+                    "tags": ["a", "b"],
+                    "note": if id % 2 == 0 { Value::Null } else { json!("leave at the door") },
+                })
+            })
+            .collect(),
+    )
+}
+
+const SIZES: [(&str, usize); 3] = [("small", 10), ("medium", 1_000), ("large", 20_000)];
+
+fn walk_value_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("walk_value_tree");
+    group.sample_size(10);
+    for (size, records) in SIZES {
+        let value = fixture(records);
+        group.bench_function(size, |b| b.iter(|| stages::walk(black_box(&value))));
+    }
+    group.finish();
+}
+
+fn calculate_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calculate_hash");
+    group.sample_size(10);
+    for (size, records) in SIZES {
+        let mut tree = stages::walk(&fixture(records));
+        stages::squash(&mut tree);
+        group.bench_function(size, |b| {
+            b.iter_batched(
+                || tree.clone(),
+                |mut tree| stages::calculate_hash(&mut tree),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn to_type_string(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_type_string");
+    group.sample_size(10);
+    let options = RenderOptions::default();
+    for (size, records) in SIZES {
+        let mut tree = stages::walk(&fixture(records));
+        stages::squash(&mut tree);
+        stages::calculate_hash(&mut tree);
+        group.bench_function(size, |b| {
+            b.iter(|| stages::to_type_string(black_box(&tree), &options))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, walk_value_tree, calculate_hash, to_type_string);
+criterion_main!(benches);
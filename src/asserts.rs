@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::guards::check_expression;
+use crate::schema::child_path;
+use crate::typescript_node::{
+    Fingerprint, RenderContext, RenderOptions, TypeScriptNode, TypeScriptPrimativeType,
+};
+
+/// Renders a `parse{name}` function for the root and each common type, which returns its
+/// argument typed as `{name}`, or throws a `TypeError` naming the JSON path which failed and the
+/// type it expected there. Paths are built as template literal contents, starting from the
+/// `path` the value was found at, so array indices are filled in at runtime. The functions of
+/// types referring to common types call theirs.
+pub(crate) fn to_assert_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let context = RenderContext::new(node, options);
+    let type_names = &context.type_names;
+    let mut output = String::new();
+    for (name, named_node) in node.named_types(type_names) {
+        output.push_str(&assert_function(name, named_node, type_names));
+    }
+    output.push_str(&assert_function(&options.root_name, node, type_names));
+    output
+}
+
+fn assert_function(
+    name: &str,
+    node: &TypeScriptNode,
+    type_names: &HashMap<Fingerprint, String>,
+) -> String {
+    let mut body = String::new();
+    Checks { type_names }.push(&mut body, node, "v", "${path}", 1, 0, true);
+    format!(
+        "\nexport function parse{}(v: unknown, path = \"$\"): {} {{\n{}{}  return v as {};\n}}\n",
+        name, name, FAIL_FUNCTION, body, name
+    )
+}
+
+const FAIL_FUNCTION: &str =
+    "  const fail = (path: string, expected: string, actual: unknown): never => {
+    const found = actual === null ? \"null\" : Array.isArray(actual) ? \"array\" : typeof actual;
+    throw new TypeError(`${path}: expected ${expected} but found ${found}`);
+  };
+";
+
+/// Pushes the checks of values, calling the functions of the common types in `type_names`
+struct Checks<'a> {
+    type_names: &'a HashMap<Fingerprint, String>,
+}
+
+impl Checks<'_> {
+    /// Pushes the checks of `value` against the node, those of the common type it is an
+    /// instance of unless it is the `top` of a function
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &self,
+        body: &mut String,
+        node: &TypeScriptNode,
+        value: &str,
+        path: &str,
+        indent: usize,
+        depth: usize,
+        top: bool,
+    ) {
+        let pad = "  ".repeat(indent);
+        let mut absent = Vec::new();
+        if node.is_optional() {
+            absent.push(format!("{} !== undefined", value));
+        }
+        if node.is_nullable() {
+            absent.push(format!("{} !== null", value));
+        }
+        let (check, inner_indent) = if absent.is_empty() {
+            (pad.clone(), indent)
+        } else {
+            body.push_str(&format!("{}if ({}) {{\n", pad, absent.join(" && ")));
+            (format!("{}  ", pad), indent + 1)
+        };
+        let expected = node.type_signature().as_str();
+        let fail = format!("fail(`{}`, \"{}\", {});", path, expected, value);
+        let named = match node.type_signature() {
+            TypeScriptPrimativeType::Object if !top => self.type_names.get(&node.hash()),
+            _ => None,
+        };
+        match node.type_signature() {
+            TypeScriptPrimativeType::Object if named.is_some() => body.push_str(&format!(
+                "{}parse{}({}, `{}`);\n",
+                check,
+                named.unwrap(),
+                value,
+                path
+            )),
+            TypeScriptPrimativeType::String
+            | TypeScriptPrimativeType::Number
+            | TypeScriptPrimativeType::Boolean => body.push_str(&format!(
+                "{}if (typeof {} !== \"{}\") {}\n",
+                check, value, expected, fail
+            )),
+            TypeScriptPrimativeType::Null => {
+                body.push_str(&format!("{}if ({} !== null) {}\n", check, value, fail))
+            }
+            TypeScriptPrimativeType::Unknown => {
+                body.push_str(&format!("{}if ({} === undefined) {}\n", check, value, fail))
+            }
+            TypeScriptPrimativeType::Object => {
+                body.push_str(&format!(
+                    "{}if (typeof {} !== \"object\" || {} === null || Array.isArray({})) {}\n",
+                    check, value, value, value, fail
+                ));
+                for sub_item in node.sub_items() {
+                    let key = sub_item.name().unwrap_or_default();
+                    let field = format!(
+                        "({} as Record<string, unknown>)[{}]",
+                        value,
+                        serde_json::to_string(key).unwrap()
+                    );
+                    self.push(
+                        body,
+                        sub_item,
+                        &field,
+                        &format!("{}{}", path, escape_template(&child_path("", key))),
+                        inner_indent,
+                        depth,
+                        false,
+                    );
+                }
+            }
+            TypeScriptPrimativeType::Union => {
+                let checks = node
+                    .sub_items()
+                    .iter()
+                    .map(|member| check_expression(member, value, depth))
+                    .join(" || ");
+                let expected = node
+                    .sub_items()
+                    .iter()
+                    .map(|member| member.type_signature().as_str())
+                    .dedup()
+                    .join(" | ");
+                body.push_str(&format!(
+                    "{}if (!({})) fail(`{}`, \"{}\", {});\n",
+                    check, checks, path, expected, value
+                ));
+            }
+            TypeScriptPrimativeType::Array => {
+                body.push_str(&format!(
+                    "{}if (!Array.isArray({})) {}\n",
+                    check, value, fail
+                ));
+                let item = format!("item{}", depth);
+                let index = format!("i{}", depth);
+                let element_path = format!("{}[${{{}}}]", path, index);
+                let alternatives: Vec<&TypeScriptNode> = node
+                    .elements()
+                    .iter()
+                    .unique_by(|sub_item| check_expression(sub_item, &item, depth + 1))
+                    .collect();
+                if !alternatives.is_empty() {
+                    body.push_str(&format!(
+                        "{}({} as unknown[]).forEach(({}: unknown, {}: number) => {{\n",
+                        check, value, item, index
+                    ));
+                    if let [element] = alternatives.as_slice() {
+                        self.push(
+                            body,
+                            element,
+                            &item,
+                            &element_path,
+                            inner_indent + 1,
+                            depth + 1,
+                            false,
+                        );
+                    } else {
+                        let checks = alternatives
+                            .iter()
+                            .map(|element| check_expression(element, &item, depth + 1))
+                            .sorted()
+                            .join(" || ");
+                        let expected = alternatives
+                            .iter()
+                            .map(|element| element.type_signature().as_str())
+                            .sorted()
+                            .dedup()
+                            .join(" | ");
+                        body.push_str(&format!(
+                            "{}  if (!({})) fail(`{}`, \"{}\", {});\n",
+                            check, checks, element_path, expected, item
+                        ));
+                    }
+                    body.push_str(&format!("{}}});\n", check));
+                }
+            }
+        }
+        if !absent.is_empty() {
+            body.push_str(&format!("{}}}\n", pad));
+        }
+    }
+}
+
+/// Escapes a path segment for use inside a template literal
+fn escape_template(segment: &str) -> String {
+    segment
+        .replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_assert_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn asserts_name_failing_paths() {
+        let val_tree =
+            serde_json::from_str(r#"{ "payments": [{ "amount": 1 }], "note": null }"#).unwrap();
+        let result = walk_value_tree(&val_tree, None);
+        let output = to_assert_string(&result, &RenderOptions::default());
+        assert!(output.starts_with(
+            "\nexport function parseDefaultType(v: unknown, path = \"$\"): DefaultType {\n"
+        ));
+        assert!(output.ends_with(
+            "  if (typeof v !== \"object\" || v === null || Array.isArray(v)) fail(`${path}`, \"object\", v);
+  if ((v as Record<string, unknown>)[\"note\"] !== null) fail(`${path}.note`, \"null\", (v as Record<string, unknown>)[\"note\"]);
+  if (!Array.isArray((v as Record<string, unknown>)[\"payments\"])) fail(`${path}.payments`, \"array\", (v as Record<string, unknown>)[\"payments\"]);
+  ((v as Record<string, unknown>)[\"payments\"] as unknown[]).forEach((item0: unknown, i0: number) => {
+    if (typeof item0 !== \"object\" || item0 === null || Array.isArray(item0)) fail(`${path}.payments[${i0}]`, \"object\", item0);
+    if (typeof (item0 as Record<string, unknown>)[\"amount\"] !== \"number\") fail(`${path}.payments[${i0}].amount`, \"number\", (item0 as Record<string, unknown>)[\"amount\"]);
+  });
+  return v as DefaultType;
+}
+"
+        ));
+    }
+
+    #[test]
+    fn asserts_unions_of_array_elements() {
+        let val_tree = serde_json::from_str(r#"[1, "a"]"#).unwrap();
+        let result = walk_value_tree(&val_tree, None);
+        assert!(to_assert_string(&result, &RenderOptions::default()).contains(
+            "  if (!(typeof item0 === \"number\" || typeof item0 === \"string\")) fail(`${path}[${i0}]`, \"number | string\", item0);\n"
+        ));
+    }
+
+    #[test]
+    fn asserts_recurse_through_themselves() {
+        let val_tree = serde_json::from_str(
+            r#"{ "id": 1, "replies": [{ "id": 2, "replies": [{ "id": 3, "replies": [] }] }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.calculate_hash();
+        assert_eq!(to_assert_string(&result, &RenderOptions::default()),
+            "\nexport function parseDefaultType(v: unknown, path = \"$\"): DefaultType {\n  const fail = (path: string, expected: string, actual: unknown): never => {\n    const found = actual === null ? \"null\" : Array.isArray(actual) ? \"array\" : typeof actual;\n    throw new TypeError(`${path}: expected ${expected} but found ${found}`);\n  };\n  if (typeof v !== \"object\" || v === null || Array.isArray(v)) fail(`${path}`, \"object\", v);\n  if (typeof (v as Record<string, unknown>)[\"id\"] !== \"number\") fail(`${path}.id`, \"number\", (v as Record<string, unknown>)[\"id\"]);\n  if (!Array.isArray((v as Record<string, unknown>)[\"replies\"])) fail(`${path}.replies`, \"array\", (v as Record<string, unknown>)[\"replies\"]);\n  ((v as Record<string, unknown>)[\"replies\"] as unknown[]).forEach((item0: unknown, i0: number) => {\n    parseDefaultType(item0, `${path}.replies[${i0}]`);\n  });\n  return v as DefaultType;\n}\n"
+        );
+    }
+}
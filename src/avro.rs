@@ -0,0 +1,166 @@
+use serde_json::{json, Value};
+
+use crate::declarations::Declarations;
+use crate::naming::segment;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+/// Renders the tree as an Avro schema, a record per object type, named as the Python classes
+/// are. A record is defined where it first appears and referred to by name after that. Fields
+/// which were missing or `null` in some samples are unions with `null`, defaulting to it.
+pub(crate) fn to_avro_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let mut renderer = Renderer {
+        records: Declarations::new(node, options, &[]),
+    };
+    let schema = match node.type_signature() {
+        TypeScriptPrimativeType::Object => renderer.record(node, options.root_name.clone(), true),
+        _ => renderer.schema(node, &options.root_name),
+    };
+    let mut output = serde_json::to_string_pretty(&schema).unwrap();
+    output.push('\n');
+    output
+}
+
+/// `user-id` -> `user_id`, as names are limited to ASCII letters, digits and `_`
+fn to_avro_name(name: &str) -> String {
+    let mut avro_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !avro_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro_name.insert(0, '_');
+    }
+    avro_name
+}
+
+struct Renderer {
+    /// Names of the records, common types being referred to by name once defined
+    records: Declarations,
+}
+
+impl Renderer {
+    /// The schema of a value, `hint` naming the record of an inline object. Values below
+    /// `--max-depth` can only be carried as JSON-encoded strings.
+    fn schema(&mut self, node: &TypeScriptNode, hint: &str) -> Value {
+        let schema = match node.type_signature() {
+            TypeScriptPrimativeType::String | TypeScriptPrimativeType::Unknown => json!("string"),
+            TypeScriptPrimativeType::Number if node.is_integer() => json!("long"),
+            TypeScriptPrimativeType::Number => json!("double"),
+            TypeScriptPrimativeType::Boolean => json!("boolean"),
+            TypeScriptPrimativeType::Null => return json!("null"),
+            // unions can't hold unions, so `null` joins the branches
+            TypeScriptPrimativeType::Union => {
+                let mut branches = Vec::new();
+                if node.is_nullable() || node.is_optional() {
+                    branches.push(json!("null"));
+                }
+                for member in node.sub_items() {
+                    let branch = self.schema(member, hint);
+                    if !branches.contains(&branch) {
+                        branches.push(branch);
+                    }
+                }
+                return Value::Array(branches);
+            }
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                json!(self.records.referenced_name(node))
+            }
+            TypeScriptPrimativeType::Object => match self.records.emitted_name(node) {
+                Some(name) => json!(name),
+                None => self.record(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array => {
+                let element_hint = format!("{}Item", hint);
+                let mut items = Vec::new();
+                for element in node.elements() {
+                    let element_schema = self.schema(element, &element_hint);
+                    let branches = match element_schema {
+                        Value::Array(branches) => branches,
+                        element_schema => vec![element_schema],
+                    };
+                    for branch in branches {
+                        if !items.contains(&branch) {
+                            items.push(branch);
+                        }
+                    }
+                }
+                // `null` has to come first in a union for it to be the default
+                items.sort_by_key(|item| item != "null");
+                let items = match items.len() {
+                    0 => json!("string"),
+                    1 => items.pop().unwrap(),
+                    _ => Value::Array(items),
+                };
+                json!({ "type": "array", "items": items })
+            }
+        };
+        if node.is_nullable() || node.is_optional() {
+            json!(["null", schema])
+        } else {
+            schema
+        }
+    }
+
+    /// Defines the record of an object. The root is named `hint`, common types by their type
+    /// name and other objects after `hint`.
+    fn record(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> Value {
+        let name = self.records.name(node, to_avro_name(&hint), root);
+        let mut fields = Vec::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let field_type = self.schema(sub_item, &format!("{}{}", name, segment(key)));
+            let mut field = json!({ "name": to_avro_name(key), "type": field_type });
+            if field["type"] == "null" || field["type"].get(0) == Some(&json!("null")) {
+                field["default"] = Value::Null;
+            }
+            fields.push(field);
+        }
+        json!({ "type": "record", "name": name, "fields": fields })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_avro_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_avro_records() {
+        let val_tree = serde_json::from_str(
+            r#"{ "billing": { "city": "a" }, "shipping": { "city": "b" }, "lines": [{ "qty": 1, "note": null }, { "qty": 2 }], "tags": ["a", null] }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        let schema: serde_json::Value =
+            serde_json::from_str(&to_avro_string(&result, &RenderOptions::default())).unwrap();
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "type": "record",
+                "name": "DefaultType",
+                "fields": [
+                    { "name": "billing", "type": {
+                        "type": "record",
+                        "name": "DefaultType_0",
+                        "fields": [{ "name": "city", "type": "string" }],
+                    } },
+                    { "name": "lines", "type": { "type": "array", "items": {
+                        "type": "record",
+                        "name": "DefaultType_1",
+                        "fields": [
+                            { "name": "note", "type": "null", "default": null },
+                            { "name": "qty", "type": "long" },
+                        ],
+                    } } },
+                    { "name": "shipping", "type": "DefaultType_0" },
+                    { "name": "tags", "type": { "type": "array", "items": ["null", "string"] } },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+/// How long each stage of `bench` took, every iteration
+#[derive(Default)]
+pub(crate) struct Timings {
+    stages: Vec<(&'static str, Vec<Duration>)>,
+}
+
+impl Timings {
+    /// Runs a stage, adding the time it took to those of its earlier iterations
+    pub(crate) fn time<T>(&mut self, stage: &'static str, run: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = run();
+        let elapsed = started.elapsed();
+        match self.stages.iter_mut().find(|(name, _)| *name == stage) {
+            Some((_, durations)) => durations.push(elapsed),
+            None => self.stages.push((stage, vec![elapsed])),
+        }
+        result
+    }
+
+    /// A table of the fastest, mean and slowest time of each stage, in the order they ran,
+    /// followed by the totals of the iterations
+    pub(crate) fn report(&self) -> String {
+        let iterations = self
+            .stages
+            .iter()
+            .map(|(_, durations)| durations.len())
+            .min()
+            .unwrap_or_default();
+        let totals: Vec<Duration> = (0..iterations)
+            .map(|iteration| {
+                self.stages
+                    .iter()
+                    .map(|(_, durations)| durations[iteration])
+                    .sum()
+            })
+            .collect();
+        let mut report = format!(
+            "{:<8} {:>10} {:>10} {:>10}\n",
+            "stage", "min", "mean", "max"
+        );
+        for (stage, durations) in self
+            .stages
+            .iter()
+            .map(|(stage, durations)| (*stage, durations.as_slice()))
+            .chain(std::iter::once(("total", totals.as_slice())))
+            .filter(|(_, durations)| !durations.is_empty())
+        {
+            let min = durations.iter().min().copied().unwrap_or_default();
+            let max = durations.iter().max().copied().unwrap_or_default();
+            let mean = durations.iter().sum::<Duration>() / durations.len() as u32;
+            report.push_str(&format!(
+                "{:<8} {:>10} {:>10} {:>10}\n",
+                stage,
+                format_duration(min),
+                format_duration(mean),
+                format_duration(max)
+            ));
+        }
+        report
+    }
+}
+
+/// `12.3 ms`, in the unit which keeps the number readable
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds >= 1.0 {
+        format!("{:.2} s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.1} ms", seconds * 1e3)
+    } else {
+        format!("{:.1} µs", seconds * 1e6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{format_duration, Timings};
+
+    #[test]
+    fn reports_timings() {
+        let mut timings = Timings::default();
+        for _ in 0..3 {
+            assert_eq!(timings.time("walk", || 1), 1);
+            timings.time("render", || ());
+        }
+        let report = timings.report();
+        let stages: Vec<&str> = report
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect();
+        assert_eq!(stages, ["stage", "walk", "render", "total"]);
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.5 ms");
+        assert_eq!(format_duration(Duration::from_secs(2)), "2.00 s");
+        assert_eq!(format_duration(Duration::from_nanos(2500)), "2.5 µs");
+    }
+}
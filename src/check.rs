@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+
+use crate::errors::ErrorKind;
+use crate::sarif::{to_sarif_log, SarifResult, SarifRule};
+use crate::schema::{parent_path, Compatibility, FieldChange};
+use crate::typescript_parser;
+use crate::Result;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CheckFormat {
+    /// Plain text listing of the stale lines, or of the changes
+    Human,
+    /// GitHub Actions `::error` workflow annotations
+    Github,
+    /// SARIF 2.1.0 log for code scanning tools
+    Sarif,
+}
+
+const STALE_TYPES_RULE: SarifRule = SarifRule {
+    id: "stale-generated-types",
+    description: "Generated types no longer match the types inferred from the input",
+};
+
+const BREAKING_CHANGE_RULE: SarifRule = SarifRule {
+    id: "breaking-schema-change",
+    description: "A change to the schema which breaks its consumers",
+};
+
+const NON_BREAKING_CHANGE_RULE: SarifRule = SarifRule {
+    id: "non-breaking-schema-change",
+    description: "A change to the schema which only widens it",
+};
+
+/// A way in which the file on disk is stale, at the line of the file it concerns
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct StaleLine {
+    pub(crate) line: usize,
+    /// The change to the type of a field, `None` when only the text of the output differs
+    change: Option<FieldChange>,
+}
+
+impl StaleLine {
+    fn summary(&self) -> String {
+        match &self.change {
+            Some(change) => change.describe(),
+            None => "the output differs from what is generated from the input".to_string(),
+        }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        match &self.change {
+            Some(FieldChange::Removed { path, old }) => format!(
+                "field `{}` is stale: `{}` is no longer generated from the input",
+                path,
+                old.describe()
+            ),
+            Some(FieldChange::Added { path, new }) => format!(
+                "field `{}` is missing: expected `{}` from the input",
+                path,
+                new.describe()
+            ),
+            Some(FieldChange::Changed { path, old, new }) => format!(
+                "field `{}` is out of date: expected `{}` from the input but found `{}`",
+                path,
+                new.describe(),
+                old.describe()
+            ),
+            None => "generated output is stale: it differs from what is generated from the input"
+                .to_string(),
+        }
+    }
+}
+
+/// Compares the file on disk with freshly generated output, by the types of their fields. A
+/// field whose type changed is reported at the line declaring it, a missing field at the
+/// object which should hold it. Output which only differs in its text, or which isn't
+/// TypeScript, is reported at its first line.
+pub(crate) fn stale_lines(existing: &str, generated: &str) -> Vec<StaleLine> {
+    if existing == generated {
+        return Vec::new();
+    }
+    let mut stale_lines: Vec<StaleLine> = match (
+        typescript_parser::parse_schema_lines(existing),
+        typescript_parser::parse_schema(generated),
+    ) {
+        (Ok((old, lines)), Ok(new)) => old
+            .changes_to(&new)
+            .into_iter()
+            .map(|change| StaleLine {
+                line: declaration_line(&lines, change.path()),
+                change: Some(change),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    if stale_lines.is_empty() {
+        stale_lines.push(StaleLine {
+            line: 1,
+            change: None,
+        });
+    }
+    stale_lines.sort_by_key(|stale_line| stale_line.line);
+    stale_lines
+}
+
+/// The line declaring the field at `path`, or the closest object holding it
+fn declaration_line(lines: &BTreeMap<String, usize>, path: &str) -> usize {
+    let mut path = Some(path);
+    while let Some(current) = path {
+        if let Some(&line) = lines.get(current) {
+            return line;
+        }
+        path = parent_path(current);
+    }
+    1
+}
+
+/// The outputs `--check` found stale over a run, by path, reported together at its end
+#[derive(Debug, Default)]
+pub(crate) struct StaleOutputs {
+    found: Mutex<Vec<(String, Vec<StaleLine>)>>,
+}
+
+impl StaleOutputs {
+    pub(crate) fn push(&self, path: &str, stale_lines: Vec<StaleLine>) {
+        let mut found = self.found.lock().unwrap_or_else(|e| e.into_inner());
+        found.push((path.to_string(), stale_lines));
+    }
+
+    /// Prints the report of every stale output found since the last call, failing when there
+    /// was one
+    pub(crate) fn report(&self, format: CheckFormat) -> Result<()> {
+        let found = std::mem::take(&mut *self.found.lock().unwrap_or_else(|e| e.into_inner()));
+        if found.is_empty() {
+            return Ok(());
+        }
+        print!("{}", render_report(&found, format));
+        anyhow::bail!(
+            ErrorKind::Stale.because(format!("{} generated output(s) are stale", found.len()))
+        )
+    }
+}
+
+pub(crate) fn render_report(outputs: &[(String, Vec<StaleLine>)], format: CheckFormat) -> String {
+    let mut report = String::new();
+    let mut results = Vec::new();
+    for (path, stale_lines) in outputs {
+        match format {
+            CheckFormat::Human => {
+                report.push_str(&format!(
+                    "{} is out of date with the inferred types:\n",
+                    path
+                ));
+                for stale_line in stale_lines {
+                    report.push_str(&format!("{}: {}\n", stale_line.line, stale_line.summary()));
+                }
+            }
+            CheckFormat::Github => {
+                for stale_line in stale_lines {
+                    report.push_str(&format!(
+                        "::error file={},line={},title={}::{}\n",
+                        escape_property(path),
+                        stale_line.line,
+                        escape_property("myrrh: generated types are stale"),
+                        escape_data(&stale_line.message())
+                    ));
+                }
+            }
+            CheckFormat::Sarif => {
+                results.extend(stale_lines.iter().map(|stale_line| SarifResult {
+                    rule_id: STALE_TYPES_RULE.id,
+                    level: "error",
+                    message: stale_line.message(),
+                    uri: path.clone(),
+                    line: stale_line.line,
+                }));
+            }
+        }
+    }
+    if format == CheckFormat::Sarif {
+        report.push_str(&to_sarif_log(&[STALE_TYPES_RULE], &results));
+    }
+    report
+}
+
+/// Reports the changes `diff` found between two schemas, each at the line of `path` declaring
+/// the field, when `lines` holds it, as breaking changes are errors and others notices
+pub(crate) fn render_changes(
+    path: &str,
+    changes: &[FieldChange],
+    lines: &BTreeMap<String, usize>,
+    format: CheckFormat,
+) -> String {
+    let mut report = String::new();
+    let mut breaking_changes = 0;
+    let mut results = Vec::new();
+    for change in changes {
+        let (compatibility, reason) = change.classify();
+        let line = declaration_line(lines, change.path());
+        let message = format!("{} ({})", change.describe(), reason);
+        let (label, rule, level, annotation) = match compatibility {
+            Compatibility::Breaking => {
+                breaking_changes += 1;
+                ("breaking", &BREAKING_CHANGE_RULE, "error", "error")
+            }
+            Compatibility::NonBreaking => {
+                ("non-breaking", &NON_BREAKING_CHANGE_RULE, "note", "notice")
+            }
+        };
+        match format {
+            CheckFormat::Human => report.push_str(&format!("[{}] {}\n", label, message)),
+            CheckFormat::Github => report.push_str(&format!(
+                "::{} file={},line={},title={}::{}\n",
+                annotation,
+                escape_property(path),
+                line,
+                escape_property(&format!("myrrh: {} change", label)),
+                escape_data(&message)
+            )),
+            CheckFormat::Sarif => results.push(SarifResult {
+                rule_id: rule.id,
+                level,
+                message,
+                uri: path.to_string(),
+                line,
+            }),
+        }
+    }
+    match format {
+        CheckFormat::Human if breaking_changes > 0 => {
+            report.push_str(&format!("{} breaking change(s) found\n", breaking_changes));
+        }
+        CheckFormat::Sarif => report.push_str(&to_sarif_log(
+            &[BREAKING_CHANGE_RULE, NON_BREAKING_CHANGE_RULE],
+            &results,
+        )),
+        _ => {}
+    }
+    report
+}
+
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_changes, render_report, stale_lines, CheckFormat, StaleLine, StaleOutputs};
+    use crate::errors;
+    use crate::schema::{FieldChange, FieldType};
+    use crate::typescript_parser;
+
+    fn field_type(type_name: &str) -> FieldType {
+        FieldType {
+            type_name: type_name.to_string(),
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn identical_output_has_no_changes() {
+        let output = "type DefaultType = {\n  amount: number;\n};\n";
+        assert_eq!(stale_lines(output, output), vec![]);
+    }
+
+    #[test]
+    fn changed_fields_are_reported_at_their_declaration() {
+        let existing = "type DefaultType = {\n  amount: number;\n  user: User;\n};\n\ntype User = {\n  name: string;\n};\n";
+        let generated = "type DefaultType = {\n  amount: string;\n  user: User;\n};\n\ntype User = {\n  email: string;\n  name: string;\n};\n";
+        assert_eq!(
+            stale_lines(existing, generated),
+            vec![
+                StaleLine {
+                    line: 2,
+                    change: Some(FieldChange::Changed {
+                        path: "$.amount".to_string(),
+                        old: field_type("number"),
+                        new: field_type("string"),
+                    }),
+                },
+                StaleLine {
+                    line: 3,
+                    change: Some(FieldChange::Added {
+                        path: "$.user.email".to_string(),
+                        new: field_type("string"),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn output_only_differing_in_text_is_reported_at_its_first_line() {
+        let existing = "type DefaultType = {\n  amount: number;\n};\n";
+        let generated = "export type DefaultType = {\n  amount: number;\n};\n";
+        assert_eq!(
+            stale_lines(existing, generated),
+            vec![StaleLine {
+                line: 1,
+                change: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn github_format_annotates_stale_fields() {
+        let existing = "type DefaultType = {\n  \"woah, lol\": number;\n};\n";
+        let generated = "type DefaultType = {\n};\n";
+        let report = render_report(
+            &[("src/types.ts".to_string(), stale_lines(existing, generated))],
+            CheckFormat::Github,
+        );
+        assert_eq!(
+            report,
+            "::error file=src/types.ts,line=2,title=myrrh%3A generated types are stale::field `$[\"woah, lol\"]` is stale: `number` is no longer generated from the input\n"
+        );
+    }
+
+    #[test]
+    fn sarif_format_reports_a_result_per_change() {
+        let existing = "type DefaultType = {\n  amount: number;\n};\n";
+        let generated = "type DefaultType = {\n  amount: string;\n};\n";
+        let report = render_report(
+            &[("src/types.ts".to_string(), stale_lines(existing, generated))],
+            CheckFormat::Sarif,
+        );
+        let log: serde_json::Value = serde_json::from_str(&report).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "stale-generated-types");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/types.ts"
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+    }
+
+    #[test]
+    fn reports_every_stale_output_in_one_log() {
+        let stale_outputs = StaleOutputs::default();
+        let generated = "type DefaultType = {\n  amount: string;\n};\n";
+        for path in ["types.ts", "types.zod.ts"] {
+            stale_outputs.push(path, stale_lines("", generated));
+        }
+        let found = stale_outputs
+            .found
+            .lock()
+            .unwrap()
+            .drain(..)
+            .collect::<Vec<_>>();
+        let log: serde_json::Value =
+            serde_json::from_str(&render_report(&found, CheckFormat::Sarif)).unwrap();
+        let uris: Vec<_> = log["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"].clone()
+            })
+            .collect();
+        assert_eq!(uris, ["types.ts", "types.zod.ts"]);
+        stale_outputs.push("types.ts", stale_lines("", generated));
+        let error = stale_outputs.report(CheckFormat::Human).unwrap_err();
+        assert_eq!(error.to_string(), "1 generated output(s) are stale");
+        assert_eq!(errors::exit_code(&error), 1);
+        assert!(stale_outputs.report(CheckFormat::Human).is_ok());
+    }
+
+    #[test]
+    fn reports_schema_changes_at_their_declaration() {
+        let old = "type DefaultType = {\n  id: number;\n  note: string;\n};\n";
+        let new = "type DefaultType = {\n  id: string;\n  tag?: string;\n};\n";
+        let (old, _) = typescript_parser::parse_schema_lines(old).unwrap();
+        let (new, lines) = typescript_parser::parse_schema_lines(new).unwrap();
+        let changes = old.changes_to(&new);
+        assert_eq!(
+            render_changes("types.ts", &changes, &lines, CheckFormat::Human),
+            "[breaking] ~ $.id: number -> string (type changed)\n[breaking] - $.note: string (field removed)\n[non-breaking] + $.tag: string (optional) (optional field added)\n2 breaking change(s) found\n"
+        );
+        let log: serde_json::Value = serde_json::from_str(&render_changes(
+            "types.ts",
+            &changes,
+            &lines,
+            CheckFormat::Sarif,
+        ))
+        .unwrap();
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        let result = |index: usize, field: &str| results[index][field].clone();
+        assert_eq!(
+            [result(0, "ruleId"), result(0, "level")],
+            ["breaking-schema-change", "error"]
+        );
+        assert_eq!(
+            [result(2, "ruleId"), result(2, "level")],
+            ["non-breaking-schema-change", "note"]
+        );
+        let start_lines: Vec<_> = results
+            .iter()
+            .map(|result| result["locations"][0]["physicalLocation"]["region"]["startLine"].clone())
+            .collect();
+        assert_eq!(start_lines, [2, 1, 3]);
+        assert_eq!(
+            results[0]["message"]["text"],
+            "~ $.id: number -> string (type changed)"
+        );
+    }
+}
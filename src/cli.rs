@@ -0,0 +1,2389 @@
+//! The `myrrh-rs` command line
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::sync::Arc;
+use tracing::{event, span, Level};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use crate::check::{CheckFormat, StaleOutputs};
+use crate::csharp::{self, CSharpStyle};
+use crate::diagnostics::Diagnostics;
+use crate::errors::{self, ErrorKind};
+use crate::formats::{BigIntMode, FallbackType, NumberMode, StringFormat, StringFormatMode};
+use crate::java::{self, JavaStyle};
+use crate::json_path::JsonPath;
+use crate::naming::{NamingStrategy, TypeNameTemplate, TypeNamer};
+use crate::overrides::TypeOverride;
+use crate::path_filter::PathFilter;
+use crate::progress::Progress;
+use crate::python::{self, PythonStyle};
+use crate::rename::{KeyCase, OriginalNames};
+use crate::schema::{Compatibility, FieldSchema, Violation};
+use crate::typescript_node::{
+    BraceStyle, DeclarationStyle, FormatOptions, RenderOptions, TypeScriptNode,
+};
+use crate::{
+    asserts, avro, bench, check, compare, config, decompress, factories, fetch, flow, formats,
+    graphql, guards, highlight, html, ir, is_type_name, json_schema, lsp, normalizers, openapi,
+    parse_cbor, parse_csv, parse_json5, parse_msgpack, parse_ndjson, parse_toml, poll, postformat,
+    proto, provenance, rename, render_types, report, schema, stages, stats, swift,
+    typescript_parser, walk_value_tree, walk_value_tree_with_options, watch, zod, Context,
+    InferOptions, KeyOrder, Result, SampleStrategy,
+};
+
+/// Input file name meaning stdin
+const STDIN: &str = "-";
+
+/// Infers TypeScript types, and schemas, from JSON samples
+#[derive(Parser, Debug)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// Without a subcommand, the flags of `generate`
+    #[clap(flatten)]
+    args: Args,
+
+    /// Log more to stderr: `-v` for what is read and written, `-vv` to debug. Only warnings
+    /// are logged otherwise.
+    #[clap(short = 'v', long = "verbose", action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only log errors
+    #[clap(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// How a failure is printed to stderr, `json` for scripts telling the kinds of failure
+    /// apart
+    #[clap(
+        long = "error-format",
+        value_enum,
+        default_value = "human",
+        global = true
+    )]
+    error_format: ErrorFormat,
+}
+
+impl Cli {
+    /// The most detailed level logged
+    fn log_level(&self) -> Level {
+        match (self.quiet, self.verbose) {
+            (true, _) => Level::ERROR,
+            (false, 0) => Level::WARN,
+            (false, 1) => Level::INFO,
+            (false, 2) => Level::DEBUG,
+            (false, _) => Level::TRACE,
+        }
+    }
+}
+
+/// The flags of `generate`
+#[derive(clap::Args, Clone, Debug)]
+struct Args {
+    /// Run the targets declared in this config instead, `myrrh.toml` or `.myrrhrc.json` in the
+    /// working directory are run when there is no input and stdin is a terminal
+    #[clap(
+        long = "config",
+        conflicts_with_all = ["input_files", "from_ir", "watch_url"]
+    )]
+    config: Option<String>,
+
+    /// Input JSON file or glob, `-` or omitted to read stdin. Repeat it to merge several
+    /// samples into one type, with fields missing from some of them marked optional
+    #[clap(
+        short = 'i',
+        long = "input",
+        value_parser,
+        conflicts_with = "watch_url"
+    )]
+    input_files: Vec<String>,
+
+    /// Output file, the generated types are printed to stdout when omitted
+    #[clap(short = 'o', long = "output", value_parser)]
+    output_file: Option<String>,
+
+    #[clap(short = 's', long = "squash", value_parser)]
+    squash_common_types: Option<bool>,
+
+    /// Read the input as JSON Lines, one document per line, merging every line into one type
+    #[clap(long = "ndjson")]
+    ndjson: bool,
+
+    /// Show a progress bar on stderr, with the bytes read, the samples walked and the time
+    /// taken so far
+    #[clap(long = "progress")]
+    progress: bool,
+
+    /// Syntax of the input files, `json5` also accepting JSONC with its comments and trailing
+    /// commas, and `toml` reading configs like a `Cargo.toml`. `csv` and `tsv` type the rows of a
+    /// table, named by its header row. `msgpack` and `cbor` decode binary documents. `openapi` reads OpenAPI 3 documents, in JSON, declaring their schema components
+    /// and response bodies.
+    #[clap(long = "format", value_enum, default_value_t = InputFormat::Json, conflicts_with = "ndjson")]
+    format: InputFormat,
+
+    /// What to emit: object types as `type` aliases or `interface` declarations, Zod schemas
+    /// with their inferred types, a JSON Schema, Python classes, an HTML report of the
+    /// fields for review, or the inferred tree itself as JSON, to be rendered with `--from-ir`.
+    /// Several targets, `--emit ts,zod,json-schema`, are rendered from one inference into
+    /// `--out-dir`
+    #[clap(
+        long = "emit",
+        alias = "target",
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [Emit::Type]
+    )]
+    emits: Vec<Emit>,
+
+    /// Write the output of every `--emit` target to this directory, named after the root type
+    /// with the target's extension: `DefaultType.d.ts`, `DefaultType.zod.ts`,
+    /// `DefaultType.schema.json`
+    #[clap(long = "out-dir", conflicts_with = "output_file")]
+    out_dir: Option<String>,
+
+    /// Render a tree saved with `--emit ir` instead of inferring one from input files
+    #[clap(
+        long = "from-ir",
+        conflicts_with_all = ["input_files", "watch_url", "ndjson", "format"]
+    )]
+    from_ir: Option<String>,
+
+    /// How `--emit python` declares the classes
+    #[clap(long = "python-style", value_enum, default_value_t = PythonStyle::TypedDict)]
+    python_style: PythonStyle,
+
+    /// How `--emit csharp` declares the types
+    #[clap(long = "csharp-style", value_enum, default_value_t = CSharpStyle::Record)]
+    csharp_style: CSharpStyle,
+
+    /// How `--emit java` declares the types
+    #[clap(long = "java-style", value_enum, default_value_t = JavaStyle::Record)]
+    java_style: JavaStyle,
+
+    /// Also write a `.myrrh.json` field schema next to the output file
+    #[clap(long = "sidecar", requires = "output_file")]
+    sidecar: bool,
+
+    /// Also write a `.provenance.json` file mapping every type and field to the JSON pointers
+    /// it was inferred from
+    #[clap(long = "provenance", requires = "output_file")]
+    provenance: bool,
+
+    /// Name of the root type, common types are named after it with a `_N` suffix
+    #[clap(long = "root-name", default_value = "DefaultType", value_parser = parse_root_name)]
+    root_name: String,
+
+    /// How the common types are named
+    #[clap(long = "naming", value_enum, default_value_t = NamingStrategy::Sequential)]
+    naming: NamingStrategy,
+
+    /// Name the common types after this template instead of `--naming`: `{Root}` the root name,
+    /// `{Path}` the fields leading to the type, `{Name}` the name `--naming path-based` would
+    /// give it, `{N}` its number and `{Hash}` a digest of its shape, e.g. `{Name}Dto`
+    #[clap(long = "type-name-template", conflicts_with = "naming")]
+    type_name_template: Option<TypeNameTemplate>,
+
+    /// Keep object types with at most this many fields inline, however often they're seen
+    #[clap(long = "inline-max-fields")]
+    inline_max_fields: Option<usize>,
+
+    /// Declare an object type of its own only once it has been seen this many times
+    #[clap(long = "extract-min-occurrences", default_value_t = 2, value_parser = parse_min_occurrences)]
+    extract_min_occurrences: usize,
+
+    /// The order object fields are declared in
+    #[clap(long = "key-order", value_enum, default_value_t = KeyOrder::Sorted)]
+    key_order: KeyOrder,
+
+    /// Type string fields which only take a few distinct values, at most the given number, as
+    /// a union of those literals. A value has to be seen more than once for this to apply.
+    #[clap(long = "literals", num_args = 0..=1, default_missing_value = "8")]
+    literals: Option<usize>,
+
+    /// Type strings holding ISO 8601 dates and timestamps as `comment` (`string /* ISO date */`),
+    /// `Date`, or a branded string type with the given name
+    #[clap(long = "date-type", value_parser = formats::DateType::parse)]
+    date_type: Option<formats::DateType>,
+
+    /// Recognise strings holding UUIDs, emails, URLs and IP addresses, typed as a branded type
+    /// per format (`UUID`, `Email`, `Url`, `IPv4`, `IPv6`) or as `string` documented with
+    /// `/** @format uuid */`
+    #[clap(long = "string-formats", value_enum)]
+    string_formats: Option<StringFormatMode>,
+
+    /// Type long strings holding base64 encoded data as an alias of `string` with the given
+    /// name, documented as needing decoding
+    #[clap(long = "base64-type", num_args = 0..=1, default_missing_value = "Base64String", value_parser = parse_root_name)]
+    base64_type: Option<String>,
+
+    /// Type numbers which were only ever integers as plain `number`, a branded `Integer` type
+    /// or `number` with a `/** @type {integer} */` comment
+    #[clap(long = "number-mode", value_enum, default_value_t = NumberMode::Number)]
+    number_mode: NumberMode,
+
+    /// Type integers beyond `Number.MAX_SAFE_INTEGER`, which a `number` can't hold exactly, as
+    /// `number`, `bigint` or `string`, matching how the JSON is parsed
+    #[clap(long = "bigint-mode", value_enum, default_value_t = BigIntMode::Number)]
+    bigint_mode: BigIntMode,
+
+    /// Describe at most the given number of levels of objects and arrays, counting the root,
+    /// and type any nested deeper with the fallback type
+    #[clap(long = "max-depth")]
+    max_depth: Option<usize>,
+
+    /// Field tagging the objects of arrays with their kind, so objects with different tags are
+    /// typed as a tagged union rather than one object with optional fields. Objects of
+    /// different shapes are tagged by `type`, `kind`, `__typename`, `tag` or `event` without it.
+    #[clap(long = "discriminator")]
+    discriminator: Option<String>,
+
+    /// Walk at most this many elements of every array, so a huge array is typed from a sample
+    /// of it. A warning lists the arrays sampled and how many elements they skipped.
+    #[clap(long = "sample", value_parser = clap::value_parser!(u64).range(1..))]
+    sample: Option<u64>,
+
+    /// Which elements `--sample` walks, the first ones or ones picked at random, the same every
+    /// run, from across the whole array
+    #[clap(long = "sample-strategy", value_enum, default_value_t = SampleStrategy::First, requires = "sample")]
+    sample_strategy: SampleStrategy,
+
+    /// Force the type of the values at a JSON path, whatever the samples hold, e.g.
+    /// `'$.items[*].id=string'` or `'$..note=string | null'`. Repeatable, the last one given
+    /// winning where several apply.
+    #[clap(long = "override", value_name = "PATH=TYPE")]
+    overrides: Vec<TypeOverride>,
+
+    /// Drop the values at a JSON path from every sample before inferring the types, e.g.
+    /// `'$.debug'` or `'$.._internal'`. Repeatable.
+    #[clap(long = "exclude", value_name = "PATH")]
+    exclude: Vec<JsonPath>,
+
+    /// Keep only the values at a JSON path, and the objects and arrays leading to them, e.g.
+    /// `'$.items[*].id'`. Repeatable, `--exclude` still dropping values within them.
+    #[clap(long = "include", value_name = "PATH")]
+    include: Vec<JsonPath>,
+
+    /// The type of values which can't be described, those below `--max-depth` and the elements
+    /// of arrays which were only ever empty: `unknown`, `any`, or a `JsonValue` type declared
+    /// alongside the types
+    #[clap(long = "fallback-type", value_enum, default_value_t = FallbackType::Unknown)]
+    fallback_type: FallbackType,
+
+    /// The type of arrays which were only ever empty, e.g. `never[]`, an array of the fallback
+    /// type when not given
+    #[clap(long = "empty-array-type")]
+    empty_array_type: Option<String>,
+
+    /// Prefix every declaration with `export`
+    #[clap(long = "export")]
+    export: bool,
+
+    /// Spaces per level of indentation
+    #[clap(long = "indent", default_value_t = 2)]
+    indent: usize,
+
+    /// Indent with tabs instead of spaces
+    #[clap(long = "tabs")]
+    tabs: bool,
+
+    /// Leave out the semicolons ending members and type aliases
+    #[clap(long = "no-semicolons")]
+    no_semicolons: bool,
+
+    /// Where the brace opening the body of a declaration goes
+    #[clap(long = "brace-style", value_enum, default_value_t = BraceStyle::SameLine)]
+    brace_style: BraceStyle,
+
+    /// Document every property with up to two of the values sampled for it, `/** e.g. "paid" */`
+    #[clap(long = "examples")]
+    examples: bool,
+
+    /// Declare every property `readonly` and every array `readonly T[]`
+    #[clap(long = "readonly")]
+    readonly: bool,
+
+    /// Type fields which were only ever null as `unknown`, and flag every field whose type
+    /// couldn't be worked out from the samples, empty arrays and all, with a `// TODO` comment
+    /// saying why
+    #[clap(long = "strict", conflicts_with = "fallback_type")]
+    strict: bool,
+
+    /// Header comment written above the generated code, e.g. "generated by myrrh, do not edit"
+    #[clap(long = "banner")]
+    banner: Option<String>,
+
+    /// Type fields which were seen as `null` as optional, `field?: T`, rather than `field: T | null`
+    #[clap(long = "null-as-optional")]
+    null_as_optional: bool,
+
+    /// Emit an `isDefaultType` runtime type guard alongside the types
+    #[clap(long = "guards")]
+    guards: bool,
+
+    /// Emit a `parseDefaultType` function throwing errors which name the failing path
+    #[clap(long = "asserts")]
+    asserts: bool,
+
+    /// Emit a `mockDefaultType` factory seeded with the sampled values
+    #[clap(long = "factories")]
+    factories: bool,
+
+    /// Rename properties to the given convention
+    #[clap(long = "rename", value_enum)]
+    rename: Option<KeyCase>,
+
+    /// How the wire names of renamed properties are kept: a JSDoc `@originalName` on each, or
+    /// the wire format emitted as `RawDefaultType` with `toDefaultType` / `fromDefaultType`
+    /// converters
+    #[clap(long = "original-names", value_enum, requires = "rename")]
+    original_names: Option<OriginalNames>,
+
+    /// Shorthand for `--rename camelCase --original-names converters`
+    #[clap(long = "camel-case", conflicts_with = "rename")]
+    camel_case: bool,
+
+    /// With a directory `--input`, write the types of every JSON file in it to one file instead
+    /// of one file per JSON file
+    #[clap(long = "bundle")]
+    bundle: bool,
+
+    /// Write every declared type to a file of its own in the `--output` directory, `Name.ts`,
+    /// importing the types it refers to, with an `index.ts` re-exporting them all
+    #[clap(long = "split-types", requires = "output_file")]
+    split_types: bool,
+
+    /// Pipe the output through this shell command before writing it, e.g.
+    /// `prettier --stdin-filepath {file}`, `{file}` standing for the path written to
+    #[clap(long = "format-cmd")]
+    format_cmd: Option<String>,
+
+    /// Print a JSON report on stdout instead of the output alone: the generated code, the
+    /// warnings logged, the shape of the inferred types and how long it took
+    #[clap(long = "report", value_enum, conflicts_with_all = ["check", "dry_run"])]
+    report: Option<ReportFormat>,
+
+    /// Print the output, highlighted on a terminal, and which files would be created or
+    /// updated, without writing anything
+    #[clap(long = "dry-run", conflicts_with = "check")]
+    dry_run: bool,
+
+    /// Compare the generated output against the existing output file instead of writing it,
+    /// exiting with status 1 once every target is compared, listing the lines which differ in
+    /// each stale one
+    #[clap(long = "check", conflicts_with_all = ["watch", "watch_url"])]
+    check: bool,
+
+    /// How `--check` and `check` report stale output: plain text, GitHub Actions `::error`
+    /// annotations or a SARIF log
+    #[clap(long = "check-format", value_enum, default_value_t = CheckFormat::Human)]
+    check_format: CheckFormat,
+
+    /// Regenerate the output whenever an input file changes
+    #[clap(long = "watch", requires = "input_files", requires = "output_file")]
+    watch: bool,
+
+    /// Poll an endpoint instead of reading an input file, merging every response into the types
+    #[clap(long = "watch-url", value_parser)]
+    watch_url: Option<String>,
+
+    /// How often to poll `--watch-url`, e.g. `500ms`, `60s`, `5m`
+    #[clap(long = "interval", default_value = "60s", requires = "watch_url")]
+    interval: String,
+
+    /// Extra request header for URL inputs and `--watch-url`, `Name: value`
+    #[clap(short = 'H', long = "header", value_parser)]
+    headers: Vec<String>,
+
+    /// The weak spots of the types, logged at the end of the run or listed by `--report json`
+    #[clap(skip)]
+    diagnostics: Arc<Diagnostics>,
+
+    /// The outputs `--check` found stale, reported once every target is compared
+    #[clap(skip)]
+    stale_outputs: Arc<StaleOutputs>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ReportFormat {
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum InputFormat {
+    Json,
+    #[value(alias = "jsonc")]
+    Json5,
+    Toml,
+    Csv,
+    Tsv,
+    #[value(alias = "messagepack")]
+    Msgpack,
+    Cbor,
+    Openapi,
+}
+
+impl InputFormat {
+    /// The delimiter of the tabular formats, whose every row is a sample
+    fn delimiter(self) -> Option<u8> {
+        match self {
+            InputFormat::Csv => Some(b','),
+            InputFormat::Tsv => Some(b'\t'),
+            _ => None,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Emit {
+    #[value(aliases = ["typescript", "ts"])]
+    Type,
+    Interface,
+    Html,
+    Zod,
+    JsonSchema,
+    Python,
+    Graphql,
+    Proto,
+    Avro,
+    Csharp,
+    Swift,
+    Java,
+    Flow,
+    Ir,
+}
+
+impl Emit {
+    /// Extension of the files generated per input in directory mode, declaration files unless
+    /// they hold functions
+    fn extension(&self, has_functions: bool) -> &'static str {
+        match self {
+            Emit::Type | Emit::Interface if has_functions => "ts",
+            Emit::Type | Emit::Interface => "d.ts",
+            Emit::Zod => "ts",
+            Emit::JsonSchema => "schema.json",
+            Emit::Python => "py",
+            Emit::Graphql => "graphql",
+            Emit::Proto => "proto",
+            Emit::Avro => "avsc",
+            Emit::Csharp => "cs",
+            Emit::Swift => "swift",
+            Emit::Java => "java",
+            Emit::Flow => "js",
+            Emit::Html => "html",
+            Emit::Ir => "ir.json",
+        }
+    }
+
+    /// Name of the file written to `--out-dir`, the Zod schemas kept apart from the types
+    /// they'd share an extension with
+    fn file_name(&self, root_name: &str, has_functions: bool) -> String {
+        match self {
+            Emit::Zod => format!("{}.zod.ts", root_name),
+            _ => format!("{}.{}", root_name, self.extension(has_functions)),
+        }
+    }
+}
+
+fn parse_root_name(name: &str) -> std::result::Result<String, String> {
+    if is_type_name(name) {
+        Ok(name.to_string())
+    } else {
+        Err(format!("`{}` is not a valid TypeScript type name", name))
+    }
+}
+
+fn parse_min_occurrences(occurrences: &str) -> std::result::Result<usize, String> {
+    match occurrences.parse::<usize>() {
+        Ok(occurrences) if occurrences >= 2 => Ok(occurrences),
+        Ok(_) => Err("an object type seen once is always kept inline, give 2 or more".to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+impl Args {
+    /// The target emitted, the first when there are several
+    fn emit(&self) -> Emit {
+        self.emits[0]
+    }
+
+    /// Whether runtime functions are emitted alongside the types
+    fn has_functions(&self) -> bool {
+        self.guards || self.asserts || self.factories || self.converters()
+    }
+
+    /// The convention properties are renamed to
+    fn key_case(&self) -> Option<KeyCase> {
+        if self.camel_case {
+            Some(KeyCase::CamelCase)
+        } else {
+            self.rename
+        }
+    }
+
+    /// The `--header`s sent with requests
+    fn request_headers(&self) -> Result<Vec<(String, String)>> {
+        self.headers
+            .iter()
+            .map(|header| fetch::parse_header(header))
+            .collect()
+    }
+
+    /// Whether the wire format is emitted alongside the renamed types, with converters
+    fn converters(&self) -> bool {
+        self.camel_case || self.original_names == Some(OriginalNames::Converters)
+    }
+
+    fn infer_options(&self) -> InferOptions {
+        InferOptions {
+            detect_dates: self.date_type.is_some(),
+            detect_string_formats: self.string_formats.is_some(),
+            detect_base64: self.base64_type.is_some(),
+            detect_integers: self.number_mode != NumberMode::Number
+                || matches!(
+                    self.emit(),
+                    Emit::Python
+                        | Emit::Graphql
+                        | Emit::Proto
+                        | Emit::Avro
+                        | Emit::Csharp
+                        | Emit::Swift
+                        | Emit::Java
+                ),
+            key_order: self.key_order,
+            max_depth: self.max_depth,
+            discriminator: self.discriminator.clone(),
+            sample: self.sample.map(|sample| sample as usize),
+            sample_strategy: self.sample_strategy,
+            overrides: self.overrides.clone(),
+            diagnostics: Some(Arc::clone(&self.diagnostics)),
+        }
+    }
+
+    fn path_filter(&self) -> PathFilter<'_> {
+        PathFilter {
+            include: &self.include,
+            exclude: &self.exclude,
+        }
+    }
+
+    fn render_options(&self, root_name: &str) -> RenderOptions {
+        RenderOptions {
+            root_name: root_name.to_string(),
+            naming: self.naming,
+            type_namer: self
+                .type_name_template
+                .clone()
+                .map(|template| Arc::new(template) as Arc<dyn TypeNamer>),
+            date_type: self.date_type.clone(),
+            string_formats: self.string_formats,
+            base64_type: self.base64_type.clone(),
+            number_mode: self.number_mode,
+            bigint_mode: self.bigint_mode,
+            export: self.export,
+            original_names: self.original_names == Some(OriginalNames::Jsdoc),
+            examples: self.examples,
+            readonly: self.readonly,
+            strict: self.strict,
+            diagnostics: Some(Arc::clone(&self.diagnostics)),
+            fallback_type: self.fallback_type,
+            empty_array_type: self.empty_array_type.clone(),
+            inline_max_fields: self.inline_max_fields,
+            extract_min_occurrences: self.extract_min_occurrences,
+            // the root describes a row, the table is a list of them
+            list_alias: self.format.delimiter().is_some()
+                && matches!(self.emit(), Emit::Type | Emit::Interface),
+            format: FormatOptions {
+                indent_width: self.indent,
+                use_tabs: self.tabs,
+                semicolons: !self.no_semicolons,
+                brace_style: self.brace_style,
+            },
+            declaration_style: match self.emit() {
+                Emit::Interface => DeclarationStyle::Interface,
+                Emit::Type
+                | Emit::Html
+                | Emit::Zod
+                | Emit::JsonSchema
+                | Emit::Python
+                | Emit::Graphql
+                | Emit::Proto
+                | Emit::Avro
+                | Emit::Csharp
+                | Emit::Swift
+                | Emit::Java
+                | Emit::Flow
+                | Emit::Ir => DeclarationStyle::TypeAlias,
+            },
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Infer types from JSON samples, or run the targets declared in the config. The flags can
+    /// also be given without the subcommand.
+    Generate(Args),
+    /// Infer a JSON Schema from JSON samples, `generate --emit json-schema`
+    Schema(Args),
+    /// Classify the changes between two schema versions, failing on breaking changes
+    #[clap(visible_alias = "compat")]
+    Diff(DiffArgs),
+    /// Regenerate the types and fail if the existing output file is stale
+    Check(CheckArgs),
+    /// Merge several samples into one type, or report how their fields overlap
+    #[clap(visible_alias = "compare")]
+    Merge(MergeArgs),
+    /// Report field level changes between a generated file and a fresh sample
+    Drift(DriftArgs),
+    /// Fetch an endpoint and validate its responses against a stored schema
+    Verify(VerifyArgs),
+    /// Check JSON payloads against a stored schema, reporting the paths which don't match
+    Validate(ValidateArgs),
+    /// Regenerate the types of every fixture in the config's profiles
+    Workspace(WorkspaceArgs),
+    /// Answer requests for types from an editor, as a language server over stdin and stdout
+    Lsp(LspArgs),
+    /// Summarise the model inferred from JSON samples, to help pick squashing and extraction
+    /// thresholds
+    Stats(StatsArgs),
+    /// Time each stage of inferring and rendering the types of an input
+    #[clap(hide = true)]
+    Bench(BenchArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct BenchArgs {
+    /// The JSON input to time
+    input: String,
+
+    /// How many times each stage is run
+    #[clap(long = "iterations", default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+}
+
+#[derive(clap::Args, Debug)]
+struct StatsArgs {
+    /// JSON samples, merged into one model
+    #[clap(required = true)]
+    inputs: Vec<String>,
+
+    /// How many of the most common field names to list
+    #[clap(long = "top", default_value_t = 10)]
+    top: usize,
+}
+
+#[derive(clap::Args, Debug)]
+struct LspArgs {
+    /// Talk over stdin and stdout, the only transport, accepted as editors pass it
+    #[clap(long = "stdio")]
+    stdio: bool,
+}
+
+/// The flags of `generate`, so the output is regenerated just as it was written
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    #[clap(flatten)]
+    args: Args,
+}
+
+#[derive(clap::Args, Debug)]
+struct DriftArgs {
+    #[clap(short = 'i', long = "input", value_parser)]
+    input_file: String,
+
+    /// Previously generated types, or their `.myrrh.json` sidecar
+    #[clap(long = "against", value_parser)]
+    against: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct DiffArgs {
+    /// Previous schema: a JSON sample, an `--emit ir` tree, generated types or a `.myrrh.json`
+    /// sidecar
+    old: String,
+
+    /// New schema: a JSON sample, an `--emit ir` tree, generated types or a `.myrrh.json`
+    /// sidecar
+    new: String,
+
+    /// How the changes are reported, annotating the lines of the new schema which declare the
+    /// fields when it is generated types
+    #[clap(long = "format", value_enum, default_value_t = CheckFormat::Human)]
+    format: CheckFormat,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyArgs {
+    #[clap(long = "url", value_parser)]
+    url: String,
+
+    /// Stored schema: an `--emit ir` tree, a `.myrrh.json` sidecar or generated types
+    #[clap(long = "schema", value_parser)]
+    schema: String,
+
+    /// Number of times to fetch the endpoint
+    #[clap(short = 'n', long = "count", default_value_t = 1)]
+    count: usize,
+
+    /// Extra request header, `Name: value`
+    #[clap(short = 'H', long = "header", value_parser)]
+    headers: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// Stored schema: an `--emit ir` tree, a `.myrrh.json` sidecar or generated types
+    #[clap(long = "schema", value_parser)]
+    schema: String,
+
+    /// JSON payloads to check, `-` for stdin
+    #[clap(required = true)]
+    inputs: Vec<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct MergeArgs {
+    /// JSON samples to compare
+    #[clap(required = true, num_args = 2..)]
+    inputs: Vec<String>,
+
+    /// Report the fields common to all samples, unique to each and with disagreeing types
+    /// instead of printing the merged types
+    #[clap(long = "report")]
+    report: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct WorkspaceArgs {
+    #[clap(long = "config", default_value = "myrrh.toml")]
+    config: String,
+}
+
+pub fn main() -> std::process::ExitCode {
+    STARTED.get_or_init(std::time::Instant::now);
+    let cli = Cli::parse();
+    let error_format = cli.error_format;
+    match run_cli(cli) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(error) => {
+            match error_format {
+                ErrorFormat::Human => eprintln!("Error: {:?}", error),
+                ErrorFormat::Json => eprintln!("{}", errors::to_json(&error)),
+            }
+            std::process::ExitCode::from(errors::exit_code(&error))
+        }
+    }
+}
+
+/// Runs the subcommand, or `generate` without one
+fn run_cli(cli: Cli) -> Result<()> {
+    // logs go to stderr so the generated types can be piped from stdout, the warnings are also
+    // kept for `--report json`
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(LevelFilter::from_level(cli.log_level())),
+        )
+        .with(report::WarningLayer)
+        .init();
+
+    let span = span!(Level::INFO, "parsing");
+
+    let _enter = span.enter();
+
+    match cli.command {
+        Some(Command::Generate(args)) => run_generate(&args),
+        Some(Command::Schema(mut args)) => {
+            args.emits = vec![Emit::JsonSchema];
+            run_generate(&args)
+        }
+        Some(Command::Diff(diff_args)) => run_diff(diff_args),
+        Some(Command::Check(check_args)) => run_check(check_args),
+        Some(Command::Merge(merge_args)) => run_merge(merge_args),
+        Some(Command::Drift(drift_args)) => run_drift(drift_args),
+        Some(Command::Verify(verify_args)) => run_verify(verify_args),
+        Some(Command::Validate(validate_args)) => run_validate(validate_args),
+        Some(Command::Workspace(workspace_args)) => run_workspace(workspace_args),
+        Some(Command::Stats(stats_args)) => run_stats(stats_args),
+        Some(Command::Bench(bench_args)) => run_bench(bench_args),
+        Some(Command::Lsp(_)) => lsp::serve(std::io::stdin().lock(), std::io::stdout().lock()),
+        None => run_generate(&cli.args),
+    }
+}
+
+/// Generates the types as the flags describe
+fn run(args: &Args) -> Result<()> {
+    let targets = plan_outputs(args)?;
+    for target in &targets {
+        check_flags(target)?;
+    }
+    if args.out_dir.is_some()
+        && matches!(args.input_files.as_slice(), [input] if std::path::Path::new(input).is_dir())
+    {
+        anyhow::bail!(ErrorKind::Usage
+            .because("--out-dir writes the types of one tree, pass --output for a directory"));
+    }
+    if targets.len() > 1
+        && (args.watch_url.is_some()
+            || args.report.is_some()
+            || args.format == InputFormat::Openapi)
+    {
+        anyhow::bail!(ErrorKind::Usage.because("several --emit targets can't be combined with --watch-url, --report or --format openapi"));
+    }
+    let args = match targets.as_slice() {
+        [target] => target,
+        _ => args,
+    };
+
+    if let Some(url) = &args.watch_url {
+        if args.emit() == Emit::Html {
+            anyhow::bail!(ErrorKind::Usage.because("--emit html needs an input file"));
+        }
+        return run_watch_url(args, url);
+    }
+
+    if args.watch {
+        return run_watch(args);
+    }
+
+    run_once(args)?;
+    args.diagnostics.log();
+    args.stale_outputs.report(args.check_format)
+}
+
+/// The combinations of flags which can't be rendered, for one `--emit` target
+fn check_flags(args: &Args) -> Result<()> {
+    if matches!(
+        args.emit(),
+        Emit::JsonSchema
+            | Emit::Python
+            | Emit::Graphql
+            | Emit::Proto
+            | Emit::Avro
+            | Emit::Csharp
+            | Emit::Swift
+            | Emit::Java
+            | Emit::Flow
+            | Emit::Ir
+    ) && args.has_functions()
+    {
+        anyhow::bail!(ErrorKind::Usage.because(format!("--emit {} can't be combined with --guards, --asserts, --factories, --camel-case or --original-names converters",
+            args.emit().to_possible_value().unwrap().get_name())));
+    }
+    if matches!(
+        args.emit(),
+        Emit::JsonSchema | Emit::Avro | Emit::Html | Emit::Ir
+    ) && args.banner.is_some()
+    {
+        anyhow::bail!(ErrorKind::Usage.because("--banner needs TypeScript output"));
+    }
+    if args.split_types
+        && (!matches!(args.emit(), Emit::Type | Emit::Interface)
+            || args.has_functions()
+            || args.sidecar
+            || args.provenance
+            || args.check
+            || args.format == InputFormat::Openapi)
+    {
+        anyhow::bail!(ErrorKind::Usage.because(
+            "--split-types only splits declared types, with `--emit type` or `--emit interface`"
+        ));
+    }
+    if args.emit() == Emit::Ir && (args.sidecar || args.provenance) {
+        anyhow::bail!(ErrorKind::Usage
+            .because("--sidecar and --provenance describe rendered types, not --emit ir"));
+    }
+    if args.format == InputFormat::Openapi
+        && (!matches!(args.emit(), Emit::Type | Emit::Interface)
+            || args.has_functions()
+            || args.sidecar
+            || args.provenance
+            || args.bundle
+            || args.watch_url.is_some())
+    {
+        anyhow::bail!(ErrorKind::Usage.because(
+            "--format openapi only declares types, with `--emit type` or `--emit interface`"
+        ));
+    }
+    if args.from_ir.is_some() && args.emit() == Emit::Html {
+        anyhow::bail!(
+            ErrorKind::Usage.because("--emit html needs the samples, which the IR doesn't keep")
+        );
+    }
+    Ok(())
+}
+
+/// The flags of every `--emit` target, naming one target each and the file in `--out-dir` it
+/// is written to
+fn plan_outputs(args: &Args) -> Result<Vec<Args>> {
+    let out_dir = match &args.out_dir {
+        Some(out_dir) => std::path::Path::new(out_dir),
+        None if args.emits.len() > 1 => {
+            anyhow::bail!(ErrorKind::Usage
+                .because("several --emit targets need an --out-dir to write them to"))
+        }
+        None => return Ok(vec![args.clone()]),
+    };
+    let mut targets: Vec<Args> = Vec::new();
+    for &emit in &args.emits {
+        let output_file = out_dir
+            .join(emit.file_name(&args.root_name, args.has_functions()))
+            .to_string_lossy()
+            .into_owned();
+        if let Some(target) = targets
+            .iter()
+            .find(|target| target.output_file.as_ref() == Some(&output_file))
+        {
+            anyhow::bail!(ErrorKind::Usage.because(format!(
+                "--emit {} and --emit {} would both write `{}`",
+                target.emit().to_possible_value().unwrap().get_name(),
+                emit.to_possible_value().unwrap().get_name(),
+                output_file
+            )));
+        }
+        targets.push(Args {
+            emits: vec![emit],
+            output_file: Some(output_file),
+            ..args.clone()
+        });
+    }
+    Ok(targets)
+}
+
+/// Generates the types, or runs the targets of the config when there is nothing else to read
+fn run_generate(args: &Args) -> Result<()> {
+    let config_path = match &args.config {
+        Some(config) => config.clone(),
+        None if args.input_files.is_empty()
+            && args.from_ir.is_none()
+            && args.watch_url.is_none()
+            && std::io::stdin().is_terminal() =>
+        {
+            match config::DEFAULT_CONFIGS
+                .iter()
+                .find(|config| std::path::Path::new(config).exists())
+            {
+                Some(config) => config.to_string(),
+                None => return run(args),
+            }
+        }
+        None => return run(args),
+    };
+    run_targets(&config_path, args.dry_run)
+}
+
+/// Runs the targets of the config in the order they are declared, only previewing their output
+/// with `dry_run`
+fn run_targets(config_path: &str, dry_run: bool) -> Result<()> {
+    let config = config::Config::read(std::path::Path::new(&config_path))?;
+    if config.targets.is_empty() {
+        anyhow::bail!(ErrorKind::Config.because(format!("`{}` declares no targets", config_path)));
+    }
+    for target in &config.targets {
+        let output = target.output.display().to_string();
+        let cli =
+            Cli::try_parse_from(std::iter::once("myrrh".to_string()).chain(target.command_line()))
+                .with_context(|| {
+                    ErrorKind::Config.because(format!("invalid target `{}`", output))
+                })?;
+        let mut args = cli.args;
+        args.dry_run |= dry_run;
+        if let Some(parent) = target.output.parent().filter(|_| !args.dry_run) {
+            std::fs::create_dir_all(parent)
+                .context(ErrorKind::Output.because("could not create output directory"))?;
+        }
+        if args.config.is_some() {
+            anyhow::bail!(
+                ErrorKind::Config.because(format!("target `{}` can't run another config", output))
+            );
+        }
+        run(&args).with_context(|| format!("could not generate `{}`", output))?;
+    }
+    Ok(())
+}
+
+/// Infers the types of the inputs and writes them out
+fn run_once(args: &Args) -> Result<()> {
+    if args.format == InputFormat::Openapi {
+        return run_openapi(args);
+    }
+    if let [directory] = args.input_files.as_slice() {
+        if std::path::Path::new(directory).is_dir() {
+            return run_directory(args, std::path::Path::new(directory));
+        }
+    }
+    let input_files = expand_inputs(&args.input_files)?;
+    let progress =
+        (args.progress && args.from_ir.is_none()).then(|| Progress::new(total_size(&input_files)));
+    let inference = match &args.from_ir {
+        Some(ir_file) => Inference::from_ir(args, ir_file)?,
+        None => infer_inputs(args, &input_files, progress.as_ref())?,
+    };
+    if let Some(out_dir) = args
+        .out_dir
+        .as_ref()
+        .filter(|_| !args.dry_run && !args.check)
+    {
+        std::fs::create_dir_all(out_dir)
+            .context(ErrorKind::Output.because("could not create output directory"))?;
+    }
+    // every target is rendered from the one inference
+    for target in plan_outputs(args)? {
+        let output = inference.render(&target, &args.root_name)?;
+        write_output(&target, target.output_file.as_deref(), output)?;
+    }
+    Ok(())
+}
+
+/// Reads and merges the samples of every input file
+fn infer_inputs<'a>(
+    args: &'a Args,
+    input_files: &[String],
+    progress: Option<&'a Progress>,
+) -> Result<Inference<'a>> {
+    // each file is walked on its own, then the partial trees merged in input order
+    let inference = input_files
+        .par_iter()
+        .map(|input_file| {
+            let mut inference = Inference::new(args).with_progress(progress);
+            inference.read(input_file)?;
+            Ok(inference)
+        })
+        .collect::<Result<Vec<_>>>();
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+    Ok(inference?
+        .into_iter()
+        .reduce(Inference::merge)
+        .unwrap_or_else(|| Inference::new(args)))
+}
+
+/// Declares the schema components and response bodies of every OpenAPI document given
+fn run_openapi(args: &Args) -> Result<()> {
+    let headers = args.request_headers()?;
+    let mut types = String::new();
+    for input_file in expand_inputs(&args.input_files)? {
+        let spec: Value = errors::parse_json(&read_input_file(&input_file, &headers)?)
+            .context(ErrorKind::Parse.because("OpenAPI documents have to be JSON"))?;
+        let trees = openapi::type_trees(&spec).with_context(|| {
+            ErrorKind::Parse.because(format!("could not read OpenAPI document `{}`", input_file))
+        })?;
+        for (name, mut tree) in trees {
+            if args.key_order == KeyOrder::Sorted {
+                tree.sort_fields();
+            }
+            tree.calculate_hash();
+            let options = args.render_options(&name);
+            let declarations = render_types(&tree, &options);
+            if types.is_empty() {
+                types = declarations;
+            } else {
+                types.push('\n');
+                types.push_str(&without_shared_declarations(
+                    args,
+                    &options,
+                    &types,
+                    declarations,
+                ));
+            }
+        }
+    }
+    let output = RenderedOutput {
+        types,
+        field_schema: FieldSchema::default(),
+        provenance: None,
+        files: Vec::new(),
+        report: None,
+    };
+    write_output(args, args.output_file.as_deref(), output)
+}
+
+/// Generates the types of every JSON file in `directory`, each named after its file, into one
+/// file per JSON file or, with `--bundle`, a single file
+fn run_directory(args: &Args, directory: &std::path::Path) -> Result<()> {
+    if args.bundle && !matches!(args.emit(), Emit::Type | Emit::Interface | Emit::Zod) {
+        anyhow::bail!(ErrorKind::Usage.because("--bundle needs TypeScript output"));
+    }
+    if args.split_types {
+        anyhow::bail!(ErrorKind::Usage
+            .because("--split-types needs a single tree of types, not a directory of samples"));
+    }
+    if args.bundle && (args.sidecar || args.provenance) {
+        anyhow::bail!(ErrorKind::Usage
+            .because("--sidecar and --provenance need one output file per input, not --bundle"));
+    }
+    let mut fixtures = Vec::new();
+    config::collect_fixtures(directory, &mut fixtures)?;
+    fixtures.sort();
+    let output_directory = args
+        .output_file
+        .as_deref()
+        .map(std::path::Path::new)
+        .unwrap_or(directory);
+    let fixture_files: Vec<String> = fixtures
+        .iter()
+        .map(|fixture| fixture.to_string_lossy().into_owned())
+        .collect();
+    let progress = args
+        .progress
+        .then(|| Progress::new(total_size(&fixture_files)));
+    let outputs = fixtures
+        .par_iter()
+        .map(|fixture| {
+            let stem = fixture.file_stem().unwrap_or_default().to_string_lossy();
+            let root_name = rename::to_pascal_case(&stem);
+            if !is_type_name(&root_name) {
+                anyhow::bail!(ErrorKind::Input.because(format!(
+                    "can't name a type after `{}`, `{}` is not a valid TypeScript type name",
+                    fixture.display(),
+                    root_name
+                )));
+            }
+            let mut inference = Inference::new(args).with_progress(progress.as_ref());
+            inference.read(&fixture.to_string_lossy())?;
+            let output = inference.render(args, &root_name)?;
+            Ok((root_name, output))
+        })
+        .collect::<Result<Vec<_>>>();
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+    let outputs = outputs?;
+    let mut bundle = String::new();
+    for (fixture, (root_name, output)) in fixtures.iter().zip(outputs) {
+        if args.bundle {
+            if !bundle.is_empty() {
+                let options = args.render_options(&root_name);
+                bundle.push('\n');
+                bundle.push_str(&without_shared_declarations(
+                    args,
+                    &options,
+                    &bundle,
+                    output.types,
+                ));
+            } else {
+                bundle.push_str(&output.types);
+            }
+            continue;
+        }
+        let relative = fixture.strip_prefix(directory).unwrap_or(fixture);
+        let output_file = output_directory
+            .join(relative)
+            .with_extension(args.emit().extension(args.has_functions()));
+        if let Some(parent) = output_file.parent() {
+            std::fs::create_dir_all(parent)
+                .context(ErrorKind::Output.because("could not create output directory"))?;
+        }
+        write_output(args, Some(&output_file.to_string_lossy()), output)?;
+    }
+    let output = RenderedOutput {
+        types: bundle,
+        field_schema: FieldSchema::default(),
+        provenance: None,
+        files: Vec::new(),
+        report: None,
+    };
+    if args.bundle {
+        write_output(args, args.output_file.as_deref(), output)?;
+    }
+    Ok(())
+}
+
+/// Merges samples into one tree as they are read, so they needn't all be held in memory at
+/// once. The samples themselves are only kept for the HTML report.
+struct Inference<'a> {
+    args: &'a Args,
+    tree: Option<TypeScriptNode>,
+    samples: Vec<Value>,
+    progress: Option<&'a Progress>,
+}
+
+impl<'a> Inference<'a> {
+    fn new(args: &'a Args) -> Self {
+        Inference {
+            args,
+            tree: None,
+            samples: Vec::new(),
+            progress: None,
+        }
+    }
+
+    /// The tree saved with `--emit ir` in `ir_file`
+    fn from_ir(args: &'a Args, ir_file: &str) -> Result<Self> {
+        let tree = ir::from_ir_str(&read_input_file(ir_file, &args.request_headers()?)?)
+            .with_context(|| format!("could not read IR from `{}`", ir_file))?;
+        Ok(Inference {
+            tree: Some(tree),
+            ..Inference::new(args)
+        })
+    }
+
+    /// Reports what is read and walked to `progress`
+    fn with_progress(mut self, progress: Option<&'a Progress>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Merges in the samples of an input file, one per line with `--ndjson`, attributed to
+    /// where they came from
+    fn read(&mut self, input_file: &str) -> Result<()> {
+        let source_name = if input_file == STDIN {
+            "<stdin>"
+        } else {
+            input_file
+        };
+        let headers = self.args.request_headers()?;
+        let reader = open_input(input_file, &headers, self.progress)?;
+        if self.args.ndjson {
+            for sample in parse_ndjson(reader) {
+                let (line, sample) = sample?;
+                self.add(&format!("{}:{}", source_name, line), sample)?;
+            }
+            Ok(())
+        } else if let Some(delimiter) = self.args.format.delimiter() {
+            for row in parse_csv(reader, delimiter)? {
+                let (line, row) = row?;
+                self.add(&format!("{}:{}", source_name, line), row)?;
+            }
+            Ok(())
+        } else {
+            let sample = parse_input(input_file, self.args.format, reader)?;
+            self.add(source_name, sample)
+        }
+    }
+
+    /// Merges in the samples of another inference, which are taken to come after these
+    fn merge(mut self, other: Inference<'a>) -> Self {
+        self.tree = match (self.tree, other.tree) {
+            (Some(tree), Some(other_tree)) => Some(tree.merge(other_tree)),
+            (tree, other_tree) => tree.or(other_tree),
+        };
+        self.samples.extend(other.samples);
+        self
+    }
+
+    fn add(&mut self, source: &str, mut sample: Value) -> Result<()> {
+        self.args.path_filter().apply(&mut sample);
+        let mut tree = walk_value_tree_with_options(&sample, None, &self.args.infer_options());
+        tree.attribute_sources_to(&source.into());
+        self.tree = Some(match self.tree.take() {
+            Some(merged) => merged.merge(tree),
+            None => tree,
+        });
+        if self.args.emits.contains(&Emit::Html) {
+            self.samples.push(sample);
+        }
+        if let Some(progress) = self.progress {
+            progress.sampled();
+        }
+        Ok(())
+    }
+
+    /// Renders the merged tree, named `root_name`, as the flags of one of the `--emit` targets
+    /// ask
+    fn render(&self, args: &Args, root_name: &str) -> Result<RenderedOutput> {
+        let tree = self
+            .tree
+            .as_ref()
+            .context(ErrorKind::Inference.because("no JSON documents in the input"))?;
+        let mut output = render_output(args, tree, root_name);
+        if args.emit() == Emit::Html {
+            output.types = html::to_html_report(&self.samples, root_name);
+        }
+        Ok(output)
+    }
+}
+
+struct RenderedOutput {
+    types: String,
+    field_schema: FieldSchema,
+    provenance: Option<String>,
+    /// The types as one file per declaration, by file name, with `--split-types`
+    files: Vec<(String, String)>,
+    /// The shape of the types and the time taken to render them, with `--report`
+    report: Option<Value>,
+}
+
+/// Renders the types, and any functions asked for, from an unhashed tree
+fn render_output(args: &Args, tree: &TypeScriptNode, root_name: &str) -> RenderedOutput {
+    let started = std::time::Instant::now();
+    if args.emit() == Emit::Ir {
+        return RenderedOutput {
+            types: ir::to_ir_string(tree),
+            field_schema: FieldSchema::default(),
+            provenance: None,
+            files: Vec::new(),
+            report: None,
+        };
+    }
+    let mut tree = tree.clone();
+    // fields only seen in later samples were appended when merging
+    if args.key_order == KeyOrder::Sorted {
+        tree.sort_fields();
+    }
+    if let Some(limit) = args.literals {
+        tree.infer_literals(limit);
+    }
+    if args.null_as_optional {
+        tree.null_as_optional();
+    }
+    if args.squash_common_types.unwrap_or(true) {
+        tree.fold_recursive_objects();
+        tree.unify_repeated_objects();
+    }
+    let raw = if args.converters() {
+        Some(tree.clone())
+    } else {
+        None
+    };
+    let mut result = tree;
+    if let Some(key_case) = args.key_case() {
+        result.rename_fields(&|key| key_case.apply(key));
+    }
+    if args.squash_common_types.unwrap_or(true) {
+        result.calculate_hash();
+    }
+    let field_schema = FieldSchema::from_node(&result);
+    let options = args.render_options(root_name);
+    if options.bigint_mode == BigIntMode::Number && result.uses_big_integer() {
+        event!(
+            Level::WARN,
+            "integers beyond Number.MAX_SAFE_INTEGER are typed as `number`, which can't hold them exactly, pass --bigint-mode to type them as `bigint` or `string`"
+        );
+    }
+    let provenance = if args.provenance {
+        Some(provenance::to_provenance_json(&result, &options))
+    } else {
+        None
+    };
+    let mut functions = String::new();
+    if args.guards {
+        functions.push_str(&guards::to_guard_string(&result, &options));
+    }
+    if args.asserts {
+        functions.push_str(&asserts::to_assert_string(&result, &options));
+    }
+    if args.factories {
+        functions.push_str(&factories::to_factory_string(&result, &options));
+    }
+    if args.converters() {
+        functions.push_str(&normalizers::to_normalizer_string(&result, root_name));
+    }
+    let shape = args.report.map(|_| stats::shape_summary(&result, &options));
+    let files = if args.split_types {
+        TypeScriptNode::to_type_files(&result, &options)
+    } else {
+        Vec::new()
+    };
+    let mut output_string = render_emit(args, &result, &options);
+    if let Some(mut raw) = raw {
+        if args.squash_common_types.unwrap_or(true) {
+            raw.calculate_hash();
+        }
+        let raw_types = render_emit(
+            args,
+            &raw,
+            &RenderOptions {
+                root_name: format!("Raw{}", options.root_name),
+                // names taken from the paths would clash with those of the renamed types
+                naming: match options.naming {
+                    NamingStrategy::PathBased => NamingStrategy::Sequential,
+                    naming => naming,
+                },
+                type_namer: None,
+                list_alias: false,
+                ..options.clone()
+            },
+        );
+        // the raw types are emitted alongside the renamed ones
+        output_string.push('\n');
+        output_string.push_str(&without_shared_declarations(
+            args,
+            &options,
+            &output_string,
+            raw_types,
+        ));
+    }
+    output_string.push_str(&functions);
+    let report = shape.map(|shape| {
+        serde_json::json!({
+            "shape": shape,
+            "renderMs": started.elapsed().as_millis() as u64,
+        })
+    });
+    RenderedOutput {
+        types: output_string,
+        field_schema,
+        provenance,
+        files,
+        report,
+    }
+}
+
+/// Removes what is declared once per file, the zod import and the branded types, from types
+/// emitted after `preceding` when it already declares them
+fn without_shared_declarations(
+    args: &Args,
+    options: &RenderOptions,
+    preceding: &str,
+    types: String,
+) -> String {
+    let mut types = types;
+    if args.emit() == Emit::Zod && preceding.contains(zod::ZOD_IMPORT) {
+        types = types.replacen(zod::ZOD_IMPORT, "", 1);
+    }
+    let declarations = options
+        .date_type
+        .as_ref()
+        .and_then(|d| d.declaration())
+        .into_iter()
+        .chain(options.number_mode.declaration())
+        .chain(options.fallback_type.declaration())
+        .chain(StringFormat::STRING_FORMATS.iter().filter_map(|&format| {
+            options
+                .string_formats
+                .and_then(|mode| mode.declaration(format))
+        }))
+        .chain(
+            options
+                .base64_type
+                .as_deref()
+                .map(formats::base64_declaration),
+        );
+    for declaration in declarations {
+        let declaration = format!("\n{}{}", options.declaration_prefix(), declaration);
+        if preceding.contains(&declaration) {
+            types = types.replacen(&declaration, "", 1);
+        }
+    }
+    types
+}
+
+/// The banner as a `//` comment, or a `#` one for Python and GraphQL, one line per line of the
+/// banner
+fn banner_comment(banner: &str, emit: Emit) -> String {
+    let marker = if matches!(emit, Emit::Python | Emit::Graphql) {
+        "#"
+    } else {
+        "//"
+    };
+    banner
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{}\n", marker)
+            } else {
+                format!("{} {}\n", marker, line)
+            }
+        })
+        .collect()
+}
+
+fn write_output(args: &Args, output_file: Option<&str>, output: RenderedOutput) -> Result<()> {
+    let types = match &args.banner {
+        Some(banner) => format!("{}\n{}", banner_comment(banner, args.emit()), output.types),
+        None => output.types,
+    };
+    if !output.files.is_empty() {
+        let directory = std::path::Path::new(
+            output_file
+                .context(ErrorKind::Usage.because("--split-types needs an output directory"))?,
+        );
+        if !args.dry_run {
+            std::fs::create_dir_all(directory)
+                .context(ErrorKind::Output.because("could not create output directory"))?;
+            event!(Level::INFO, output_directory = %directory.display(), "writing output to files");
+        }
+        let mut written = serde_json::Map::new();
+        for (file_name, contents) in &output.files {
+            let path = directory.join(file_name);
+            let contents = match &args.banner {
+                Some(banner) => format!("{}\n{}", banner_comment(banner, args.emit()), contents),
+                None => contents.clone(),
+            };
+            let contents = postformatted(args, &path.to_string_lossy(), contents)?;
+            if args.dry_run {
+                preview(Some(&path), &contents, true);
+                continue;
+            }
+            std::fs::write(&path, &contents).with_context(|| {
+                ErrorKind::Output.because(format!("could not write `{}`", file_name))
+            })?;
+            written.insert(path.display().to_string(), contents.into());
+        }
+        if args.report.is_some() {
+            let files = written.keys().cloned().collect();
+            print_report(
+                output.report,
+                Value::Object(written),
+                files,
+                &args.diagnostics,
+            );
+        }
+        return Ok(());
+    }
+    let types = postformatted(
+        args,
+        &output_file.map(str::to_string).unwrap_or_else(|| {
+            format!(
+                "{}.{}",
+                args.root_name,
+                args.emit().extension(args.has_functions())
+            )
+        }),
+        types,
+    )?;
+    if args.check {
+        let output_file = output_file
+            .context(ErrorKind::Usage.because("--check needs an output file to compare against"))?;
+        let existing = std::fs::read_to_string(output_file).unwrap_or_default();
+        let stale_lines = check::stale_lines(&existing, &types);
+        if stale_lines.is_empty() {
+            event!(Level::INFO, output_file, "generated output is up to date");
+        } else {
+            args.stale_outputs.push(output_file, stale_lines);
+        }
+        return Ok(());
+    }
+    if args.dry_run {
+        let output_file = output_file.map(std::path::Path::new);
+        preview(output_file, &types, true);
+        if let Some(output_file) = output_file.map(|path| path.to_string_lossy()) {
+            if args.sidecar {
+                let sidecar = schema::sidecar_path(&output_file);
+                preview(Some(&sidecar), &output.field_schema.to_json(), false);
+            }
+            if let Some(provenance) = &output.provenance {
+                let provenance_file = provenance::provenance_path(&output_file);
+                preview(Some(&provenance_file), provenance, false);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(output_file) = output_file {
+        event!(Level::INFO, output_file, "writing output to file");
+        if args.sidecar {
+            std::fs::write(
+                schema::sidecar_path(output_file),
+                output.field_schema.to_json(),
+            )
+            .context(ErrorKind::Output.because("could not write sidecar file"))?;
+        }
+        if let Some(provenance) = &output.provenance {
+            std::fs::write(provenance::provenance_path(output_file), provenance)
+                .context(ErrorKind::Output.because("could not write provenance file"))?;
+        }
+        std::fs::write(output_file, &types)
+            .context(ErrorKind::Output.because("could not write to file"))?;
+    }
+    match (args.report, output_file) {
+        (Some(_), _) => print_report(
+            output.report,
+            types.into(),
+            output_file.map(str::to_string).into_iter().collect(),
+            &args.diagnostics,
+        ),
+        (None, None) => print!("{}", types),
+        (None, Some(_)) => (),
+    }
+    Ok(())
+}
+
+/// When the run started, for the timing of `--report json`
+static STARTED: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+
+/// Prints the `--report json` report of a run generating `code`, the contents of the output or
+/// those of each file by path, written to `files`
+fn print_report(
+    rendered: Option<Value>,
+    code: Value,
+    files: Vec<String>,
+    diagnostics: &Diagnostics,
+) {
+    let rendered = rendered.unwrap_or_default();
+    let report = serde_json::json!({
+        "code": code,
+        "files": files,
+        "warnings": report::take_warnings(),
+        "diagnostics": diagnostics.take(),
+        "shape": rendered["shape"],
+        "timing": {
+            "renderMs": rendered["renderMs"],
+            "totalMs": STARTED.get_or_init(std::time::Instant::now).elapsed().as_millis() as u64,
+        },
+    });
+    println!("{}", report);
+}
+
+/// Prints what `--dry-run` would write to `path`: whether the file would be created, updated
+/// or left as it is, then the contents when asked for, highlighted on a terminal
+fn preview(path: Option<&std::path::Path>, contents: &str, show_contents: bool) {
+    let colour = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    if let Some(path) = path {
+        let (status, code) = match std::fs::read_to_string(path) {
+            Ok(existing) if existing == contents => ("unchanged", "2"),
+            Ok(_) => ("would update", "33"),
+            Err(_) => ("would create", "32"),
+        };
+        if colour {
+            println!("\x1b[1;{}m{}\x1b[0m {}", code, status, path.display());
+        } else {
+            println!("{} {}", status, path.display());
+        }
+    }
+    if show_contents {
+        if colour {
+            print!("{}", highlight::highlight(contents));
+        } else {
+            print!("{}", contents);
+        }
+    }
+}
+
+/// The output run through `--format-cmd`, as is when there is none
+fn postformatted(args: &Args, file: &str, contents: String) -> Result<String> {
+    match &args.format_cmd {
+        Some(command) => postformat::format_with(command, file, &contents)
+            .context(ErrorKind::Render.because("could not format the output")),
+        None => Ok(contents),
+    }
+}
+
+/// Polls `url` forever, merging every response into the accumulated types and rewriting the
+/// output whenever they change
+fn run_watch_url(args: &Args, url: &str) -> Result<()> {
+    let interval = poll::parse_interval(&args.interval)?;
+    let headers = args.request_headers()?;
+    let mut accumulated: Option<TypeScriptNode> = None;
+    let mut last_output: Option<String> = None;
+    loop {
+        let infer_options = args.infer_options();
+        match fetch::fetch_json(url, &headers).map(|mut v| {
+            args.path_filter().apply(&mut v);
+            walk_value_tree_with_options(&v, None, &infer_options)
+        }) {
+            Ok(mut observation) => {
+                observation.attribute_sources_to(&url.into());
+                let tree = match accumulated.take() {
+                    Some(tree) => tree.merge(observation),
+                    None => observation,
+                };
+                let output = render_output(args, &tree, &args.root_name);
+                accumulated = Some(tree);
+                if last_output.as_ref() == Some(&output.types) {
+                    event!(Level::INFO, url, "inferred types unchanged");
+                } else {
+                    last_output = Some(output.types.clone());
+                    write_output(args, args.output_file.as_deref(), output)?;
+                }
+            }
+            Err(error) => event!(Level::WARN, url, error = %error, "could not fetch"),
+        }
+        args.diagnostics.log();
+        std::thread::sleep(interval);
+    }
+}
+
+fn run_watch(args: &Args) -> Result<()> {
+    if args.input_files.iter().any(|input| fetch::is_url(input)) {
+        anyhow::bail!(ErrorKind::Usage
+            .because("--watch needs input files, use --watch-url to poll an endpoint"));
+    }
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)
+        .context(ErrorKind::Input.because("could not start watching"))?;
+    for input in &args.input_files {
+        let (path, mode) = watch::watched_path(input);
+        notify::Watcher::watch(&mut watcher, &path, mode).with_context(|| {
+            ErrorKind::Input.because(format!("could not watch `{}`", path.display()))
+        })?;
+    }
+    if let Err(error) = run_once(args) {
+        event!(Level::WARN, error = %error, "could not generate types");
+    }
+    args.diagnostics.log();
+    while let Ok(event) = receiver.recv() {
+        // editors save in several steps, so wait for the rest of them
+        std::thread::sleep(watch::SETTLE_TIME);
+        let events = std::iter::once(event).chain(receiver.try_iter());
+        let relevant = events
+            .filter_map(|event| event.ok())
+            .any(|event| watch::is_relevant(&event, &args.input_files));
+        if !relevant {
+            continue;
+        }
+        event!(Level::INFO, "input changed, regenerating");
+        if let Err(error) = run_once(args) {
+            event!(Level::WARN, error = %error, "could not generate types");
+        }
+        args.diagnostics.log();
+    }
+    Ok(())
+}
+
+fn run_check(check_args: CheckArgs) -> Result<()> {
+    let mut args = check_args.args;
+    if args.watch || args.watch_url.is_some() || args.report.is_some() || args.dry_run {
+        anyhow::bail!(ErrorKind::Usage
+            .because("check only compares the output, it can't be combined with --watch, --watch-url, --report or --dry-run"));
+    }
+    args.check = true;
+    run(&args)
+}
+
+fn run_drift(args: DriftArgs) -> Result<()> {
+    let existing = FieldSchema::read_for(&args.against)?;
+    let fresh = FieldSchema::from_node(&infer_types(&args.input_file, Some(false))?);
+    let changes = existing.changes_to(&fresh);
+    if changes.is_empty() {
+        println!("No schema drift against {}", args.against);
+    } else {
+        println!("Schema drift against {}:", args.against);
+        for change in changes {
+            println!("  {}", change.describe());
+        }
+    }
+    Ok(())
+}
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    let old = load_schema(&args.old)?;
+    let new = load_schema(&args.new)?;
+    let changes = old.changes_to(&new);
+    let lines = declaration_lines(&args.new);
+    print!(
+        "{}",
+        check::render_changes(&args.new, &changes, &lines, args.format)
+    );
+    if changes
+        .iter()
+        .any(|change| change.classify().0 == Compatibility::Breaking)
+    {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// The lines of a file of generated types declaring each field, none for other schemas
+fn declaration_lines(path: &str) -> std::collections::BTreeMap<String, usize> {
+    std::fs::read_to_string(path)
+        .ok()
+        .filter(|_| !path.ends_with(".json"))
+        .and_then(|source| typescript_parser::parse_schema_lines(&source).ok())
+        .map(|(_, lines)| lines)
+        .unwrap_or_default()
+}
+
+fn run_verify(args: VerifyArgs) -> Result<()> {
+    let schema = load_schema(&args.schema)?;
+    let headers = args
+        .headers
+        .iter()
+        .map(|header| fetch::parse_header(header))
+        .collect::<Result<Vec<_>>>()?;
+    let mut stdout = std::io::stdout();
+    let mut failed_responses = 0;
+    for attempt in 1..=args.count {
+        let response = fetch::fetch_json(&args.url, &headers)?;
+        let checked = format!("response {}/{}", attempt, args.count);
+        let violations = schema.violations(&response);
+        if !report_violations(&mut stdout, &checked, &args.schema, violations)? {
+            failed_responses += 1;
+        }
+    }
+    if failed_responses > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_validate(args: ValidateArgs) -> Result<()> {
+    let schema = load_schema(&args.schema)?;
+    if validate_inputs(&mut std::io::stdout(), &schema, &args.schema, &args.inputs)? > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Checks every input against the schema, reporting each, and returns how many didn't match
+fn validate_inputs(
+    out: &mut dyn Write,
+    schema: &FieldSchema,
+    schema_path: &str,
+    inputs: &[String],
+) -> Result<usize> {
+    let mut failed_inputs = 0;
+    for input in inputs {
+        let payload = read_input(input, InputFormat::Json, &[])?;
+        if !report_violations(out, input, schema_path, schema.violations(&payload))? {
+            failed_inputs += 1;
+        }
+    }
+    Ok(failed_inputs)
+}
+
+/// Writes whether what was checked against the schema matched it, listing the violations by
+/// path when it didn't, and returns whether it did
+fn report_violations(
+    out: &mut dyn Write,
+    checked: &str,
+    schema: &str,
+    violations: Vec<Violation>,
+) -> Result<bool> {
+    if violations.is_empty() {
+        writeln!(out, "{}: ok", checked)?;
+        return Ok(true);
+    }
+    writeln!(out, "{}: does not match {}", checked, schema)?;
+    for violation in violations {
+        writeln!(out, "  {}: {}", violation.path, violation.message)?;
+    }
+    Ok(false)
+}
+
+fn run_merge(args: MergeArgs) -> Result<()> {
+    if args.report {
+        let samples = args
+            .inputs
+            .iter()
+            .map(|input| {
+                Ok((
+                    input.clone(),
+                    FieldSchema::from_node(&infer_types(input, Some(false))?),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        print!("{}", compare::overlap_report(&samples));
+        return Ok(());
+    }
+    let mut merged: Option<TypeScriptNode> = None;
+    for input in &args.inputs {
+        let tree = walk_value_tree(&read_input(input, InputFormat::Json, &[])?, None);
+        merged = Some(match merged {
+            Some(merged) => merged.merge(tree),
+            None => tree,
+        });
+    }
+    let mut merged = merged.context(ErrorKind::Usage.because("no inputs given"))?;
+    merged.fold_recursive_objects();
+    merged.unify_repeated_objects();
+    merged.calculate_hash();
+    print!("{}", render_types(&merged, &RenderOptions::default()));
+    Ok(())
+}
+
+fn run_stats(args: StatsArgs) -> Result<()> {
+    let mut merged: Option<TypeScriptNode> = None;
+    for input in expand_inputs(&args.inputs)? {
+        let tree = walk_value_tree(&read_input(&input, InputFormat::Json, &[])?, None);
+        merged = Some(match merged {
+            Some(merged) => merged.merge(tree),
+            None => tree,
+        });
+    }
+    let merged = merged.context(ErrorKind::Inference.because("no JSON documents in the input"))?;
+    print!("{}", stats::stats_report(&merged, args.top));
+    Ok(())
+}
+
+fn run_bench(args: BenchArgs) -> Result<()> {
+    let options = RenderOptions::default();
+    let mut timings = bench::Timings::default();
+    let mut input_length = 0;
+    for _ in 0..args.iterations {
+        let content = timings.time("read", || read_input_file(&args.input, &[]))?;
+        input_length = content.len();
+        let value: Value = timings.time("parse", || errors::parse_json(&content))?;
+        let mut tree = timings.time("walk", || stages::walk(&value));
+        timings.time("squash", || stages::squash(&mut tree));
+        timings.time("hash", || stages::calculate_hash(&mut tree));
+        let types = timings.time("render", || stages::to_type_string(&tree, &options));
+        std::hint::black_box(types);
+    }
+    println!(
+        "{}: {} bytes, {} iteration{}\n",
+        args.input,
+        input_length,
+        args.iterations,
+        if args.iterations == 1 { "" } else { "s" }
+    );
+    print!("{}", timings.report());
+    Ok(())
+}
+
+fn run_workspace(args: WorkspaceArgs) -> Result<()> {
+    let config = config::Config::read(std::path::Path::new(&args.config))?;
+    let mut rows = Vec::new();
+    for profile in config.ordered_profiles()? {
+        let targets = match profile.targets() {
+            Ok(targets) => targets,
+            Err(error) => {
+                rows.push((
+                    profile.name.clone(),
+                    String::new(),
+                    format!("failed: {:#}", error),
+                ));
+                continue;
+            }
+        };
+        for (fixture, output) in targets {
+            let status = match regenerate(&fixture, &output, profile.squash) {
+                Ok(true) => "changed".to_string(),
+                Ok(false) => "unchanged".to_string(),
+                Err(error) => format!("failed: {:#}", error),
+            };
+            rows.push((profile.name.clone(), output.display().to_string(), status));
+        }
+    }
+    let profile_width = rows.iter().map(|row| row.0.len()).chain([7]).max().unwrap();
+    let target_width = rows.iter().map(|row| row.1.len()).chain([6]).max().unwrap();
+    println!(
+        "{:profile_width$}  {:target_width$}  status",
+        "profile", "target"
+    );
+    for (profile, target, status) in &rows {
+        println!(
+            "{:profile_width$}  {:target_width$}  {}",
+            profile, target, status
+        );
+    }
+    let count = |status: &str| rows.iter().filter(|row| row.2.starts_with(status)).count();
+    let failed = count("failed");
+    println!(
+        "{} changed, {} unchanged, {} failed",
+        count("changed"),
+        count("unchanged"),
+        failed
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Regenerates the types of one fixture, returning whether the output file changed
+fn regenerate(
+    fixture: &std::path::Path,
+    output: &std::path::Path,
+    squash_common_types: Option<bool>,
+) -> Result<bool> {
+    let generated = generate_types(&fixture.to_string_lossy(), squash_common_types)?;
+    if std::fs::read_to_string(output).ok().as_ref() == Some(&generated) {
+        return Ok(false);
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .context(ErrorKind::Output.because("could not create output directory"))?;
+    }
+    std::fs::write(output, generated)
+        .context(ErrorKind::Output.because("could not write to file"))?;
+    Ok(true)
+}
+
+/// Loads the field schema of a sidecar, an `--emit ir` tree, a JSON sample or previously
+/// generated types. JSON holding the `{ "version", "tree" }` envelope of the IR is read as the
+/// IR whatever the file is called.
+fn load_schema(path: &str) -> Result<FieldSchema> {
+    if path.ends_with(".myrrh.json") {
+        FieldSchema::read_sidecar(std::path::Path::new(path))
+    } else if path.ends_with(".json") {
+        let document = read_input(path, InputFormat::Json, &[])?;
+        let tree = if ir::is_ir_document(&document) {
+            ir::from_ir_value(document)
+                .with_context(|| format!("could not read IR from `{}`", path))?
+        } else {
+            infer_value(&document, Some(false))?
+        };
+        Ok(FieldSchema::from_node(&tree))
+    } else {
+        FieldSchema::read_for(path)
+    }
+}
+
+fn generate_types(input_file: &str, squash_common_types: Option<bool>) -> Result<String> {
+    Ok(render_types(
+        &infer_types(input_file, squash_common_types)?,
+        &RenderOptions::default(),
+    ))
+}
+
+fn render_emit(args: &Args, result: &TypeScriptNode, options: &RenderOptions) -> String {
+    match args.emit() {
+        Emit::Python => python::to_python_string(result, options, args.python_style),
+        Emit::Graphql => graphql::to_graphql_string(result, options),
+        Emit::Proto => proto::to_proto_string(result, options),
+        Emit::Avro => avro::to_avro_string(result, options),
+        Emit::Csharp => csharp::to_csharp_string(result, options, args.csharp_style),
+        Emit::Swift => swift::to_swift_string(result, options),
+        Emit::Java => java::to_java_string(result, options, args.java_style),
+        Emit::Flow => flow::to_flow_string(result, options),
+        Emit::Zod => zod::to_zod_string(result, options),
+        Emit::JsonSchema => json_schema::to_json_schema_string(result, options),
+        Emit::Type | Emit::Interface | Emit::Html | Emit::Ir => render_types(result, options),
+    }
+}
+
+fn infer_types(input_file: &str, squash_common_types: Option<bool>) -> Result<TypeScriptNode> {
+    infer_value(
+        &read_input(input_file, InputFormat::Json, &[])?,
+        squash_common_types,
+    )
+}
+
+fn infer_value(v: &Value, squash_common_types: Option<bool>) -> Result<TypeScriptNode> {
+    let mut result: TypeScriptNode = walk_value_tree(v, None);
+    if squash_common_types.unwrap_or(true) {
+        result.fold_recursive_objects();
+        result.unify_repeated_objects();
+        result.calculate_hash();
+    }
+    Ok(result)
+}
+
+/// Expands the `--input` globs into the files they match, in order, reading stdin when there
+/// are none
+fn expand_inputs(inputs: &[String]) -> Result<Vec<String>> {
+    if inputs.is_empty() {
+        return Ok(vec![STDIN.to_string()]);
+    }
+    let mut files = Vec::new();
+    for input in inputs {
+        if fetch::is_url(input) || !input.contains(['*', '?', '[']) {
+            files.push(input.clone());
+            continue;
+        }
+        let mut matches = glob::glob(input)
+            .with_context(|| ErrorKind::Usage.because(format!("invalid glob `{}`", input)))?
+            .map(|path| path.map(|path| path.to_string_lossy().into_owned()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .with_context(|| {
+                ErrorKind::Input.because(format!("could not expand glob `{}`", input))
+            })?;
+        if matches.is_empty() {
+            anyhow::bail!(ErrorKind::Input.because(format!("no files match `{}`", input)));
+        }
+        matches.sort();
+        files.extend(matches);
+    }
+    Ok(files)
+}
+
+/// The size of the input files, unless some are read from stdin or a URL
+fn total_size(input_files: &[String]) -> Option<u64> {
+    input_files
+        .iter()
+        .filter(|input_file| *input_file != STDIN && !fetch::is_url(input_file))
+        .map(|input_file| {
+            std::fs::metadata(input_file)
+                .ok()
+                .map(|metadata| metadata.len())
+        })
+        .sum::<Option<u64>>()
+        .filter(|_| {
+            input_files
+                .iter()
+                .all(|input_file| input_file != STDIN && !fetch::is_url(input_file))
+        })
+}
+
+/// The error reading the content of `input_file` fails with
+fn read_failure(input_file: &str) -> errors::Failure {
+    ErrorKind::Input.because(if fetch::is_url(input_file) {
+        format!("could not read response from `{}`", input_file)
+    } else if input_file == STDIN {
+        "could not read stdin".to_string()
+    } else {
+        format!("could not read file `{}`", input_file)
+    })
+}
+
+/// Reads a file, stdin or, for a URL, the response to a request sent with `headers`,
+/// decompressed if need be
+fn read_input_file(input_file: &str, headers: &[(String, String)]) -> Result<String> {
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut open_input(input_file, headers, None)?, &mut content)
+        .with_context(|| read_failure(input_file))?;
+    Ok(content)
+}
+
+fn read_input(
+    input_file: &str,
+    format: InputFormat,
+    headers: &[(String, String)],
+) -> Result<Value> {
+    parse_input(input_file, format, open_input(input_file, headers, None)?)
+}
+
+/// Reads `input_file` from `reader` and parses it as `format`
+fn parse_input(
+    input_file: &str,
+    format: InputFormat,
+    mut reader: Box<dyn BufRead>,
+) -> Result<Value> {
+    if matches!(format, InputFormat::Msgpack | InputFormat::Cbor) {
+        let mut content = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut content)
+            .with_context(|| read_failure(input_file))?;
+        event!(
+            Level::INFO,
+            input_file_content_length = content.len(),
+            "input file content"
+        );
+        return match format {
+            InputFormat::Msgpack => parse_msgpack(&content),
+            _ => parse_cbor(&content),
+        };
+    }
+    let mut input_file_content = String::new();
+    std::io::Read::read_to_string(&mut reader, &mut input_file_content)
+        .with_context(|| read_failure(input_file))?;
+
+    let input_length = String::len(&input_file_content);
+    event!(
+        Level::INFO,
+        input_file_content_length = input_length,
+        "input file content"
+    );
+
+    match format {
+        InputFormat::Json | InputFormat::Openapi => errors::parse_json(&input_file_content),
+        InputFormat::Json5 => parse_json5(&input_file_content),
+        InputFormat::Toml => parse_toml(&input_file_content),
+        InputFormat::Csv | InputFormat::Tsv => Ok(Value::Array(
+            parse_csv(
+                input_file_content.as_bytes(),
+                format.delimiter().unwrap_or(b','),
+            )?
+            .map(|row| row.map(|(_, row)| row))
+            .collect::<Result<_>>()?,
+        )),
+        InputFormat::Msgpack | InputFormat::Cbor => unreachable!("binary input is read as bytes"),
+    }
+}
+
+/// Opens a file, stdin or, for a URL, the response body, to be read as it arrives and
+/// decompressed when it is gzip or zstd. What is read, before decompressing, advances
+/// `progress`.
+fn open_input(
+    input_file: &str,
+    headers: &[(String, String)],
+    progress: Option<&Progress>,
+) -> Result<Box<dyn BufRead>> {
+    let reader: Box<dyn BufRead> = if fetch::is_url(input_file) {
+        Box::new(BufReader::new(fetch::fetch_reader(input_file, headers)?))
+    } else if input_file == STDIN {
+        Box::new(std::io::stdin().lock())
+    } else {
+        let file = std::fs::File::open(input_file).with_context(|| {
+            ErrorKind::Input.because(format!("could not read file `{}`", input_file))
+        })?;
+        Box::new(BufReader::new(file))
+    };
+    decompress::decompressed(match progress {
+        Some(progress) => progress.wrap(reader),
+        None => reader,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        expand_inputs, infer_inputs, load_schema, plan_outputs, report_violations, run, run_check,
+        validate_inputs, Cli, Command, STDIN,
+    };
+    use crate::schema::Violation;
+    use crate::walk_value_tree;
+    use crate::{errors, ir};
+    use clap::Parser;
+
+    /// Writes the files to a directory of their own, named after the test
+    fn write_files(test: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join(format!("myrrh-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (name, content) in files {
+            std::fs::write(directory.join(name), content).unwrap();
+        }
+        directory
+    }
+
+    #[test]
+    fn reports_violations_by_path() {
+        let mut out = Vec::new();
+        let violations = vec![Violation {
+            path: "$.tags[1]".to_string(),
+            message: "expected string but found number".to_string(),
+        }];
+        assert!(!report_violations(&mut out, "a.json", "types.ir.json", violations).unwrap());
+        assert!(report_violations(&mut out, "b.json", "types.ir.json", Vec::new()).unwrap());
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "a.json: does not match types.ir.json\n  $.tags[1]: expected string but found number\nb.json: ok\n"
+        );
+    }
+
+    #[test]
+    fn validates_payloads_against_an_ir_whatever_its_name() {
+        let tree = walk_value_tree(&serde_json::json!({ "id": 1, "tags": ["a"] }), None);
+        let directory = write_files(
+            "validate",
+            &[
+                ("schema.json", &ir::to_ir_string(&tree)),
+                ("good.json", r#"{ "id": 2, "tags": [] }"#),
+                ("bad.json", r#"{ "id": 3, "tags": ["a", 1] }"#),
+            ],
+        );
+        let path = |name: &str| directory.join(name).to_string_lossy().into_owned();
+        let schema = load_schema(&path("schema.json")).unwrap();
+        let mut out = Vec::new();
+        let failed = validate_inputs(
+            &mut out,
+            &schema,
+            "schema.json",
+            &[path("good.json"), path("bad.json")],
+        )
+        .unwrap();
+        assert_eq!(failed, 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{}: ok\n{}: does not match schema.json\n  $.tags[1]: expected string but found number\n",
+                path("good.json"),
+                path("bad.json")
+            )
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn expands_globs_into_the_files_they_match_in_order() {
+        let directory = write_files(
+            "expand",
+            &[("b.json", "{}"), ("a.json", "{}"), ("c.txt", "")],
+        );
+        let path = |name: &str| directory.join(name).to_string_lossy().into_owned();
+        assert_eq!(
+            expand_inputs(&[path("*.json"), path("c.txt")]).unwrap(),
+            [path("a.json"), path("b.json"), path("c.txt")]
+        );
+        assert_eq!(expand_inputs(&[]).unwrap(), [STDIN]);
+        let missing = expand_inputs(&[path("*.yaml")]).unwrap_err();
+        assert_eq!(
+            missing.to_string(),
+            format!("no files match `{}`", path("*.yaml"))
+        );
+        assert_eq!(errors::exit_code(&missing), 3);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn checks_against_the_output_generate_writes_with_the_same_flags() {
+        let directory = write_files("check", &[("input.json", r#"{ "id": 1 }"#)]);
+        let path = |name: &str| directory.join(name).to_string_lossy().into_owned();
+        let flags = [
+            "--input",
+            &path("input.json"),
+            "--output",
+            &path("types.ts"),
+            "--emit",
+            "interface",
+            "--readonly",
+            "--export",
+        ];
+        let generate = Cli::try_parse_from(["myrrh"].iter().chain(&flags)).unwrap();
+        run(&generate.args).unwrap();
+        let check = Cli::try_parse_from(["myrrh", "check"].iter().chain(&flags)).unwrap();
+        match check.command {
+            Some(Command::Check(check_args)) => run_check(check_args).unwrap(),
+            command => panic!("expected check, got {:?}", command),
+        }
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn reports_every_stale_target_before_exiting_with_1() {
+        let directory = write_files("check-targets", &[("input.json", r#"{ "id": 1 }"#)]);
+        let input = directory.join("input.json").to_string_lossy().into_owned();
+        let out_dir = directory.join("out").to_string_lossy().into_owned();
+        let cli = Cli::try_parse_from([
+            "myrrh",
+            "--input",
+            &input,
+            "--emit",
+            "ts,zod",
+            "--out-dir",
+            &out_dir,
+            "--check",
+        ])
+        .unwrap();
+        let stale = run(&cli.args).unwrap_err();
+        assert_eq!(stale.to_string(), "2 generated output(s) are stale");
+        assert_eq!(errors::exit_code(&stale), 1);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn exits_with_2_for_flags_which_cant_be_combined() {
+        let cli = Cli::try_parse_from(["myrrh", "--emit", "ts,zod"]).unwrap();
+        assert_eq!(errors::exit_code(&plan_outputs(&cli.args).unwrap_err()), 2);
+    }
+
+    #[test]
+    fn merges_the_samples_of_every_input() {
+        let directory = write_files(
+            "merge",
+            &[
+                ("a.json", r#"{ "id": 1, "name": "a" }"#),
+                ("b.json", r#"{ "id": 2, "tags": ["b"] }"#),
+            ],
+        );
+        let glob = directory.join("*.json").to_string_lossy().into_owned();
+        let cli = Cli::try_parse_from(["myrrh", "--input", &glob]).unwrap();
+        let inputs = expand_inputs(&cli.args.input_files).unwrap();
+        let inference = infer_inputs(&cli.args, &inputs, None).unwrap();
+        let output = inference.render(&cli.args, "DefaultType").unwrap();
+        assert_eq!(
+            output.types,
+            "type DefaultType = {\n  id: number;\n  name?: string;\n  tags?: string[];\n};\n"
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn declares_the_rows_of_a_table_right_after_a_row() {
+        let directory = write_files("csv", &[("rows.csv", "id,name\n1,a\n")]);
+        let input = directory.join("rows.csv").to_string_lossy().into_owned();
+        let cli = Cli::try_parse_from([
+            "myrrh",
+            "--input",
+            &input,
+            "--format",
+            "csv",
+            "--no-semicolons",
+            "--guards",
+            "--root-name",
+            "Row",
+        ])
+        .unwrap();
+        let inference = infer_inputs(&cli.args, &[input], None).unwrap();
+        let output = inference.render(&cli.args, "Row").unwrap();
+        assert!(output
+            .types
+            .starts_with("type Row = {\n  id: number\n  name: string\n}\n\ntype Rows = Row[]\n\nexport function isRow("));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
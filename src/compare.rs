@@ -0,0 +1,129 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use itertools::Itertools;
+
+use crate::schema::FieldSchema;
+
+/// Reports which fields are common to all samples, which are unique to one of them or shared by
+/// some, and where the samples disagree on a field's type
+pub(crate) fn overlap_report(samples: &[(String, FieldSchema)]) -> String {
+    let paths: BTreeSet<&String> = samples
+        .iter()
+        .flat_map(|(_, schema)| schema.fields.keys())
+        .collect();
+    let mut common = Vec::new();
+    let mut unique: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut some = Vec::new();
+    let mut disagreements = Vec::new();
+    for path in paths {
+        let present: Vec<(&str, &str)> = samples
+            .iter()
+            .filter_map(|(name, schema)| {
+                schema
+                    .fields
+                    .get(path)
+                    .map(|field| (name.as_str(), field.type_name.as_str()))
+            })
+            .collect();
+        let types: BTreeSet<&str> = present.iter().map(|(_, type_name)| *type_name).collect();
+        if types.len() > 1 {
+            let by_type = types
+                .iter()
+                .map(|type_name| {
+                    let names = present
+                        .iter()
+                        .filter(|(_, t)| t == type_name)
+                        .map(|(name, _)| *name)
+                        .join(", ");
+                    format!("{} in {}", type_name, names)
+                })
+                .join("; ");
+            disagreements.push(format!("{}: {}", path, by_type));
+        }
+        let type_string = types.iter().join(" / ");
+        match present.len() {
+            n if n == samples.len() => common.push(format!("{}: {}", path, type_string)),
+            1 => unique
+                .entry(present[0].0)
+                .or_default()
+                .push(format!("{}: {}", path, type_string)),
+            _ => some.push(format!(
+                "{}: {}",
+                path,
+                present.iter().map(|(name, _)| *name).join(", ")
+            )),
+        }
+    }
+    let mut report = String::new();
+    let total = common.len() + some.len() + unique.values().map(Vec::len).sum::<usize>();
+    push_section(
+        &mut report,
+        &format!("Common to all {} samples", samples.len()),
+        &common,
+    );
+    for (name, _) in samples {
+        push_section(
+            &mut report,
+            &format!("Only in {}", name),
+            unique
+                .get(name.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+        );
+    }
+    if samples.len() > 2 {
+        push_section(&mut report, "In some samples", &some);
+    }
+    push_section(&mut report, "Type disagreements", &disagreements);
+    report.push_str(&format!(
+        "{} of {} fields are common to all samples\n",
+        common.len(),
+        total
+    ));
+    report
+}
+
+fn push_section(report: &mut String, title: &str, lines: &[String]) {
+    report.push_str(&format!("{}:\n", title));
+    if lines.is_empty() {
+        report.push_str("  (none)\n");
+    }
+    for line in lines {
+        report.push_str(&format!("  {}\n", line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::overlap_report;
+    use crate::{schema::FieldSchema, walk_value_tree};
+
+    fn sample(name: &str, json: &str) -> (String, FieldSchema) {
+        let val_tree = serde_json::from_str(json).unwrap();
+        let schema = FieldSchema::from_node(&walk_value_tree(&val_tree, None));
+        (name.to_string(), schema)
+    }
+
+    #[test]
+    fn reports_field_overlap() {
+        let report = overlap_report(&[
+            sample("a.json", r#"{ "id": 1, "amount": 2, "refunded": true }"#),
+            sample("b.json", r#"{ "id": 1, "amount": "2" }"#),
+        ]);
+        assert_eq!(
+            report,
+            "Common to all 2 samples:
+  $: object
+  $.amount: number / string
+  $.id: number
+Only in a.json:
+  $.refunded: boolean
+Only in b.json:
+  (none)
+Type disagreements:
+  $.amount: number in a.json; string in b.json
+3 of 4 fields are common to all samples
+"
+        );
+    }
+}
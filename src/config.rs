@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::errors::ErrorKind;
+use crate::{Context, Result};
+
+/// Config files looked for in the working directory when none is given
+pub(crate) const DEFAULT_CONFIGS: [&str; 2] = ["myrrh.toml", ".myrrhrc.json"];
+
+/// `myrrh.toml` or `.myrrhrc.json`: generation targets, and profiles each regenerating the
+/// types of every fixture in its directories
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(default, rename = "target", alias = "targets")]
+    pub(crate) targets: Vec<Target>,
+    #[serde(default, rename = "profile", alias = "profiles")]
+    pub(crate) profiles: Vec<Profile>,
+}
+
+/// One generation, as if run with the equivalent flags
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Target {
+    /// Input files, globs or URLs, merged into one type
+    pub(crate) input: Vec<String>,
+    pub(crate) output: PathBuf,
+    #[serde(default)]
+    pub(crate) root_name: Option<String>,
+    /// As given to `--emit`
+    #[serde(default)]
+    pub(crate) emit: Option<String>,
+    /// As given to `--rename`
+    #[serde(default)]
+    pub(crate) rename: Option<String>,
+    /// As given to `--original-names`
+    #[serde(default)]
+    pub(crate) original_names: Option<String>,
+    /// Types forced at JSON paths, as given to `--override`, `"$.items[*].id" = "string"`
+    #[serde(default)]
+    pub(crate) overrides: BTreeMap<String, String>,
+    /// Any other flags, as written on the command line
+    #[serde(default)]
+    pub(crate) flags: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Profile {
+    pub(crate) name: String,
+    /// Directories scanned for `.json` fixtures
+    pub(crate) fixtures: Vec<PathBuf>,
+    /// Directory the `.ts` output of every fixture is written to, mirroring the fixture layout
+    pub(crate) output: PathBuf,
+    /// Profiles which have to be regenerated before this one, e.g. shared types
+    #[serde(default)]
+    pub(crate) depends_on: Vec<String>,
+    #[serde(default)]
+    pub(crate) squash: Option<bool>,
+}
+
+impl Config {
+    /// Reads a config file, TOML or, for `.json` files, JSON, resolving the paths in it
+    /// relative to the file
+    pub(crate) fn read(path: &Path) -> Result<Config> {
+        let contents = std::fs::read_to_string(path).with_context(|| {
+            ErrorKind::Config.because(format!("could not read config `{}`", path.display()))
+        })?;
+        let mut config: Config = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&contents).with_context(|| {
+                ErrorKind::Config.because(format!("could not parse config `{}`", path.display()))
+            })?
+        } else {
+            toml::from_str(&contents).with_context(|| {
+                ErrorKind::Config.because(format!("could not parse config `{}`", path.display()))
+            })?
+        };
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        for target in &mut config.targets {
+            for input in &mut target.input {
+                if !crate::fetch::is_url(input) {
+                    *input = base.join(&*input).to_string_lossy().into_owned();
+                }
+            }
+            target.output = base.join(&target.output);
+        }
+        for profile in &mut config.profiles {
+            for fixtures in &mut profile.fixtures {
+                *fixtures = base.join(&*fixtures);
+            }
+            profile.output = base.join(&profile.output);
+        }
+        Ok(config)
+    }
+
+    /// Profiles ordered so that every profile comes after the ones it depends on, otherwise
+    /// keeping the order they were declared in
+    pub(crate) fn ordered_profiles(&self) -> Result<Vec<&Profile>> {
+        for profile in &self.profiles {
+            if let Some(unknown) = profile
+                .depends_on
+                .iter()
+                .find(|dependency| !self.profiles.iter().any(|p| &&p.name == dependency))
+            {
+                anyhow::bail!(ErrorKind::Config.because(format!(
+                    "profile `{}` depends on unknown profile `{}`",
+                    profile.name, unknown
+                )));
+            }
+        }
+        let mut ordered: Vec<&Profile> = Vec::new();
+        while ordered.len() < self.profiles.len() {
+            let ready = self.profiles.iter().find(|profile| {
+                !ordered.iter().any(|done| done.name == profile.name)
+                    && profile
+                        .depends_on
+                        .iter()
+                        .all(|dependency| ordered.iter().any(|done| &done.name == dependency))
+            });
+            match ready {
+                Some(profile) => ordered.push(profile),
+                None => {
+                    anyhow::bail!(ErrorKind::Config.because("profiles have a dependency cycle"))
+                }
+            }
+        }
+        Ok(ordered)
+    }
+}
+
+impl Target {
+    /// The flags the target stands for
+    pub(crate) fn command_line(&self) -> Vec<String> {
+        let mut command_line = Vec::new();
+        for input in &self.input {
+            command_line.extend(["--input".to_string(), input.clone()]);
+        }
+        command_line.extend([
+            "--output".to_string(),
+            self.output.to_string_lossy().into_owned(),
+        ]);
+        let options = [
+            ("--root-name", &self.root_name),
+            ("--emit", &self.emit),
+            ("--rename", &self.rename),
+            ("--original-names", &self.original_names),
+        ];
+        for (flag, value) in options {
+            if let Some(value) = value {
+                command_line.extend([flag.to_string(), value.clone()]);
+            }
+        }
+        for (path, type_override) in &self.overrides {
+            command_line.extend([
+                "--override".to_string(),
+                format!("{}={}", path, type_override),
+            ]);
+        }
+        command_line.extend(self.flags.iter().cloned());
+        command_line
+    }
+}
+
+impl Profile {
+    /// Every `.json` fixture in the profile's directories, paired with its output file
+    pub(crate) fn targets(&self) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let mut targets = Vec::new();
+        for directory in &self.fixtures {
+            let mut fixtures = Vec::new();
+            collect_fixtures(directory, &mut fixtures)?;
+            fixtures.sort();
+            for fixture in fixtures {
+                let relative = fixture.strip_prefix(directory).unwrap_or(&fixture);
+                let output = self.output.join(relative).with_extension("ts");
+                targets.push((fixture, output));
+            }
+        }
+        Ok(targets)
+    }
+}
+
+/// Collects the `.json` files in `directory` and its subdirectories
+pub(crate) fn collect_fixtures(directory: &Path, fixtures: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(directory).with_context(|| {
+        ErrorKind::Input.because(format!("could not read fixtures `{}`", directory.display()))
+    })?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_fixtures(&path, fixtures)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            fixtures.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn reads_targets_as_flags() {
+        let config: Config = serde_json::from_str(
+            r#"{ "targets": [{ "input": ["a.json", "b.json"], "output": "types.ts", "root_name": "Order", "overrides": { "$.items[*].id": "string" }, "flags": ["--export"] }] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.targets[0].command_line(),
+            [
+                "--input",
+                "a.json",
+                "--input",
+                "b.json",
+                "--output",
+                "types.ts",
+                "--root-name",
+                "Order",
+                "--override",
+                "$.items[*].id=string",
+                "--export"
+            ]
+        );
+    }
+
+    #[test]
+    fn orders_profiles_by_dependency() {
+        let config: Config = toml::from_str(
+            r#"
+            [[profile]]
+            name = "api"
+            fixtures = ["fixtures/api"]
+            output = "src/api"
+            depends_on = ["shared"]
+
+            [[profile]]
+            name = "shared"
+            fixtures = ["fixtures/shared"]
+            output = "src/shared"
+            "#,
+        )
+        .unwrap();
+        let names: Vec<&str> = config
+            .ordered_profiles()
+            .unwrap()
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect();
+        assert_eq!(names, ["shared", "api"]);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::{BTreeSet, HashSet};
+
+use clap::ValueEnum;
+
+use crate::declarations::{numbered, to_type_identifier, Declarations};
+use crate::naming::segment;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const INDENT: &str = "    ";
+
+/// The type of values C# has no type for: `null` on its own, arrays mixing types, unions and
+/// values below `--max-depth`
+const JSON_ELEMENT: &str = "JsonElement";
+
+/// How the C# types are declared, as given to `--csharp-style`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CSharpStyle {
+    /// `public record Order` with `init` properties
+    #[default]
+    Record,
+    /// `public class Order` with `set` properties
+    Class,
+}
+
+impl CSharpStyle {
+    fn keyword(&self) -> &'static str {
+        match self {
+            CSharpStyle::Record => "record",
+            CSharpStyle::Class => "class",
+        }
+    }
+
+    fn accessor(&self) -> &'static str {
+        match self {
+            CSharpStyle::Record => "init",
+            CSharpStyle::Class => "set",
+        }
+    }
+}
+
+/// Renders the tree as C# types for `System.Text.Json`, a type per object type, named as the
+/// Python classes are. Properties are PascalCase, with a `[JsonPropertyName]` attribute
+/// wherever that isn't the JSON key. Properties which were always present and never `null`
+/// are `required`, the others nullable.
+pub(crate) fn to_csharp_string(
+    node: &TypeScriptNode,
+    options: &RenderOptions,
+    style: CSharpStyle,
+) -> String {
+    let mut renderer = Renderer {
+        types: Declarations::new(node, options, &[]),
+        style,
+        usings: BTreeSet::new(),
+    };
+    let mut root_comment = None;
+    if node.type_signature() == &TypeScriptPrimativeType::Object {
+        renderer.declaration(node, options.root_name.clone(), true);
+    } else {
+        // only objects get a type, so the type of the root is noted instead
+        let root_type = renderer.type_name(node, &options.root_name);
+        root_comment = Some(format!("// {}: {}\n", options.root_name, root_type));
+    }
+    let mut output = String::from("#nullable enable\n");
+    if !renderer.usings.is_empty() {
+        output.push('\n');
+        for using in &renderer.usings {
+            output.push_str(&format!("using {};\n", using));
+        }
+    }
+    for declaration in root_comment.iter().chain(renderer.types.iter()) {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    output
+}
+
+struct Renderer {
+    types: Declarations,
+    style: CSharpStyle,
+    usings: BTreeSet<&'static str>,
+}
+
+impl Renderer {
+    /// The type of a value, `?` when it was seen as `null`, `hint` naming the type of an
+    /// inline object
+    fn type_name(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        let type_name = match node.type_signature() {
+            TypeScriptPrimativeType::String => "string".to_string(),
+            TypeScriptPrimativeType::Number if node.is_integer() => "long".to_string(),
+            TypeScriptPrimativeType::Number => "double".to_string(),
+            TypeScriptPrimativeType::Boolean => "bool".to_string(),
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                self.types.referenced_name(node).to_string()
+            }
+            TypeScriptPrimativeType::Object => match self.types.emitted_name(node) {
+                Some(name) => name.to_string(),
+                None => self.declaration(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array => {
+                self.usings.insert("System.Collections.Generic");
+                let elements: Vec<&TypeScriptNode> = node
+                    .elements()
+                    .iter()
+                    .filter(|element| element.type_signature() != &TypeScriptPrimativeType::Null)
+                    .collect();
+                let nullable_elements = elements.len() < node.elements().len()
+                    || elements.iter().any(|element| element.is_nullable());
+                // the types of elements mixed with others aren't declared, as they end up as
+                // JSON elements
+                let element_type = match elements.as_slice() {
+                    [element] => {
+                        let element_type = self.type_name(element, &format!("{}Item", hint));
+                        match element_type.strip_suffix('?') {
+                            Some(element_type) => element_type.to_string(),
+                            None => element_type,
+                        }
+                    }
+                    _ => self.json_element(),
+                };
+                if nullable_elements {
+                    format!("List<{}?>", element_type)
+                } else {
+                    format!("List<{}>", element_type)
+                }
+            }
+            TypeScriptPrimativeType::Null
+            | TypeScriptPrimativeType::Unknown
+            | TypeScriptPrimativeType::Union => self.json_element(),
+        };
+        if node.is_nullable() || node.type_signature() == &TypeScriptPrimativeType::Null {
+            format!("{}?", type_name)
+        } else {
+            type_name
+        }
+    }
+
+    fn json_element(&mut self) -> String {
+        self.usings.insert("System.Text.Json");
+        JSON_ELEMENT.to_string()
+    }
+
+    /// Emits the type of an object ahead of the types it refers to, returning its name. The
+    /// root is named `hint`, common types by their type name and other objects after `hint`.
+    fn declaration(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self.types.name(node, to_type_identifier(&hint), root);
+        let index = self.types.reserve();
+        // a member can't share the name of its type
+        let mut property_names = HashSet::from([name.clone()]);
+        let mut body = String::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let property_name = numbered(&to_type_identifier(key), "", &property_names);
+            property_names.insert(property_name.clone());
+            let mut property_type = self.type_name(sub_item, &format!("{}{}", name, segment(key)));
+            if property_name != key {
+                self.usings.insert("System.Text.Json.Serialization");
+                body.push_str(&format!(
+                    "{}[JsonPropertyName({})]\n",
+                    INDENT,
+                    serde_json::to_string(key).unwrap()
+                ));
+            }
+            let required = if sub_item.is_optional() || property_type.ends_with('?') {
+                if !property_type.ends_with('?') {
+                    property_type.push('?');
+                }
+                ""
+            } else {
+                "required "
+            };
+            body.push_str(&format!(
+                "{}public {}{} {} {{ get; {}; }}\n",
+                INDENT,
+                required,
+                property_type,
+                property_name,
+                self.style.accessor()
+            ));
+        }
+        let declaration = format!("public {} {}\n{{\n{}}}\n", self.style.keyword(), name, body);
+        self.types.fill(index, declaration);
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_csharp_string, CSharpStyle};
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_csharp_records() {
+        let val_tree = serde_json::from_str(
+            r#"{ "user_id": 1, "Lines": [{ "qty": 1.5, "note": null }, { "qty": 2 }], "tags": ["a", null], "meta": [1, "a"] }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            to_csharp_string(&result, &RenderOptions::default(), CSharpStyle::Record),
+            "#nullable enable
+
+using System.Collections.Generic;
+using System.Text.Json;
+using System.Text.Json.Serialization;
+
+public record DefaultType
+{
+    public required List<DefaultType_0> Lines { get; init; }
+    [JsonPropertyName(\"meta\")]
+    public required List<JsonElement> Meta { get; init; }
+    [JsonPropertyName(\"tags\")]
+    public required List<string?> Tags { get; init; }
+    [JsonPropertyName(\"user_id\")]
+    public required long UserId { get; init; }
+}
+
+public record DefaultType_0
+{
+    [JsonPropertyName(\"note\")]
+    public JsonElement? Note { get; init; }
+    [JsonPropertyName(\"qty\")]
+    public required double Qty { get; init; }
+}
+"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_types_of_mixed_elements() {
+        let val_tree = serde_json::from_str(
+            r#"{ "items": [{ "a": 1, "c": true }, { "b": "x" }], "lines": [{ "qty": 1 }, null] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &InferOptions::default());
+        result.calculate_hash();
+        assert_eq!(
+            to_csharp_string(&result, &RenderOptions::default(), CSharpStyle::Record),
+            "#nullable enable
+
+using System.Collections.Generic;
+using System.Text.Json;
+using System.Text.Json.Serialization;
+
+public record DefaultType
+{
+    [JsonPropertyName(\"items\")]
+    public required List<JsonElement> Items { get; init; }
+    [JsonPropertyName(\"lines\")]
+    public required List<DefaultTypeLinesItem?> Lines { get; init; }
+}
+
+public record DefaultTypeLinesItem
+{
+    [JsonPropertyName(\"qty\")]
+    public required double Qty { get; init; }
+}
+"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::rename::{to_camel_case, to_pascal_case};
+use crate::typescript_node::{Fingerprint, RenderOptions, TypeScriptNode};
+
+/// The declarations of the languages without inline object types, which declare a type per
+/// object type. Common types take their type names; other types are named after a hint, the
+/// path leading to them, numbered when that name is already taken.
+pub(crate) struct Declarations {
+    type_names: HashMap<Fingerprint, String>,
+    /// Names of the declarations, including those yet to be emitted
+    taken: HashSet<String>,
+    /// Common types already emitted
+    emitted: HashSet<Fingerprint>,
+    declarations: Vec<String>,
+}
+
+impl Declarations {
+    /// Plans the declarations of the types of `node`, none of which is named as the root or
+    /// any of `reserved`
+    pub(crate) fn new(node: &TypeScriptNode, options: &RenderOptions, reserved: &[&str]) -> Self {
+        let type_names = node.type_names(options);
+        Declarations {
+            taken: type_names
+                .values()
+                .cloned()
+                .chain([options.root_name.clone()])
+                .chain(reserved.iter().map(|name| name.to_string()))
+                .collect(),
+            type_names,
+            emitted: HashSet::new(),
+            declarations: Vec::new(),
+        }
+    }
+
+    /// The name of the recursive type a self-reference refers to
+    pub(crate) fn referenced_name(&self, node: &TypeScriptNode) -> &str {
+        &self.type_names[&node.hash()]
+    }
+
+    /// The name of a common type which was already emitted
+    pub(crate) fn emitted_name(&self, node: &TypeScriptNode) -> Option<&str> {
+        match self.type_names.get(&node.hash()) {
+            Some(name) if self.emitted.contains(&node.hash()) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Names the declaration of a type: the root is named `hint`, common types by their type
+    /// name, marking them emitted, and other types after `hint`
+    pub(crate) fn name(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        match self.type_names.get(&node.hash()) {
+            _ if root => hint,
+            Some(name) => {
+                self.emitted.insert(node.hash());
+                name.clone()
+            }
+            None => self.unique_name(&hint),
+        }
+    }
+
+    /// `hint`, numbered when a declaration is already named so
+    pub(crate) fn unique_name(&mut self, hint: &str) -> String {
+        let name = numbered(hint, "", &self.taken);
+        self.taken.insert(name.clone());
+        name
+    }
+
+    /// Holds the place of a declaration, so it comes ahead of those of the types it refers to
+    /// once it is filled in
+    pub(crate) fn reserve(&mut self) -> usize {
+        self.declarations.push(String::new());
+        self.declarations.len() - 1
+    }
+
+    pub(crate) fn fill(&mut self, index: usize, declaration: String) {
+        self.declarations[index] = declaration;
+    }
+
+    pub(crate) fn push(&mut self, declaration: String) {
+        self.declarations.push(declaration);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &String> {
+        self.declarations.iter()
+    }
+}
+
+/// `base`, or the first of `base{separator}2`, `base{separator}3`... which isn't taken
+pub(crate) fn numbered(base: &str, separator: &str, taken: &HashSet<String>) -> String {
+    (1..)
+        .map(|number| match number {
+            1 => base.to_string(),
+            _ => format!("{}{}{}", base, separator, number),
+        })
+        .find(|name| !taken.contains(name))
+        .unwrap()
+}
+
+/// `user-id` -> `UserId`, dropping anything which can't appear in an identifier
+pub(crate) fn to_type_identifier(name: &str) -> String {
+    to_identifier(to_pascal_case(name))
+}
+
+/// `user_id` -> `userId`, dropping anything which can't appear in an identifier
+pub(crate) fn to_member_identifier(key: &str) -> String {
+    to_identifier(to_camel_case(key))
+}
+
+fn to_identifier(name: String) -> String {
+    let mut identifier: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    if !identifier.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
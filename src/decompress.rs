@@ -0,0 +1,58 @@
+use std::io::{BufRead, BufReader};
+
+use crate::errors::ErrorKind;
+use crate::{Context, Result};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The input decompressed as it is read when it starts like a gzip or zstd stream, as it is
+/// otherwise. No JSON, or other input, starts with either.
+pub(crate) fn decompressed(mut reader: Box<dyn BufRead>) -> Result<Box<dyn BufRead>> {
+    let start = reader
+        .fill_buf()
+        .context(ErrorKind::Input.because("could not read the input"))?;
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(
+            reader,
+        ))))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        let decoder = ruzstd::decoding::StreamingDecoder::new(reader)
+            .map_err(|error| anyhow::anyhow!("{}", error))
+            .context(ErrorKind::Parse.because("could not decompress zstd"))?;
+        Ok(Box::new(BufReader::new(decoder)))
+    } else {
+        Ok(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Read, Write};
+
+    use super::decompressed;
+
+    fn read(compressed: Vec<u8>) -> String {
+        let mut content = String::new();
+        let reader: Box<dyn BufRead> = Box::new(std::io::Cursor::new(compressed));
+        decompressed(reader)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn decompresses_gzip_and_zstd() {
+        let json = r#"{ "id": 1 }"#;
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gzip.write_all(json.as_bytes()).unwrap();
+        assert_eq!(read(gzip.finish().unwrap()), json);
+        let zstd = ruzstd::encoding::compress_to_vec(
+            json.as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        assert_eq!(read(zstd), json);
+        assert_eq!(read(json.as_bytes().to_vec()), json);
+    }
+}
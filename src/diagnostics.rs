@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use itertools::Itertools;
+use serde::Serialize;
+#[cfg(feature = "cli")]
+use tracing::{event, Level};
+
+use crate::typescript_node::{Fingerprint, TypeScriptNode, TypeScriptPrimativeType};
+
+/// What a diagnostic is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// A value was seen with types which can't be merged, and is typed as a union of them
+    ConflictingTypes,
+    /// An array was only ever empty, so its elements are typed with the fallback type
+    EmptyArray,
+    /// `--sample` skipped elements of an array
+    SampledArray,
+    /// Object types of different shapes share a hash, and are kept inline
+    HashCollision,
+}
+
+/// A weak spot of the inferred types, found at a JSON path of the samples
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Where in the samples, `$.items[*].id`
+    pub path: String,
+    pub message: String,
+}
+
+/// Collects the diagnostics of inferring and rendering types, when set in the
+/// [`crate::InferOptions`] and [`crate::RenderOptions`], each reported once however many
+/// samples and targets it was found in
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    found: Mutex<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    /// The diagnostics found since the last call, by path
+    pub fn take(&self) -> Vec<Diagnostic> {
+        let mut found = self.found.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *found)
+            .into_iter()
+            .sorted_by(|a, b| a.path.cmp(&b.path).then(a.kind.cmp(&b.kind)))
+            .collect()
+    }
+
+    /// Logs the diagnostics found since the last call as warnings, at the end of a run
+    #[cfg(feature = "cli")]
+    pub(crate) fn log(&self) {
+        for diagnostic in self.take() {
+            event!(
+                Level::WARN,
+                path = diagnostic.path.as_str(),
+                "{}",
+                diagnostic.message
+            );
+        }
+    }
+
+    pub(crate) fn record(&self, kind: DiagnosticKind, path: String, message: String) {
+        let mut found = self.found.lock().unwrap_or_else(|e| e.into_inner());
+        if !found
+            .iter()
+            .any(|diagnostic| diagnostic.kind == kind && diagnostic.path == path)
+        {
+            found.push(Diagnostic {
+                kind,
+                path,
+                message,
+            });
+        }
+    }
+
+    /// Records the weak spots of a tree about to be rendered, whose hashes have been
+    /// calculated
+    pub(crate) fn inspect(&self, tree: &TypeScriptNode) {
+        let colliding = tree.colliding_hashes();
+        self.inspect_node(tree, "$".to_string(), &colliding);
+    }
+
+    fn inspect_node(&self, node: &TypeScriptNode, path: String, colliding: &HashSet<Fingerprint>) {
+        match node.type_signature() {
+            TypeScriptPrimativeType::Object => {
+                if colliding.contains(&node.hash()) {
+                    self.record(
+                        DiagnosticKind::HashCollision,
+                        path.clone(),
+                        "object type shares a hash with another of a different shape, kept inline"
+                            .to_string(),
+                    );
+                }
+                for field in node.sub_items() {
+                    let name = field.wire_name().unwrap_or_default();
+                    self.inspect_node(field, member_path(&path, name), colliding);
+                }
+            }
+            TypeScriptPrimativeType::Array if node.elements().is_empty() => self.record(
+                DiagnosticKind::EmptyArray,
+                path,
+                "only ever an empty array, its elements are typed with the fallback type"
+                    .to_string(),
+            ),
+            TypeScriptPrimativeType::Array => {
+                for element in node.elements() {
+                    self.inspect_node(element, format!("{}[*]", path), colliding);
+                }
+            }
+            TypeScriptPrimativeType::Union => {
+                // samples of different shapes are expected to make the root a union
+                if !node.is_root() {
+                    let types = node
+                        .sub_items()
+                        .iter()
+                        .map(|member| member.type_signature().as_str())
+                        .collect::<Vec<_>>();
+                    self.record(
+                        DiagnosticKind::ConflictingTypes,
+                        path.clone(),
+                        format!(
+                            "seen with {} conflicting types, {}, typed as a union",
+                            types.len(),
+                            types.join(", ")
+                        ),
+                    );
+                }
+                for member in node.sub_items() {
+                    self.inspect_node(member, path.clone(), colliding);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The JSON path of the member `name` of the value at `path`
+fn member_path(path: &str, name: &str) -> String {
+    if crate::is_identifier(name) {
+        format!("{}.{}", path, name)
+    } else {
+        format!("{}[{}]", path, serde_json::to_string(name).unwrap())
+    }
+}
+
+/// The JSON path of the value at a JSON pointer, `/items/0` being `$.items[0]`
+pub(crate) fn pointer_path(pointer: &str) -> String {
+    crate::json_path::pointer_segments(pointer)
+        .iter()
+        .fold("$".to_string(), |path, segment| {
+            if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}[{}]", path, segment)
+            } else {
+                member_path(&path, segment)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pointer_path, DiagnosticKind, Diagnostics};
+    use crate::walk_value_tree;
+
+    #[test]
+    fn reports_weak_spots_once_by_path() {
+        let samples = [
+            serde_json::json!({ "id": 1, "tags": [], "meta": { "odd key": 1 } }),
+            serde_json::json!({ "id": "a", "tags": [], "meta": { "odd key": "b" } }),
+            serde_json::json!({ "id": true, "tags": [], "meta": {} }),
+        ];
+        let mut tree = samples
+            .iter()
+            .map(|sample| walk_value_tree(sample, None))
+            .reduce(|tree, other| tree.merge(other))
+            .unwrap();
+        tree.calculate_hash();
+        let diagnostics = Diagnostics::new();
+        diagnostics.inspect(&tree);
+        diagnostics.inspect(&tree);
+        let found: Vec<(DiagnosticKind, String, String)> = diagnostics
+            .take()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.kind, diagnostic.path, diagnostic.message))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    DiagnosticKind::ConflictingTypes,
+                    "$.id".to_string(),
+                    "seen with 3 conflicting types, number, string, boolean, typed as a union"
+                        .to_string()
+                ),
+                (
+                    DiagnosticKind::ConflictingTypes,
+                    "$.meta[\"odd key\"]".to_string(),
+                    "seen with 2 conflicting types, number, string, typed as a union".to_string()
+                ),
+                (
+                    DiagnosticKind::EmptyArray,
+                    "$.tags".to_string(),
+                    "only ever an empty array, its elements are typed with the fallback type"
+                        .to_string()
+                ),
+            ]
+        );
+        assert!(diagnostics.take().is_empty());
+        assert_eq!(pointer_path("/items/0/a~1b"), "$.items[0][\"a/b\"]");
+        assert_eq!(pointer_path(""), "$");
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::{self, Display};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::Result;
+
+/// How many characters either side of a parse error a snippet shows of a long line, such as
+/// minified JSON
+const SNIPPET_CONTEXT: usize = 40;
+
+/// What went wrong, telling failures apart by exit code for scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ErrorKind {
+    /// The flags can't be used together, or ask for something which isn't there
+    Usage,
+    /// An input couldn't be read or fetched
+    Input,
+    /// An input isn't valid JSON, or whichever format was given
+    Parse,
+    /// The samples can't be described, e.g. there are none
+    Inference,
+    /// The types couldn't be rendered or formatted
+    Render,
+    /// The config is unreadable or invalid
+    Config,
+    /// The output couldn't be written
+    Output,
+    /// The output `--check` compared against is out of date
+    Stale,
+}
+
+impl ErrorKind {
+    /// The status the run exits with. 2 is also what invalid arguments exit with, 1 is left for
+    /// what the run found rather than failures: stale `check` output, a breaking `diff`, and
+    /// payloads `validate`, `verify` or `workspace` reject
+    pub(crate) fn exit_code(&self) -> u8 {
+        match self {
+            ErrorKind::Usage => 2,
+            ErrorKind::Input => 3,
+            ErrorKind::Parse => 4,
+            ErrorKind::Inference => 5,
+            ErrorKind::Render => 6,
+            ErrorKind::Config => 7,
+            ErrorKind::Output => 8,
+            ErrorKind::Stale => 1,
+        }
+    }
+
+    /// The context of an error of this kind, `message` being what is printed
+    pub(crate) fn because(self, message: impl Into<String>) -> Failure {
+        Failure {
+            kind: self,
+            message: message.into(),
+            location: None,
+        }
+    }
+}
+
+/// An error's context, tagging it with its kind
+#[derive(Debug)]
+pub(crate) struct Failure {
+    kind: ErrorKind,
+    message: String,
+    location: Option<Location>,
+}
+
+/// Where in an input parsing stopped
+#[derive(Debug)]
+struct Location {
+    /// 1-based
+    line: usize,
+    /// 1-based, in bytes as serde_json counts them
+    column: usize,
+    snippet: String,
+}
+
+impl std::error::Error for Failure {}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(location) = &self.location {
+            write!(
+                f,
+                " at line {}, column {}\n{}",
+                location.line, location.column, location.snippet
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a JSON document, an error pointing at where in it parsing stopped
+pub(crate) fn parse_json<T: DeserializeOwned>(content: &str) -> Result<T> {
+    parse_json_from(content, 1)
+}
+
+/// Parses a JSON document read from line `line_number` of a file, as with JSON Lines
+pub(crate) fn parse_json_line<T: DeserializeOwned>(content: &str, line_number: usize) -> Result<T> {
+    parse_json_from(content, line_number)
+}
+
+fn parse_json_from<T: DeserializeOwned>(content: &str, first_line: usize) -> Result<T> {
+    serde_json::from_str(content).map_err(|error| {
+        let mut failure = ErrorKind::Parse.because("could not parse json");
+        if error.line() > 0 {
+            let suffix = format!(" at line {} column {}", error.line(), error.column());
+            let message = error.to_string();
+            let reason = message.strip_suffix(&suffix).unwrap_or(&message);
+            let line = first_line + error.line() - 1;
+            let text = content.lines().nth(error.line() - 1).unwrap_or_default();
+            failure.location = Some(Location {
+                line,
+                column: error.column(),
+                snippet: snippet(text, line, error.column(), reason),
+            });
+        }
+        anyhow::Error::new(failure)
+    })
+}
+
+/// The line parsing stopped at, numbered as in a compiler's error, with a caret under the
+/// column and the reason it stopped
+fn snippet(text: &str, line: usize, column: usize, reason: &str) -> String {
+    // serde_json counts columns from 1, in bytes, which may fall inside a character
+    let boundary = |index: usize| {
+        (0..=index.min(text.len()))
+            .rev()
+            .find(|&index| text.is_char_boundary(index))
+            .unwrap_or_default()
+    };
+    let offset = boundary(column.saturating_sub(1));
+    let start = boundary(
+        text[..offset]
+            .char_indices()
+            .rev()
+            .nth(SNIPPET_CONTEXT - 1)
+            .map_or(0, |(index, _)| index),
+    );
+    let end = text[offset..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(text.len(), |(index, _)| offset + index);
+    let (before, after) = (start > 0, end < text.len());
+    // tabs are kept so the caret lines up however wide the terminal draws them
+    let indent: String = text[start..offset]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{gutter} |\n{number} | {}{}{}\n{gutter} | {}{}^ {}",
+        if before { "..." } else { "" },
+        &text[start..end],
+        if after { "..." } else { "" },
+        if before { "   " } else { "" },
+        indent,
+        reason,
+        gutter = gutter,
+        number = number,
+    )
+}
+
+/// The kind of an error, that of the outermost context tagging it
+pub(crate) fn kind_of(error: &anyhow::Error) -> Option<ErrorKind> {
+    error.downcast_ref::<Failure>().map(|failure| failure.kind)
+}
+
+/// The status a run failing with `error` exits with
+pub(crate) fn exit_code(error: &anyhow::Error) -> u8 {
+    kind_of(error).map_or(1, |kind| kind.exit_code())
+}
+
+/// Where in the input a parse error is, 1-based
+fn location(error: &anyhow::Error) -> Option<(usize, usize)> {
+    error.chain().find_map(|cause| {
+        let location = cause.downcast_ref::<Failure>()?.location.as_ref()?;
+        Some((location.line, location.column))
+    })
+}
+
+/// The error as JSON for `--error-format json`: its kind, message and causes, and where in the
+/// input it is for parse errors
+pub(crate) fn to_json(error: &anyhow::Error) -> serde_json::Value {
+    let mut report = json!({
+        "kind": kind_of(error),
+        "message": error.to_string(),
+        "causes": error.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>(),
+        "exitCode": exit_code(error),
+    });
+    if let Some((line, column)) = location(error) {
+        report["line"] = line.into();
+        report["column"] = column.into();
+    }
+    json!({ "error": report })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use serde_json::json;
+
+    use super::{exit_code, kind_of, parse_json, parse_json_line, to_json, ErrorKind};
+
+    #[test]
+    fn classifies_errors() {
+        let parse = parse_json::<serde_json::Value>("{\n  \"a\": }")
+            .context("could not generate `types.ts`")
+            .unwrap_err();
+        assert_eq!(kind_of(&parse), Some(ErrorKind::Parse));
+        assert_eq!(exit_code(&parse), 4);
+        assert_eq!(
+            to_json(&parse),
+            json!({
+                "error": {
+                    "kind": "parse",
+                    "message": "could not generate `types.ts`",
+                    "causes": [
+                        "could not parse json at line 2, column 8\n  |\n2 |   \"a\": }\n  |        ^ expected value"
+                    ],
+                    "exitCode": 4,
+                    "line": 2,
+                    "column": 8,
+                }
+            })
+        );
+        assert_eq!(exit_code(&anyhow::anyhow!("no kind")), 1);
+        assert_eq!(exit_code(&ErrorKind::Usage.because("bad flags").into()), 2);
+        assert_eq!(exit_code(&ErrorKind::Stale.because("stale").into()), 1);
+    }
+
+    #[test]
+    fn points_at_parse_errors() {
+        let error = |content| {
+            parse_json::<serde_json::Value>(content)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("{\n\t\"a\": [1,, 2]\n}"),
+            "could not parse json at line 2, column 10\n  |\n2 | \t\"a\": [1,, 2]\n  | \t        ^ expected value"
+        );
+        assert_eq!(
+            error("{ \"a\": 1"),
+            "could not parse json at line 1, column 8\n  |\n1 | { \"a\": 1\n  |        ^ EOF while parsing an object"
+        );
+        let minified = format!(
+            "{{\"a\":\"{}\",\"b\":tru,\"c\":\"{}\"}}",
+            "x".repeat(50),
+            "y".repeat(50)
+        );
+        assert_eq!(
+            error(&minified),
+            format!(
+                "could not parse json at line 1, column 66\n  |\n1 | ...{}\",\"b\":tru,\"c\":\"{}...\n  |    {}^ expected ident",
+                "x".repeat(31),
+                "y".repeat(34),
+                " ".repeat(40)
+            )
+        );
+        assert_eq!(
+            parse_json_line::<serde_json::Value>("{\"a\": }", 12).unwrap_err().to_string(),
+            "could not parse json at line 12, column 7\n   |\n12 | {\"a\": }\n   |       ^ expected value"
+        );
+    }
+}
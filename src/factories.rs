@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::typescript_node::{
+    Fingerprint, RenderContext, RenderOptions, TypeScriptNode, TypeScriptPrimativeType,
+};
+
+/// Renders a `mock{name}` factory for the root and each common type, returning a valid value
+/// built from the sampled values with any `overrides` applied on top. The values of common
+/// types are built by their factories.
+pub(crate) fn to_factory_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let context = RenderContext::new(node, options);
+    let type_names = &context.type_names;
+    let mut output = String::new();
+    for (name, named_node) in node.named_types(type_names) {
+        output.push_str(&factory(
+            name,
+            named_node,
+            &object_value(named_node, 1, type_names),
+        ));
+    }
+    output.push_str(&factory(
+        &options.root_name,
+        node,
+        &mock_value(node, 1, type_names, true),
+    ));
+    output
+}
+
+fn factory(name: &str, node: &TypeScriptNode, value: &str) -> String {
+    match node.type_signature() {
+        TypeScriptPrimativeType::Object => format!(
+            "\nexport function mock{}(overrides?: Partial<{}>): {} {{\n  return {{ ...{}, ...overrides }};\n}}\n",
+            name, name, name, value
+        ),
+        _ => format!(
+            "\nexport function mock{}(overrides?: {}): {} {{\n  return overrides ?? {};\n}}\n",
+            name, name, name, value
+        ),
+    }
+}
+
+/// A value of the node, calling the factory of the common type it is an instance of, from
+/// `type_names`, unless it is the `top` of a factory. Recursion stops at the references of a
+/// recursive type to itself, as an empty array of them or `null`.
+fn mock_value(
+    node: &TypeScriptNode,
+    indent: usize,
+    type_names: &HashMap<Fingerprint, String>,
+    top: bool,
+) -> String {
+    match node.type_signature() {
+        TypeScriptPrimativeType::String => sampled(node, "\"\""),
+        TypeScriptPrimativeType::Number => sampled(node, "0"),
+        TypeScriptPrimativeType::Boolean => sampled(node, "false"),
+        TypeScriptPrimativeType::Null => "null".to_string(),
+        TypeScriptPrimativeType::Unknown => sampled(node, "null"),
+        TypeScriptPrimativeType::Object if node.is_self_reference() => "null".to_string(),
+        TypeScriptPrimativeType::Object => match type_names.get(&node.hash()) {
+            Some(name) if !top => format!("mock{}()", name),
+            _ => object_value(node, indent, type_names),
+        },
+        TypeScriptPrimativeType::Union => match node.sub_items().first() {
+            Some(member) => mock_value(member, indent, type_names, false),
+            None => "null".to_string(),
+        },
+        TypeScriptPrimativeType::Array => match node.sub_items().first() {
+            Some(element) if !element.is_self_reference() => {
+                format!("[{}]", mock_value(element, indent, type_names, false))
+            }
+            _ => "[]".to_string(),
+        },
+    }
+}
+
+fn object_value(
+    node: &TypeScriptNode,
+    indent: usize,
+    type_names: &HashMap<Fingerprint, String>,
+) -> String {
+    if node.sub_items().is_empty() {
+        return "{}".to_string();
+    }
+    let pad = "  ".repeat(indent + 1);
+    let fields: Vec<String> = node
+        .sub_items()
+        .iter()
+        // an optional reference to the type itself is left out rather than built as `null`
+        .filter(|sub_item| !(sub_item.is_self_reference() && sub_item.is_optional()))
+        .map(|sub_item| {
+            format!(
+                "{}{}: {},\n",
+                pad,
+                serde_json::to_string(sub_item.name().unwrap_or_default()).unwrap(),
+                mock_value(sub_item, indent + 1, type_names, false)
+            )
+        })
+        .collect();
+    format!("{{\n{}{}}}", fields.concat(), "  ".repeat(indent))
+}
+
+fn sampled(node: &TypeScriptNode, fallback: &str) -> String {
+    node.example()
+        .map(|example| example.to_string())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_factory_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn factories_use_sampled_values() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1337, "currency": "USD" }], "refunded": false, "note": null }"#,
+        )
+        .unwrap();
+        let result = walk_value_tree(&val_tree, None);
+        assert_eq!(
+            to_factory_string(&result, &RenderOptions::default()),
+            "
+export function mockDefaultType(overrides?: Partial<DefaultType>): DefaultType {
+  return { ...{
+    \"note\": null,
+    \"payments\": [{
+      \"amount\": 1337,
+      \"currency\": \"USD\",
+    }],
+    \"refunded\": false,
+  }, ...overrides };
+}
+"
+        );
+    }
+
+    #[test]
+    fn factories_for_non_object_roots() {
+        let val_tree = serde_json::from_str(r#"["a", "b"]"#).unwrap();
+        let result = walk_value_tree(&val_tree, None);
+        assert_eq!(
+            to_factory_string(&result, &RenderOptions::default()),
+            "\nexport function mockDefaultType(overrides?: DefaultType): DefaultType {\n  return overrides ?? [\"a\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn factories_call_the_factories_of_common_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "lines": [{ "sku": "a" }, { "sku": "b" }], "returns": [{ "sku": "c" }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            to_factory_string(&result, &RenderOptions::default()),
+            "
+export function mockDefaultType_0(overrides?: Partial<DefaultType_0>): DefaultType_0 {
+  return { ...{
+    \"sku\": \"a\",
+  }, ...overrides };
+}
+
+export function mockDefaultType(overrides?: Partial<DefaultType>): DefaultType {
+  return { ...{
+    \"lines\": [mockDefaultType_0()],
+    \"returns\": [mockDefaultType_0()],
+  }, ...overrides };
+}
+"
+        );
+    }
+
+    #[test]
+    fn factories_stop_at_references_to_themselves() {
+        let val_tree = serde_json::from_str(
+            r#"{ "id": 1, "replies": [{ "id": 2, "replies": [{ "id": 3, "replies": [] }] }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.calculate_hash();
+        assert_eq!(to_factory_string(&result, &RenderOptions::default()),
+            "\nexport function mockDefaultType(overrides?: Partial<DefaultType>): DefaultType {\n  return { ...{\n    \"id\": 1,\n    \"replies\": [],\n  }, ...overrides };\n}\n"
+        );
+    }
+}
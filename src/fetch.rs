@@ -0,0 +1,70 @@
+use serde_json::Value;
+use std::io::Read;
+use ureq::http::Response;
+use ureq::Body;
+
+use crate::errors::{self, ErrorKind};
+use crate::{Context, Result};
+
+/// Parses a `Name: value` header given on the command line
+pub(crate) fn parse_header(header: &str) -> Result<(String, String)> {
+    let (name, value) = header.split_once(':').ok_or_else(|| {
+        ErrorKind::Usage.because(format!(
+            "header `{}` is not of the form `Name: value`",
+            header
+        ))
+    })?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Whether an `--input` is an HTTP(S) URL rather than a file
+pub(crate) fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Fetches `url` and parses the response body as JSON
+pub(crate) fn fetch_json(url: &str, headers: &[(String, String)]) -> Result<Value> {
+    let body = fetch_body(url, headers)?;
+    errors::parse_json(&body).with_context(|| format!("response from `{}` is not json", url))
+}
+
+/// Fetches `url`, asking for JSON, and returns the response body
+pub(crate) fn fetch_body(url: &str, headers: &[(String, String)]) -> Result<String> {
+    request(url, headers)?
+        .body_mut()
+        .read_to_string()
+        .with_context(|| {
+            ErrorKind::Input.because(format!("could not read response from `{}`", url))
+        })
+}
+
+/// Fetches `url`, returning a reader over the response body as it arrives
+pub(crate) fn fetch_reader(url: &str, headers: &[(String, String)]) -> Result<impl Read> {
+    Ok(request(url, headers)?.into_body().into_reader())
+}
+
+fn request(url: &str, headers: &[(String, String)]) -> Result<Response<Body>> {
+    let mut request = ureq::get(url).header("Accept", "application/json");
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request
+        .call()
+        .with_context(|| ErrorKind::Input.because(format!("could not fetch `{}`", url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_url, parse_header};
+
+    #[test]
+    fn parses_headers_and_recognises_urls() {
+        assert_eq!(
+            parse_header("Authorization: Bearer a:b").unwrap(),
+            ("Authorization".to_string(), "Bearer a:b".to_string())
+        );
+        assert!(parse_header("Authorization").is_err());
+        assert!(is_url("https://api.example.com/v1/users?page=1"));
+        assert!(!is_url("fixtures/*.json"));
+    }
+}
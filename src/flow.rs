@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::formats::FallbackType;
+use crate::typescript_node::{Fingerprint, RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const INDENT: &str = "  ";
+
+/// Renders the tree as Flow type aliases, `{root_name}` followed by each common type. Objects are
+/// exact, nullable values are maybe types and arrays are `$ReadOnlyArray`s.
+pub(crate) fn to_flow_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let type_names = node.type_names(options);
+    let mut output = String::from("// @flow\n");
+    output.push_str(&declaration(
+        &options.root_name,
+        &flow_type(node, &type_names, options, 0, true),
+        options,
+    ));
+    for (name, named_node) in node.named_types(&type_names) {
+        output.push_str(&declaration(
+            name,
+            &flow_type(named_node, &type_names, options, 0, true),
+            options,
+        ));
+    }
+    output
+}
+
+fn declaration(name: &str, flow_type: &str, options: &RenderOptions) -> String {
+    format!(
+        "\n{}type {} = {};\n",
+        options.declaration_prefix(),
+        name,
+        flow_type
+    )
+}
+
+/// The type of a value. Common object types are referred to by name, unless rendering the body
+/// of their declaration.
+fn flow_type(
+    node: &TypeScriptNode,
+    type_names: &HashMap<Fingerprint, String>,
+    options: &RenderOptions,
+    indent: usize,
+    declaration_body: bool,
+) -> String {
+    let flow_type = match node.type_signature() {
+        TypeScriptPrimativeType::String => match node.literals() {
+            Some(literals) => literals
+                .iter()
+                .map(|literal| serde_json::to_string(literal).unwrap())
+                .join(" | "),
+            None => "string".to_string(),
+        },
+        TypeScriptPrimativeType::Number => "number".to_string(),
+        TypeScriptPrimativeType::Boolean => "boolean".to_string(),
+        TypeScriptPrimativeType::Null => "null".to_string(),
+        TypeScriptPrimativeType::Unknown => fallback(options).to_string(),
+        TypeScriptPrimativeType::Union => node
+            .sub_items()
+            .iter()
+            .map(|member| flow_type(member, type_names, options, indent, false))
+            .join(" | "),
+        TypeScriptPrimativeType::Object => match type_names.get(&node.hash()) {
+            Some(name) if !declaration_body => name.clone(),
+            _ if node.sub_items().is_empty() => "{||}".to_string(),
+            _ => {
+                let mut object = String::from("{|\n");
+                for sub_item in node.sub_items() {
+                    let key = sub_item.name().unwrap_or_default();
+                    let key = if crate::is_identifier(key) {
+                        key.to_string()
+                    } else {
+                        serde_json::to_string(key).unwrap()
+                    };
+                    object.push_str(&format!(
+                        "{}{}{}{}: {},\n",
+                        INDENT.repeat(indent + 1),
+                        if options.readonly { "+" } else { "" },
+                        key,
+                        if sub_item.is_optional() { "?" } else { "" },
+                        flow_type(sub_item, type_names, options, indent + 1, false)
+                    ));
+                }
+                object.push_str(&INDENT.repeat(indent));
+                object.push_str("|}");
+                object
+            }
+        },
+        TypeScriptPrimativeType::Array => {
+            let element_types: Vec<String> = node
+                .elements()
+                .iter()
+                .map(|element| flow_type(element, type_names, options, indent, false))
+                .unique()
+                .sorted()
+                .collect();
+            match element_types.as_slice() {
+                [] => match &options.empty_array_type {
+                    Some(empty_array_type) => empty_array_type.clone(),
+                    None => format!("$ReadOnlyArray<{}>", fallback(options)),
+                },
+                _ => format!("$ReadOnlyArray<{}>", element_types.join(" | ")),
+            }
+        }
+    };
+    match node.is_nullable() {
+        true if TypeScriptNode::has_top_level_union(&flow_type) => format!("?({})", flow_type),
+        true => format!("?{}", flow_type),
+        false => flow_type,
+    }
+}
+
+/// Flow's `mixed` stands in for TypeScript's `unknown`, and `JsonValue` isn't declared
+fn fallback(options: &RenderOptions) -> &'static str {
+    match options.fallback_type {
+        FallbackType::Any => "any",
+        FallbackType::Unknown | FallbackType::JsonValue => "mixed",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_flow_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn renders_flow_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": "a" }, { "amount": 2, "note": null }], "woah lol": [null, "a"], "items": [{ "id": 1 }, { "id": 2, "extra": true }], "tags": [] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.unify_repeated_objects();
+        result.calculate_hash();
+        assert_eq!(
+            to_flow_string(&result, &RenderOptions::default()),
+            "// @flow
+
+type DefaultType = {|
+  items: $ReadOnlyArray<DefaultType_0>,
+  payments: $ReadOnlyArray<DefaultType_1>,
+  tags: $ReadOnlyArray<mixed>,
+  \"woah lol\": $ReadOnlyArray<null | string>,
+|};
+
+type DefaultType_0 = {|
+  id: number,
+  extra?: boolean,
+|};
+
+type DefaultType_1 = {|
+  amount: number,
+  note: ?string,
+|};
+"
+        );
+    }
+}
@@ -0,0 +1,428 @@
+use std::sync::OnceLock;
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A format recognised in the values of a string field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum StringFormat {
+    /// ISO 8601 calendar date, `2024-01-31`
+    Date,
+    /// ISO 8601 timestamp, `2024-01-31T12:00:00Z`
+    DateTime,
+    /// `123e4567-e89b-12d3-a456-426614174000`
+    Uuid,
+    /// `jo@example.com`
+    Email,
+    /// An absolute URL with a scheme, `https://example.com/a`
+    Url,
+    /// `192.168.0.1`
+    Ipv4,
+    /// `2001:db8::1`
+    Ipv6,
+    /// Binary data encoded as base64, `iVBORw0KGgoAAAANSUhEUgAAAAEAAAAB`
+    Base64,
+}
+
+impl StringFormat {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            StringFormat::Date => "ISO date",
+            StringFormat::DateTime => "ISO date-time",
+            StringFormat::Uuid => "UUID",
+            StringFormat::Email => "email",
+            StringFormat::Url => "URL",
+            StringFormat::Ipv4 => "IPv4",
+            StringFormat::Ipv6 => "IPv6",
+            StringFormat::Base64 => "base64",
+        }
+    }
+
+    /// Whether the format is typed by `--date-type`
+    pub(crate) fn is_date(&self) -> bool {
+        matches!(self, StringFormat::Date | StringFormat::DateTime)
+    }
+
+    /// Whether the format is typed by `--string-formats`
+    pub(crate) fn is_string_format(&self) -> bool {
+        Self::STRING_FORMATS.contains(self)
+    }
+
+    /// The name of the format in JSON Schema's `format` and in `@format` comments
+    pub(crate) fn format_name(&self) -> &'static str {
+        match self {
+            StringFormat::Date => "date",
+            StringFormat::DateTime => "date-time",
+            StringFormat::Uuid => "uuid",
+            StringFormat::Email => "email",
+            StringFormat::Url => "uri",
+            StringFormat::Ipv4 => "ipv4",
+            StringFormat::Ipv6 => "ipv6",
+            StringFormat::Base64 => "byte",
+        }
+    }
+
+    /// The name of the branded type of a format typed by `--string-formats`, `Url` rather than `URL` so it
+    /// doesn't shadow the DOM's `URL` class
+    fn brand(&self) -> &'static str {
+        match self {
+            StringFormat::Uuid => "UUID",
+            StringFormat::Email => "Email",
+            StringFormat::Url => "Url",
+            StringFormat::Ipv4 => "IPv4",
+            StringFormat::Ipv6 => "IPv6",
+            StringFormat::Date | StringFormat::DateTime | StringFormat::Base64 => "string",
+        }
+    }
+
+    /// The formats typed by `--string-formats`, in declaration order
+    pub(crate) const STRING_FORMATS: [StringFormat; 5] = [
+        StringFormat::Uuid,
+        StringFormat::Email,
+        StringFormat::Url,
+        StringFormat::Ipv4,
+        StringFormat::Ipv6,
+    ];
+}
+
+/// How strings recognised as UUIDs, emails, URLs or IP addresses are typed, as given to
+/// `--string-formats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum StringFormatMode {
+    /// A branded type per format declared alongside the types, `string & { readonly __brand: "UUID" }`
+    Branded,
+    /// `string`, with the property documented by a `/** @format uuid */` comment
+    Jsdoc,
+}
+
+impl StringFormatMode {
+    /// The type of a string in the given format
+    pub(crate) fn render(&self, format: StringFormat) -> &'static str {
+        match self {
+            StringFormatMode::Branded => format.brand(),
+            StringFormatMode::Jsdoc => "string",
+        }
+    }
+
+    /// The declaration of the branded type of a format, which has to be emitted alongside the
+    /// types using it
+    pub(crate) fn declaration(&self, format: StringFormat) -> Option<String> {
+        match self {
+            StringFormatMode::Branded if format.is_string_format() => Some(format!(
+                "type {} = string & {{ readonly __brand: \"{}\" }};\n",
+                format.brand(),
+                format.brand()
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// The declaration of the alias strings recognised as base64 are typed as, given to
+/// `--base64-type`
+pub(crate) fn base64_declaration(name: &str) -> String {
+    format!("type {} = string;\n", name)
+}
+
+/// How strings recognised as ISO dates are typed, as given to `--date-type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateType {
+    /// `string /* ISO date */`
+    Comment,
+    /// `Date`
+    Date,
+    /// A branded string type declared alongside the types, `string & { readonly __brand: "Name" }`
+    Branded(String),
+}
+
+impl DateType {
+    #[cfg(feature = "cli")]
+    pub(crate) fn parse(date_type: &str) -> std::result::Result<DateType, String> {
+        match date_type {
+            "comment" => Ok(DateType::Comment),
+            "Date" => Ok(DateType::Date),
+            name if crate::is_type_name(name) => Ok(DateType::Branded(name.to_string())),
+            _ => Err(format!(
+                "`{}` is not `comment`, `Date` or a type name",
+                date_type
+            )),
+        }
+    }
+
+    /// The type of a string in the given format
+    pub(crate) fn render(&self, format: StringFormat) -> String {
+        match self {
+            DateType::Comment => format!("string /* {} */", format.as_str()),
+            DateType::Date => "Date".to_string(),
+            DateType::Branded(name) => name.clone(),
+        }
+    }
+
+    /// The declaration of a branded type, which has to be emitted alongside the types using it
+    pub(crate) fn declaration(&self) -> Option<String> {
+        match self {
+            DateType::Branded(name) => Some(format!(
+                "type {} = string & {{ readonly __brand: \"{}\" }};\n",
+                name, name
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// How numbers which were only ever observed as integers are typed, as given to `--number-mode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum NumberMode {
+    /// `number`
+    #[default]
+    Number,
+    /// A branded `Integer` type declared alongside the types, `number & { readonly __brand: "Integer" }`
+    Branded,
+    /// `/** @type {integer} */ number`
+    Jsdoc,
+}
+
+impl NumberMode {
+    /// The type of a number, `integer` when it was only ever observed as an integer
+    pub(crate) fn render(&self, integer: bool) -> &'static str {
+        match (self, integer) {
+            (NumberMode::Branded, true) => "Integer",
+            (NumberMode::Jsdoc, true) => "/** @type {integer} */ number",
+            _ => "number",
+        }
+    }
+
+    /// The declaration of the branded type, which has to be emitted alongside the types using it
+    pub(crate) fn declaration(&self) -> Option<String> {
+        match self {
+            NumberMode::Branded => {
+                Some("type Integer = number & { readonly __brand: \"Integer\" };\n".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How integers beyond `Number.MAX_SAFE_INTEGER`, which a `number` can't hold exactly, are typed,
+/// as given to `--bigint-mode`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum BigIntMode {
+    /// `number`, losing precision, with a warning
+    #[default]
+    Number,
+    /// `bigint`, for samples parsed by a parser producing them, e.g. `json-bigint`
+    Bigint,
+    /// `string`, for samples parsed by a parser keeping them as strings, or sent as strings
+    String,
+}
+
+impl BigIntMode {
+    pub(crate) fn render(&self) -> &'static str {
+        match self {
+            BigIntMode::Number => "number",
+            BigIntMode::Bigint => "bigint",
+            BigIntMode::String => "string",
+        }
+    }
+}
+
+/// The largest integer a JavaScript `number` holds exactly, `2^53 - 1`
+pub(crate) const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Whether a number is an integer a JavaScript `number` can't hold exactly
+pub(crate) fn is_big_integer(number: &serde_json::Number) -> bool {
+    number.as_u64().is_some_and(|n| n > MAX_SAFE_INTEGER)
+        || number
+            .as_i64()
+            .is_some_and(|n| n.unsigned_abs() > MAX_SAFE_INTEGER)
+}
+
+/// How values myrrh can't describe, those nested below `--max-depth` and the elements of arrays
+/// which were only ever empty, are typed, as given to `--fallback-type`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum FallbackType {
+    /// `unknown`
+    #[default]
+    Unknown,
+    /// `any`
+    Any,
+    /// A recursive `JsonValue` type declared alongside the types, describing any JSON value
+    JsonValue,
+}
+
+impl FallbackType {
+    pub(crate) fn render(&self) -> &'static str {
+        match self {
+            FallbackType::Unknown => "unknown",
+            FallbackType::Any => "any",
+            FallbackType::JsonValue => "JsonValue",
+        }
+    }
+
+    /// The declaration of `JsonValue`, which has to be emitted alongside the types using it
+    pub(crate) fn declaration(&self) -> Option<String> {
+        match self {
+            FallbackType::JsonValue => Some(
+                "type JsonValue = string | number | boolean | null | JsonValue[] | { [key: string]: JsonValue };\n"
+                    .to_string(),
+            ),
+            FallbackType::Unknown | FallbackType::Any => None,
+        }
+    }
+}
+
+/// Recognises ISO 8601 dates and timestamps
+pub(crate) fn detect_date(value: &str) -> Option<StringFormat> {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    static DATE_TIME: OnceLock<Regex> = OnceLock::new();
+    let date =
+        DATE.get_or_init(|| Regex::new(r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])$").unwrap());
+    let date_time = DATE_TIME.get_or_init(|| {
+        Regex::new(
+            r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])[T ]([01]\d|2[0-3]):[0-5]\d(:[0-5]\d(\.\d+)?)?(Z|[+-]([01]\d|2[0-3]):?[0-5]\d)?$",
+        )
+        .unwrap()
+    });
+    if date.is_match(value) {
+        Some(StringFormat::Date)
+    } else if date_time.is_match(value) {
+        Some(StringFormat::DateTime)
+    } else {
+        None
+    }
+}
+
+/// Recognises UUIDs, email addresses, URLs and IP addresses
+pub(crate) fn detect_string_format(value: &str) -> Option<StringFormat> {
+    static UUID: OnceLock<Regex> = OnceLock::new();
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let uuid = UUID.get_or_init(|| {
+        Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$")
+            .unwrap()
+    });
+    let email = EMAIL.get_or_init(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s.]+$").unwrap());
+    let url = URL.get_or_init(|| Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*://[^\s/?#]+\S*$").unwrap());
+    if uuid.is_match(value) {
+        Some(StringFormat::Uuid)
+    } else if email.is_match(value) {
+        Some(StringFormat::Email)
+    } else if url.is_match(value) {
+        Some(StringFormat::Url)
+    } else if value.parse::<std::net::Ipv4Addr>().is_ok() {
+        Some(StringFormat::Ipv4)
+    } else if value.parse::<std::net::Ipv6Addr>().is_ok() {
+        Some(StringFormat::Ipv6)
+    } else {
+        None
+    }
+}
+
+/// Recognises base64 of at least [`MIN_BASE64_LENGTH`] characters. Mixed case and a digit or
+/// symbol are required too, so long words and hex digests aren't taken for base64.
+pub(crate) fn detect_base64(value: &str) -> Option<StringFormat> {
+    static BASE64: OnceLock<Regex> = OnceLock::new();
+    let base64 = BASE64.get_or_init(|| {
+        Regex::new(r"^([A-Za-z0-9+/]{4})*([A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$|^[A-Za-z0-9_-]+$")
+            .unwrap()
+    });
+    let has = |pattern: fn(&char) -> bool| value.chars().any(|c| pattern(&c));
+    (value.len() >= MIN_BASE64_LENGTH
+        && base64.is_match(value)
+        && has(char::is_ascii_uppercase)
+        && has(char::is_ascii_lowercase)
+        && has(|c| !c.is_ascii_alphabetic()))
+    .then_some(StringFormat::Base64)
+}
+
+/// The shortest string taken for base64, shorter tokens are more often identifiers
+const MIN_BASE64_LENGTH: usize = 32;
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_base64, detect_date, detect_string_format, is_big_integer, StringFormat};
+
+    #[test]
+    fn detects_iso_dates() {
+        assert_eq!(detect_date("2024-01-31"), Some(StringFormat::Date));
+        assert_eq!(
+            detect_date("2024-01-31T12:00:00.123Z"),
+            Some(StringFormat::DateTime)
+        );
+        assert_eq!(
+            detect_date("2024-01-31 12:00+01:00"),
+            Some(StringFormat::DateTime)
+        );
+        assert_eq!(detect_date("2024-13-01"), None);
+        assert_eq!(detect_date("31/01/2024"), None);
+        assert_eq!(detect_date("paid"), None);
+    }
+
+    #[test]
+    fn detects_string_formats() {
+        assert_eq!(
+            detect_string_format("123e4567-e89b-12d3-a456-426614174000"),
+            Some(StringFormat::Uuid)
+        );
+        assert_eq!(
+            detect_string_format("jo@example.com"),
+            Some(StringFormat::Email)
+        );
+        assert_eq!(
+            detect_string_format("https://example.com/a?b=1"),
+            Some(StringFormat::Url)
+        );
+        assert_eq!(detect_string_format("10.0.0.1"), Some(StringFormat::Ipv4));
+        assert_eq!(
+            detect_string_format("2001:db8::1"),
+            Some(StringFormat::Ipv6)
+        );
+        assert_eq!(detect_string_format("jo@example"), None);
+        assert_eq!(detect_string_format("example.com"), None);
+        assert_eq!(detect_string_format("10.0.0.256"), None);
+    }
+
+    #[test]
+    fn detects_base64() {
+        assert_eq!(
+            detect_base64("iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg=="),
+            Some(StringFormat::Base64)
+        );
+        assert_eq!(
+            detect_base64("eyJhbGciOiJIUzI1NiJ9_eyJzdWIiOiIxMjM0NTY3ODkwIn0"),
+            Some(StringFormat::Base64)
+        );
+        assert_eq!(detect_base64("aGVsbG8="), None);
+        assert_eq!(
+            detect_base64("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            None
+        );
+        assert_eq!(
+            detect_base64("AnIdentifierWhichIsRatherLongButNotEncoded"),
+            None
+        );
+        assert_eq!(
+            detect_base64("not base64 at all, though it is long enough"),
+            None
+        );
+    }
+
+    #[test]
+    fn detects_big_integers() {
+        let number = |json| serde_json::from_str::<serde_json::Number>(json).unwrap();
+        assert!(!is_big_integer(&number("9007199254740991")));
+        assert!(is_big_integer(&number("9007199254740992")));
+        assert!(is_big_integer(&number("1234567890123456789")));
+        assert!(is_big_integer(&number("-9007199254740992")));
+        assert!(!is_big_integer(&number("-9007199254740991")));
+        assert!(!is_big_integer(&number("9007199254740992.5")));
+    }
+}
@@ -0,0 +1,270 @@
+use itertools::Itertools;
+
+use crate::declarations::Declarations;
+use crate::naming::segment;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const INDENT: &str = "  ";
+
+/// The scalar values GraphQL has no type for are given: `null` on its own, arrays mixing
+/// types, unions of anything but objects, empty objects and values below `--max-depth`
+const JSON_SCALAR: &str = "JSON";
+
+/// Renders the tree as GraphQL SDL, a `type` per object type as GraphQL has no inline object
+/// types. Common types take their usual names; other objects are named after the path leading
+/// to them, `{root_name}Order` for the object under `order`. Fields which were always present
+/// and never `null` are non-null.
+pub(crate) fn to_graphql_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let mut renderer = Renderer {
+        declarations: Declarations::new(node, options, &[]),
+        uses_json: false,
+    };
+    let mut output = String::new();
+    if node.type_signature() == &TypeScriptPrimativeType::Object && !node.sub_items().is_empty() {
+        renderer.object_type(node, options.root_name.clone(), true);
+    } else if is_object_union(node) {
+        let name = renderer.type_name(node, options.root_name.clone(), true);
+        renderer.union_type(node.sub_items().iter(), name);
+    } else {
+        // only object types can be declared, so the type of the root is noted instead
+        let field_type = renderer.field_type(node, &options.root_name);
+        output.push_str(&format!("# {}: {}\n", options.root_name, field_type));
+    }
+    if renderer.uses_json {
+        renderer
+            .declarations
+            .push(format!("scalar {}\n", JSON_SCALAR));
+    }
+    for declaration in renderer.declarations.iter() {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(declaration);
+    }
+    output
+}
+
+/// `user-id` -> `user_id`, as names are limited to ASCII letters, digits and `_`
+fn to_graphql_name(name: &str) -> String {
+    let mut graphql_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !graphql_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        graphql_name.insert(0, '_');
+    }
+    graphql_name
+}
+
+/// Whether a union can be declared as one, its members all being object types
+fn is_object_union(node: &TypeScriptNode) -> bool {
+    node.type_signature() == &TypeScriptPrimativeType::Union
+        && node.sub_items().iter().all(is_object_type)
+}
+
+/// Whether a value is typed as a declared object type, which unions can hold
+fn is_object_type(node: &TypeScriptNode) -> bool {
+    node.type_signature() == &TypeScriptPrimativeType::Object
+        && (node.is_self_reference() || !node.sub_items().is_empty())
+}
+
+struct Renderer {
+    declarations: Declarations,
+    uses_json: bool,
+}
+
+impl Renderer {
+    /// The type of a field, non-null unless it was missing from some samples or seen as
+    /// `null`, `hint` naming the type of an inline object
+    fn field_type(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        let named_type = self.named_type(node, hint);
+        if node.is_nullable()
+            || node.is_optional()
+            || node.type_signature() == &TypeScriptPrimativeType::Null
+        {
+            named_type
+        } else {
+            format!("{}!", named_type)
+        }
+    }
+
+    /// The type of a value, without its nullability
+    fn named_type(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        match node.type_signature() {
+            TypeScriptPrimativeType::String => "String".to_string(),
+            TypeScriptPrimativeType::Number if node.is_integer() => "Int".to_string(),
+            TypeScriptPrimativeType::Number => "Float".to_string(),
+            TypeScriptPrimativeType::Boolean => "Boolean".to_string(),
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                self.declarations.referenced_name(node).to_string()
+            }
+            TypeScriptPrimativeType::Object if !node.sub_items().is_empty() => {
+                match self.declarations.emitted_name(node) {
+                    Some(name) => name.to_string(),
+                    None => self.object_type(node, hint.to_string(), false),
+                }
+            }
+            TypeScriptPrimativeType::Array => {
+                let element_hint = format!("{}Item", hint);
+                let has_null = node
+                    .elements()
+                    .iter()
+                    .any(|element| element.type_signature() == &TypeScriptPrimativeType::Null);
+                let elements: Vec<&TypeScriptNode> = node
+                    .elements()
+                    .iter()
+                    .filter(|element| element.type_signature() != &TypeScriptPrimativeType::Null)
+                    .collect();
+                // only the types of the elements which end up in the list are declared
+                let element_type = match elements.as_slice() {
+                    [element] => self.named_type(element, &element_hint),
+                    [_, _, ..] if elements.iter().all(|element| is_object_type(element)) => {
+                        let name = self
+                            .declarations
+                            .unique_name(&to_graphql_name(&format!("{}Union", hint)));
+                        self.union_type(elements.iter().copied(), name)
+                    }
+                    _ => {
+                        self.uses_json = true;
+                        return format!("[{}]", JSON_SCALAR);
+                    }
+                };
+                if has_null || elements.iter().any(|element| element.is_nullable()) {
+                    format!("[{}]", element_type)
+                } else {
+                    format!("[{}!]", element_type)
+                }
+            }
+            TypeScriptPrimativeType::Union if is_object_union(node) => {
+                let name = self.type_name(node, hint.to_string(), false);
+                self.union_type(node.sub_items().iter(), name)
+            }
+            TypeScriptPrimativeType::Object
+            | TypeScriptPrimativeType::Union
+            | TypeScriptPrimativeType::Null
+            | TypeScriptPrimativeType::Unknown => {
+                self.uses_json = true;
+                JSON_SCALAR.to_string()
+            }
+        }
+    }
+
+    /// Emits the type of an object ahead of the types it refers to, returning its name. The
+    /// root is named `hint`, common types by their type name and other objects after `hint`.
+    fn object_type(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self.type_name(node, hint, root);
+        let index = self.declarations.reserve();
+        let mut body = String::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let field_type = self.field_type(sub_item, &format!("{}{}", name, segment(key)));
+            body.push_str(&format!(
+                "{}{}: {}\n",
+                INDENT,
+                to_graphql_name(key),
+                field_type
+            ));
+        }
+        self.declarations
+            .fill(index, format!("type {} {{\n{}}}\n", name, body));
+        name
+    }
+
+    /// Emits a union named `name` of object types ahead of its members, returning its name
+    fn union_type<'a>(
+        &mut self,
+        members: impl Iterator<Item = &'a TypeScriptNode>,
+        name: String,
+    ) -> String {
+        let index = self.declarations.reserve();
+        let members: Vec<String> = members
+            .map(|member| self.named_type(member, &format!("{}Variant", name)))
+            .unique()
+            .collect();
+        self.declarations
+            .fill(index, format!("union {} = {}\n", name, members.join(" | ")));
+        name
+    }
+
+    /// The name a type is declared under: the root is named `hint`, common types by their type
+    /// name and other types after `hint`
+    fn type_name(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        self.declarations.name(node, to_graphql_name(&hint), root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_graphql_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_graphql_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": null }, { "amount": 2.5, "note": "a", "refund": { "id": 1 } }], "tags": ["a", null], "order-meta": { "lines": [1, "a"], "extra": null } }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.unify_repeated_objects();
+        result.calculate_hash();
+        assert_eq!(
+            to_graphql_string(&result, &RenderOptions::default()),
+            "type DefaultType {
+  order_meta: DefaultTypeOrderMeta!
+  payments: [DefaultType_0!]!
+  tags: [String]!
+}
+
+type DefaultTypeOrderMeta {
+  extra: JSON
+  lines: [JSON]!
+}
+
+type DefaultType_0 {
+  amount: Float!
+  note: String
+  refund: DefaultType_0Refund
+}
+
+type DefaultType_0Refund {
+  id: Int!
+}
+
+scalar JSON
+"
+        );
+    }
+
+    #[test]
+    fn declares_arrays_of_object_types_as_lists_of_a_union() {
+        let val_tree =
+            serde_json::from_str(r#"{ "items": [{ "a": 1, "c": true }, { "b": "x", "d": [1] }] }"#)
+                .unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &InferOptions::default());
+        result.calculate_hash();
+        assert_eq!(
+            to_graphql_string(&result, &RenderOptions::default()),
+            "type DefaultType {
+  items: [DefaultTypeItemsUnion!]!
+}
+
+union DefaultTypeItemsUnion = DefaultTypeItemsUnionVariant | DefaultTypeItemsUnionVariant2
+
+type DefaultTypeItemsUnionVariant {
+  a: Float!
+  c: Boolean!
+}
+
+type DefaultTypeItemsUnionVariant2 {
+  b: String!
+  d: [Float!]!
+}
+"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::typescript_node::{
+    Fingerprint, RenderContext, RenderOptions, TypeScriptNode, TypeScriptPrimativeType,
+};
+
+/// Renders an `is{name}` type guard for the root and each common type, performing the
+/// structural checks implied by the inferred tree, so consumers get runtime narrowing without a
+/// validation library. The guards of types referring to common types call theirs.
+pub(crate) fn to_guard_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let context = RenderContext::new(node, options);
+    let type_names = &context.type_names;
+    let mut output = String::new();
+    for (name, named_node) in node.named_types(type_names) {
+        output.push_str(&guard(
+            name,
+            &object_check(named_node, "v", 0, &field_name, type_names),
+        ));
+    }
+    output.push_str(&guard(
+        &options.root_name,
+        &check(node, "v", 0, &field_name, type_names, true),
+    ));
+    output
+}
+
+fn guard(name: &str, check: &str) -> String {
+    format!(
+        "\nexport function is{}(v: unknown): v is {} {{\n  return {};\n}}\n",
+        name, name, check
+    )
+}
+
+pub(crate) fn check_expression(node: &TypeScriptNode, value: &str, depth: usize) -> String {
+    check_expression_with_keys(node, value, depth, &field_name)
+}
+
+fn field_name(sub_item: &TypeScriptNode) -> &str {
+    sub_item.name().unwrap_or_default()
+}
+
+/// Like [`check_expression`], reading each field from the key chosen by `key`
+pub(crate) fn check_expression_with_keys(
+    node: &TypeScriptNode,
+    value: &str,
+    depth: usize,
+    key: &dyn Fn(&TypeScriptNode) -> &str,
+) -> String {
+    check(node, value, depth, key, &HashMap::new(), false)
+}
+
+/// The check of a value against the node, calling the guard of the common type it is an
+/// instance of, from `type_names`, unless it is the `top` of a guard
+fn check(
+    node: &TypeScriptNode,
+    value: &str,
+    depth: usize,
+    key: &dyn Fn(&TypeScriptNode) -> &str,
+    type_names: &HashMap<Fingerprint, String>,
+    top: bool,
+) -> String {
+    let check = match node.type_signature() {
+        TypeScriptPrimativeType::String => format!("typeof {} === \"string\"", value),
+        TypeScriptPrimativeType::Number => format!("typeof {} === \"number\"", value),
+        TypeScriptPrimativeType::Boolean => format!("typeof {} === \"boolean\"", value),
+        TypeScriptPrimativeType::Null => format!("{} === null", value),
+        TypeScriptPrimativeType::Unknown => format!("{} !== undefined", value),
+        TypeScriptPrimativeType::Object => match type_names.get(&node.hash()) {
+            Some(name) if !top => format!("is{}({})", name, value),
+            _ => object_check(node, value, depth, key, type_names),
+        },
+        TypeScriptPrimativeType::Union => format!(
+            "({})",
+            node.sub_items()
+                .iter()
+                .map(|member| check(member, value, depth, key, type_names, false))
+                .join(" || ")
+        ),
+        TypeScriptPrimativeType::Array => {
+            let item = format!("item{}", depth);
+            let element_checks: Vec<String> = node
+                .elements()
+                .iter()
+                .map(|sub_item| check(sub_item, &item, depth + 1, key, type_names, false))
+                .sorted()
+                .dedup()
+                .collect();
+            if element_checks.is_empty() {
+                format!("Array.isArray({})", value)
+            } else {
+                format!(
+                    "(Array.isArray({}) && ({} as unknown[]).every(({}: unknown) => {}))",
+                    value,
+                    value,
+                    item,
+                    element_checks.join(" || ")
+                )
+            }
+        }
+    };
+    let check = if node.is_nullable() {
+        format!("({} === null || {})", value, check)
+    } else {
+        check
+    };
+    if node.is_optional() {
+        format!("({} === undefined || {})", value, check)
+    } else {
+        check
+    }
+}
+
+fn object_check(
+    node: &TypeScriptNode,
+    value: &str,
+    depth: usize,
+    key: &dyn Fn(&TypeScriptNode) -> &str,
+    type_names: &HashMap<Fingerprint, String>,
+) -> String {
+    let mut checks = vec![
+        format!("typeof {} === \"object\"", value),
+        format!("{} !== null", value),
+        format!("!Array.isArray({})", value),
+    ];
+    for sub_item in node.sub_items() {
+        let field = format!(
+            "({} as Record<string, unknown>)[{}]",
+            value,
+            serde_json::to_string(key(sub_item)).unwrap()
+        );
+        checks.push(check(sub_item, &field, depth, key, type_names, false));
+    }
+    format!("({})", checks.join(" && "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_guard_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn guards_objects_and_arrays() {
+        let val_tree =
+            serde_json::from_str(r#"{ "payments": [{ "amount": 1 }, "n/a"], "note": null }"#)
+                .unwrap();
+        let result = walk_value_tree(&val_tree, None);
+        assert_eq!(
+            to_guard_string(&result, &RenderOptions::default()),
+            "\nexport function isDefaultType(v: unknown): v is DefaultType {\n  return (typeof v === \"object\" && v !== null && !Array.isArray(v) && (v as Record<string, unknown>)[\"note\"] === null && (Array.isArray((v as Record<string, unknown>)[\"payments\"]) && ((v as Record<string, unknown>)[\"payments\"] as unknown[]).every((item0: unknown) => (typeof item0 === \"object\" && item0 !== null && !Array.isArray(item0) && typeof (item0 as Record<string, unknown>)[\"amount\"] === \"number\") || typeof item0 === \"string\")));\n}\n"
+        );
+    }
+
+    #[test]
+    fn guards_call_the_guards_of_common_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "lines": [{ "sku": "a" }, { "sku": "b" }], "returns": [{ "sku": "c" }], "note": null }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            to_guard_string(&result, &RenderOptions::default()),
+            "\nexport function isDefaultType_0(v: unknown): v is DefaultType_0 {\n  return (typeof v === \"object\" && v !== null && !Array.isArray(v) && typeof (v as Record<string, unknown>)[\"sku\"] === \"string\");\n}\n\nexport function isDefaultType(v: unknown): v is DefaultType {\n  return (typeof v === \"object\" && v !== null && !Array.isArray(v) && (Array.isArray((v as Record<string, unknown>)[\"lines\"]) && ((v as Record<string, unknown>)[\"lines\"] as unknown[]).every((item0: unknown) => isDefaultType_0(item0))) && (v as Record<string, unknown>)[\"note\"] === null && (Array.isArray((v as Record<string, unknown>)[\"returns\"]) && ((v as Record<string, unknown>)[\"returns\"] as unknown[]).every((item0: unknown) => isDefaultType_0(item0))));\n}\n"
+        );
+    }
+
+    #[test]
+    fn guards_recurse_through_themselves() {
+        let val_tree = serde_json::from_str(
+            r#"{ "id": 1, "replies": [{ "id": 2, "replies": [{ "id": 3, "replies": [] }] }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.calculate_hash();
+        assert_eq!(to_guard_string(&result, &RenderOptions::default()),
+            "\nexport function isDefaultType(v: unknown): v is DefaultType {\n  return (typeof v === \"object\" && v !== null && !Array.isArray(v) && typeof (v as Record<string, unknown>)[\"id\"] === \"number\" && (Array.isArray((v as Record<string, unknown>)[\"replies\"]) && ((v as Record<string, unknown>)[\"replies\"] as unknown[]).every((item0: unknown) => isDefaultType(item0))));\n}\n"
+        );
+    }
+}
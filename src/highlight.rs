@@ -0,0 +1,112 @@
+const RESET: &str = "\x1b[0m";
+const KEYWORD: &str = "\x1b[35m";
+const TYPE_NAME: &str = "\x1b[33m";
+const PRIMITIVE: &str = "\x1b[36m";
+const STRING: &str = "\x1b[32m";
+const COMMENT: &str = "\x1b[90m";
+
+const KEYWORDS: [&str; 12] = [
+    "type",
+    "interface",
+    "export",
+    "import",
+    "from",
+    "readonly",
+    "const",
+    "function",
+    "return",
+    "class",
+    "enum",
+    "extends",
+];
+
+const PRIMITIVES: [&str; 9] = [
+    "string",
+    "number",
+    "boolean",
+    "null",
+    "unknown",
+    "any",
+    "bigint",
+    "undefined",
+    "never",
+];
+
+/// Colours generated code for a terminal: keywords, primitives and other type names, string
+/// literals and comments. Tuned for TypeScript, other languages only get their strings and
+/// `//` or `/* */` comments picked out reliably.
+pub(crate) fn highlight(code: &str) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut highlighted = String::with_capacity(code.len());
+    let mut i = 0;
+    let paint = |highlighted: &mut String, colour: &str, token: &[char]| {
+        highlighted.push_str(colour);
+        highlighted.extend(token);
+        highlighted.push_str(RESET);
+    };
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                paint(&mut highlighted, COMMENT, &chars[start..i]);
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i - 1] == '*' && chars[i] == '/' && i > start + 2)
+                {
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                paint(&mut highlighted, COMMENT, &chars[start..i]);
+            }
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' && chars[i] != '\n' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                paint(&mut highlighted, STRING, &chars[start..i]);
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let followed_by_colon =
+                    chars[i..].iter().find(|c| !c.is_whitespace() && **c != '?') == Some(&':');
+                if KEYWORDS.contains(&word.as_str()) && !followed_by_colon {
+                    paint(&mut highlighted, KEYWORD, &chars[start..i]);
+                } else if PRIMITIVES.contains(&word.as_str()) && !followed_by_colon {
+                    paint(&mut highlighted, PRIMITIVE, &chars[start..i]);
+                } else if c.is_uppercase() && !followed_by_colon {
+                    paint(&mut highlighted, TYPE_NAME, &chars[start..i]);
+                } else {
+                    highlighted.push_str(&word);
+                }
+            }
+            c => {
+                highlighted.push(c);
+                i += 1;
+            }
+        }
+    }
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlight;
+
+    #[test]
+    fn highlights_typescript() {
+        assert_eq!(
+            highlight("/** e.g. 1 */\ntype Order = {\n  type: \"a\\\"b\";\n  total: number;\n};\n"),
+            "\x1b[90m/** e.g. 1 */\x1b[0m\n\x1b[35mtype\x1b[0m \x1b[33mOrder\x1b[0m = {\n  type: \x1b[32m\"a\\\"b\"\x1b[0m;\n  total: \x1b[36mnumber\x1b[0m;\n};\n"
+        );
+    }
+}
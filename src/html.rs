@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::schema::child_path;
+
+/// Distinct example values kept per field
+const MAX_EXAMPLES: usize = 5;
+
+/// What was observed at one JSON path across the whole input
+#[derive(Default)]
+struct FieldStats {
+    occurrences: usize,
+    types: BTreeMap<&'static str, usize>,
+    examples: Vec<String>,
+    number_range: Option<(f64, f64)>,
+    length_range: Option<(usize, usize)>,
+    /// Object members by key, array elements under `[*]`
+    children: BTreeMap<String, FieldStats>,
+}
+
+impl FieldStats {
+    fn observe(&mut self, value: &Value) {
+        self.occurrences += 1;
+        let type_name = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        *self.types.entry(type_name).or_default() += 1;
+        match value {
+            Value::Object(o) => {
+                for (k, v) in o {
+                    self.children.entry(k.clone()).or_default().observe(v);
+                }
+            }
+            Value::Array(a) => {
+                for v in a {
+                    self.children
+                        .entry("[*]".to_string())
+                        .or_default()
+                        .observe(v);
+                }
+            }
+            Value::Number(n) => {
+                if let Some(n) = n.as_f64() {
+                    let (min, max) = self.number_range.unwrap_or((n, n));
+                    self.number_range = Some((min.min(n), max.max(n)));
+                }
+                self.add_example(value);
+            }
+            Value::String(s) => {
+                let length = s.chars().count();
+                let (min, max) = self.length_range.unwrap_or((length, length));
+                self.length_range = Some((min.min(length), max.max(length)));
+                self.add_example(value);
+            }
+            Value::Bool(_) => self.add_example(value),
+            Value::Null => (),
+        }
+    }
+
+    fn add_example(&mut self, value: &Value) {
+        let example = value.to_string();
+        if self.examples.len() < MAX_EXAMPLES && !self.examples.contains(&example) {
+            self.examples.push(example);
+        }
+    }
+
+    fn type_string(&self) -> String {
+        self.types.keys().join(" | ")
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = vec![format!("seen {}×", self.occurrences)];
+        if let Some((min, max)) = self.number_range {
+            summary.push(format!("{} to {}", min, max));
+        }
+        if let Some((min, max)) = self.length_range {
+            summary.push(format!("length {} to {}", min, max));
+        }
+        summary.join(" · ")
+    }
+}
+
+/// Renders a self-contained HTML page with a collapsible, searchable tree of the fields in
+/// the samples, with their types, example values and statistics
+pub(crate) fn to_html_report(samples: &[Value], root_name: &str) -> String {
+    let mut root = FieldStats::default();
+    for sample in samples {
+        root.observe(sample);
+    }
+    let mut tree = String::new();
+    render_field(&mut tree, root_name, "$", &root, false);
+    format!("{}{}{}", HTML_HEAD, tree, HTML_TAIL)
+}
+
+fn render_field(out: &mut String, name: &str, path: &str, stats: &FieldStats, optional: bool) {
+    let has_children = !stats.children.is_empty();
+    out.push_str(&format!(
+        "<details{} data-path=\"{}\"><summary><span class=\"name\">{}{}</span>: <span class=\"type\">{}</span> <span class=\"stats\">{}</span></summary>\n",
+        if has_children { " open" } else { "" },
+        escape_html(path),
+        escape_html(name),
+        if optional { "?" } else { "" },
+        escape_html(&stats.type_string()),
+        escape_html(&stats.summary())
+    ));
+    if !stats.examples.is_empty() {
+        let examples = stats
+            .examples
+            .iter()
+            .map(|example| format!("<code>{}</code>", escape_html(&truncate(example))))
+            .join(", ");
+        out.push_str(&format!(
+            "<div class=\"examples\">e.g. {}</div>\n",
+            examples
+        ));
+    }
+    let objects = stats.types.get("object").copied().unwrap_or_default();
+    for (key, child) in &stats.children {
+        if key == "[*]" {
+            render_field(out, key, &format!("{}[*]", path), child, false);
+        } else {
+            let optional = child.occurrences < objects;
+            render_field(out, key, &child_path(path, key), child, optional);
+        }
+    }
+    out.push_str("</details>\n");
+}
+
+fn truncate(example: &str) -> String {
+    if example.chars().count() > 80 {
+        format!("{}…", example.chars().take(80).collect::<String>())
+    } else {
+        example.to_string()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>myrrh schema report</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+#search { width: 100%; max-width: 32rem; padding: 0.4rem; margin-bottom: 1rem; }
+details { margin-left: 1.25rem; }
+#tree > details { margin-left: 0; }
+summary { cursor: pointer; padding: 0.1rem 0; }
+.name { font-family: ui-monospace, monospace; font-weight: 600; }
+.type { font-family: ui-monospace, monospace; color: #0550ae; }
+.stats { color: #656d76; font-size: 0.85em; }
+.examples { margin-left: 1.25rem; color: #656d76; font-size: 0.85em; }
+</style>
+</head>
+<body>
+<input id="search" type="search" placeholder="Search fields">
+<div id="tree">
+"#;
+
+const HTML_TAIL: &str = r##"</div>
+<script>
+const search = document.getElementById("search");
+search.addEventListener("input", () => {
+  const query = search.value.toLowerCase();
+  // children come before their parents, so a parent can see whether any child matched
+  const fields = Array.from(document.querySelectorAll("#tree details")).reverse();
+  for (const field of fields) {
+    const children = Array.from(field.querySelectorAll(":scope > details"));
+    const matches = field.dataset.path.toLowerCase().includes(query)
+      || children.some((child) => !child.hidden);
+    field.hidden = query !== "" && !matches;
+    if (query !== "" && matches && children.length > 0) field.open = true;
+  }
+});
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::to_html_report;
+
+    #[test]
+    fn reports_fields_with_statistics() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": "<b>" }, { "amount": 20 }] }"#,
+        )
+        .unwrap();
+        let report = to_html_report(&[val_tree], "DefaultType");
+        assert!(report.contains(
+            "<details data-path=\"$.payments[*].amount\"><summary><span class=\"name\">amount</span>: <span class=\"type\">number</span> <span class=\"stats\">seen 2× · 1 to 20</span></summary>\n<div class=\"examples\">e.g. <code>1</code>, <code>20</code></div>\n"
+        ));
+        assert!(report.contains("<span class=\"name\">note?</span>"));
+        assert!(report.contains("<code>&quot;&lt;b&gt;&quot;</code>"));
+    }
+}
@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The keys seen during a walk, each kept once and shared by every node named after it, so the
+/// objects of a large array don't each hold a copy of every key. Names are interned once the
+/// walk is done, so the threads walking the elements of a large array never wait on each other.
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    names: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// The shared copy of `name`, which is `name` itself the first time it is seen
+    pub(crate) fn intern(&mut self, name: &Arc<str>) -> Arc<str> {
+        if let Some(interned) = self.names.get(name) {
+            return Arc::clone(interned);
+        }
+        self.names.insert(Arc::clone(name));
+        Arc::clone(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+    use std::sync::Arc;
+
+    #[test]
+    fn shares_one_allocation_per_key() {
+        let mut interner = Interner::default();
+        let id = interner.intern(&Arc::from("id"));
+        assert!(Arc::ptr_eq(&id, &interner.intern(&Arc::from("id"))));
+        assert!(!Arc::ptr_eq(&id, &interner.intern(&Arc::from("name"))));
+        assert_eq!(&*interner.intern(&Arc::from("name")), "name");
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::{self, ErrorKind};
+use crate::typescript_node::TypeScriptNode;
+use crate::{Context, Result};
+
+/// Version of the IR format, bumped whenever a field changes meaning
+const IR_VERSION: u64 = 1;
+
+#[derive(Serialize)]
+struct IrDocument<'a> {
+    version: u64,
+    tree: &'a TypeScriptNode,
+}
+
+/// Renders the inferred tree as the IR, a JSON document holding the tree as it was before any
+/// rendering options were applied, so it can be rendered again later with `--from-ir`
+pub(crate) fn to_ir_string(node: &TypeScriptNode) -> String {
+    let document = IrDocument {
+        version: IR_VERSION,
+        tree: node,
+    };
+    let mut output = serde_json::to_string_pretty(&document).unwrap();
+    output.push('\n');
+    output
+}
+
+/// Reads back a tree saved with `--emit ir`
+pub(crate) fn from_ir_str(content: &str) -> Result<TypeScriptNode> {
+    from_ir_value(errors::parse_json(content)?)
+}
+
+/// Whether a JSON document is the `{ "version", "tree" }` envelope of the IR
+pub(crate) fn is_ir_document(document: &Value) -> bool {
+    document.as_object().is_some_and(|object| {
+        object.len() == 2 && object.contains_key("version") && object.contains_key("tree")
+    })
+}
+
+pub(crate) fn from_ir_value(mut document: Value) -> Result<TypeScriptNode> {
+    match document.get("version").and_then(Value::as_u64) {
+        Some(IR_VERSION) => (),
+        Some(version) => anyhow::bail!(ErrorKind::Parse.because(format!(
+            "IR version {} is not supported, only version {}",
+            version, IR_VERSION
+        ))),
+        None => {
+            anyhow::bail!(ErrorKind::Parse.because("not a myrrh IR document, there is no `version`"))
+        }
+    }
+    serde_json::from_value(document["tree"].take())
+        .context(ErrorKind::Parse.because("could not read the IR tree"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_ir_str, to_ir_string};
+    use crate::typescript_node::TypeScriptNode;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn renders_the_same_types_after_a_round_trip() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": null }, { "amount": 2, "note": "a" }], "woah lol": { "amount": 3 } }"#,
+        )
+        .unwrap();
+        let tree = walk_value_tree(&val_tree, None);
+        let mut read_back = from_ir_str(&to_ir_string(&tree)).unwrap();
+        let mut tree = tree;
+        tree.calculate_hash();
+        read_back.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&read_back),
+            TypeScriptNode::to_type_string(&tree)
+        );
+        assert!(from_ir_str(r#"{ "version": 2, "tree": {} }"#).is_err());
+    }
+
+    #[test]
+    fn holds_array_elements_of_different_types_as_a_union() {
+        let val_tree = serde_json::from_str(r#"{ "tags": [1, "a", 2] }"#).unwrap();
+        let ir: serde_json::Value =
+            serde_json::from_str(&to_ir_string(&walk_value_tree(&val_tree, None))).unwrap();
+        let element = &ir["tree"]["sub_items"][0]["sub_items"];
+        assert_eq!(element.as_array().unwrap().len(), 1);
+        assert_eq!(element[0]["type"], "union");
+        assert_eq!(element[0]["sub_items"][0]["type"], "number");
+        assert_eq!(element[0]["sub_items"][0]["occurrences"], 2);
+        assert_eq!(element[0]["sub_items"][1]["type"], "string");
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::{BTreeSet, HashSet};
+
+use clap::ValueEnum;
+
+use crate::declarations::{numbered, to_member_identifier, to_type_identifier, Declarations};
+use crate::naming::segment;
+use crate::rename::to_pascal_case;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const KEYWORDS: [&str; 53] = [
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+const INDENT: &str = "    ";
+
+/// The type of values Java has no type for: `null` on its own, arrays mixing types, unions and
+/// values below `--max-depth`
+const JSON_NODE: &str = "JsonNode";
+
+/// How the Java types are declared, as given to `--java-style`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum JavaStyle {
+    /// Java 17 `record Order(...)`
+    #[default]
+    Record,
+    /// `class Order` with private fields, getters and setters
+    Pojo,
+}
+
+/// Renders the tree as Java types for Jackson, a type per object type, named as the Python
+/// classes are. Only the root is `public`, so the output compiles as a single file named after
+/// it. Fields are camelCase, with a `@JsonProperty` annotation wherever that isn't the JSON key,
+/// and boxed wherever the value was missing or `null` in some samples.
+pub(crate) fn to_java_string(
+    node: &TypeScriptNode,
+    options: &RenderOptions,
+    style: JavaStyle,
+) -> String {
+    let mut renderer = Renderer {
+        types: Declarations::new(node, options, &[]),
+        style,
+        imports: BTreeSet::new(),
+    };
+    let mut root_comment = None;
+    if node.type_signature() == &TypeScriptPrimativeType::Object {
+        renderer.declaration(node, options.root_name.clone(), true);
+    } else {
+        // only objects get a type, so the type of the root is noted instead
+        let root_type = renderer.type_name(node, &options.root_name, false);
+        root_comment = Some(format!("// {}: {}\n", options.root_name, root_type));
+    }
+    let mut output = String::new();
+    for import in &renderer.imports {
+        output.push_str(&format!("import {};\n", import));
+    }
+    for declaration in root_comment.iter().chain(renderer.types.iter()) {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(declaration);
+    }
+    output
+}
+
+/// `user_id` -> `userId`, with a `_` appended to keywords
+fn to_java_field_name(key: &str) -> String {
+    let mut field_name = to_member_identifier(key);
+    if KEYWORDS.contains(&field_name.as_str()) {
+        field_name.push('_');
+    }
+    field_name
+}
+
+/// The wrapper of a primitive, for values which can be `null` and the elements of lists
+fn boxed(type_name: String) -> String {
+    match type_name.as_str() {
+        "long" => "Long".to_string(),
+        "double" => "Double".to_string(),
+        "boolean" => "Boolean".to_string(),
+        _ => type_name,
+    }
+}
+
+struct Renderer {
+    types: Declarations,
+    style: JavaStyle,
+    imports: BTreeSet<&'static str>,
+}
+
+impl Renderer {
+    /// The type of a value, boxed when it was seen as `null` or `nullable` is set, `hint` naming
+    /// the type of an inline object
+    fn type_name(&mut self, node: &TypeScriptNode, hint: &str, nullable: bool) -> String {
+        let type_name = match node.type_signature() {
+            TypeScriptPrimativeType::String => "String".to_string(),
+            TypeScriptPrimativeType::Number if node.is_integer() => "long".to_string(),
+            TypeScriptPrimativeType::Number => "double".to_string(),
+            TypeScriptPrimativeType::Boolean => "boolean".to_string(),
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                self.types.referenced_name(node).to_string()
+            }
+            TypeScriptPrimativeType::Object => match self.types.emitted_name(node) {
+                Some(name) => name.to_string(),
+                None => self.declaration(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array => {
+                self.imports.insert("java.util.List");
+                let elements: Vec<&TypeScriptNode> = node
+                    .elements()
+                    .iter()
+                    .filter(|element| element.type_signature() != &TypeScriptPrimativeType::Null)
+                    .collect();
+                // the types of elements mixed with others aren't declared, as they end up as
+                // JSON nodes
+                let element_type = match elements.as_slice() {
+                    [element] => self.type_name(element, &format!("{}Item", hint), true),
+                    _ => self.json_node(),
+                };
+                format!("List<{}>", element_type)
+            }
+            TypeScriptPrimativeType::Null
+            | TypeScriptPrimativeType::Unknown
+            | TypeScriptPrimativeType::Union => self.json_node(),
+        };
+        if nullable || node.is_nullable() {
+            boxed(type_name)
+        } else {
+            type_name
+        }
+    }
+
+    fn json_node(&mut self) -> String {
+        self.imports
+            .insert("com.fasterxml.jackson.databind.JsonNode");
+        JSON_NODE.to_string()
+    }
+
+    /// Emits the type of an object ahead of the types it refers to, returning its name. The
+    /// root is named `hint`, common types by their type name and other objects after `hint`.
+    fn declaration(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self.types.name(node, to_type_identifier(&hint), root);
+        let index = self.types.reserve();
+        let mut field_names = HashSet::new();
+        // the annotation, type and name of each field
+        let mut fields = Vec::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let field_name = numbered(&to_java_field_name(key), "", &field_names);
+            field_names.insert(field_name.clone());
+            let field_type = self.type_name(
+                sub_item,
+                &format!("{}{}", name, segment(key)),
+                sub_item.is_optional(),
+            );
+            let annotation = if field_name != key {
+                self.imports
+                    .insert("com.fasterxml.jackson.annotation.JsonProperty");
+                Some(format!(
+                    "@JsonProperty({})",
+                    serde_json::to_string(key).unwrap()
+                ))
+            } else {
+                None
+            };
+            fields.push((annotation, field_type, field_name));
+        }
+        let modifier = if root { "public " } else { "" };
+        let declaration = match self.style {
+            JavaStyle::Record if fields.is_empty() => {
+                format!("{}record {}() {{\n}}\n", modifier, name)
+            }
+            JavaStyle::Record => {
+                let components: Vec<String> = fields
+                    .iter()
+                    .map(|(annotation, field_type, field_name)| match annotation {
+                        Some(annotation) => {
+                            format!("{}{} {} {}", INDENT, annotation, field_type, field_name)
+                        }
+                        None => format!("{}{} {}", INDENT, field_type, field_name),
+                    })
+                    .collect();
+                format!(
+                    "{}record {}(\n{}\n) {{\n}}\n",
+                    modifier,
+                    name,
+                    components.join(",\n")
+                )
+            }
+            JavaStyle::Pojo => {
+                let mut body = String::new();
+                for (annotation, field_type, field_name) in &fields {
+                    if let Some(annotation) = annotation {
+                        body.push_str(&format!("{}{}\n", INDENT, annotation));
+                    }
+                    body.push_str(&format!(
+                        "{}private {} {};\n",
+                        INDENT, field_type, field_name
+                    ));
+                }
+                for (_, field_type, field_name) in &fields {
+                    let accessor = to_pascal_case(field_name);
+                    body.push_str(&format!(
+                        "\n{0}public {1} get{2}() {{\n{0}{0}return {3};\n{0}}}\n",
+                        INDENT, field_type, accessor, field_name
+                    ));
+                    body.push_str(&format!(
+                        "\n{0}public void set{2}({1} {3}) {{\n{0}{0}this.{3} = {3};\n{0}}}\n",
+                        INDENT, field_type, accessor, field_name
+                    ));
+                }
+                format!("{}class {} {{\n{}}}\n", modifier, name, body)
+            }
+        };
+        self.types.fill(index, declaration);
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_java_string, JavaStyle};
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_java_records_and_pojos() {
+        let val_tree = serde_json::from_str(
+            r#"{ "user_id": 1, "billing": { "city": "a" }, "shipping": { "city": "b" }, "lines": [{ "qty": 1.5, "note": null }, { "qty": 2 }], "meta": [1, "a"] }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            to_java_string(&result, &RenderOptions::default(), JavaStyle::Record),
+            "import com.fasterxml.jackson.annotation.JsonProperty;
+import com.fasterxml.jackson.databind.JsonNode;
+import java.util.List;
+
+public record DefaultType(
+    DefaultType_0 billing,
+    List<DefaultType_1> lines,
+    List<JsonNode> meta,
+    DefaultType_0 shipping,
+    @JsonProperty(\"user_id\") long userId
+) {
+}
+
+record DefaultType_0(
+    String city
+) {
+}
+
+record DefaultType_1(
+    JsonNode note,
+    double qty
+) {
+}
+"
+        );
+        let val_tree = serde_json::from_str(r#"{ "is-open": true, "count": null }"#).unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            to_java_string(&result, &RenderOptions::default(), JavaStyle::Pojo),
+            "import com.fasterxml.jackson.annotation.JsonProperty;
+import com.fasterxml.jackson.databind.JsonNode;
+
+public class DefaultType {
+    private JsonNode count;
+    @JsonProperty(\"is-open\")
+    private boolean isOpen;
+
+    public JsonNode getCount() {
+        return count;
+    }
+
+    public void setCount(JsonNode count) {
+        this.count = count;
+    }
+
+    public boolean getIsOpen() {
+        return isOpen;
+    }
+
+    public void setIsOpen(boolean isOpen) {
+        this.isOpen = isOpen;
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_types_of_mixed_elements() {
+        let val_tree = serde_json::from_str(
+            r#"{ "items": [{ "a": 1, "c": true }, { "b": "x" }], "lines": [{ "qty": 1 }, null] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &InferOptions::default());
+        result.calculate_hash();
+        assert_eq!(
+            to_java_string(&result, &RenderOptions::default(), JavaStyle::Record),
+            "import com.fasterxml.jackson.databind.JsonNode;
+import java.util.List;
+
+public record DefaultType(
+    List<JsonNode> items,
+    List<DefaultTypeLinesItem> lines
+) {
+}
+
+record DefaultTypeLinesItem(
+    double qty
+) {
+}
+"
+        );
+    }
+}
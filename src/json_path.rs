@@ -0,0 +1,171 @@
+use std::str::FromStr;
+
+/// A JSON path picking values out of a sample, such as `$.items[*].id`, `$..id` or
+/// `$['odd key'][0]`, as given to `--override`, `--include` and `--exclude`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsonPath {
+    segments: Vec<PathSegment>,
+}
+
+/// A step of a JSON path after the `$` standing for the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    /// `.name`, `['name']` or `[0]`
+    Member(String),
+    /// `.*` or `[*]`, any member of an object or element of an array
+    Wildcard,
+    /// `..`, any number of levels, none included
+    Descendant,
+}
+
+impl PathSegment {
+    fn accepts(&self, key: &str) -> bool {
+        match self {
+            PathSegment::Member(name) => name == key,
+            PathSegment::Wildcard => true,
+            PathSegment::Descendant => false,
+        }
+    }
+}
+
+impl JsonPath {
+    /// Whether the path picks the value reached through `keys`, the member names and element
+    /// indices leading to it from the root
+    pub(crate) fn matches(&self, keys: &[String]) -> bool {
+        matches_path(&self.segments, keys)
+    }
+
+    /// Whether the path picks the value reached through `keys` or something nested in it
+    #[cfg(feature = "cli")]
+    pub(crate) fn leads_to(&self, keys: &[String]) -> bool {
+        leads_to(&self.segments, keys)
+    }
+}
+
+/// The member names and element indices a JSON pointer is made of
+pub(crate) fn pointer_segments(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+fn matches_path(path: &[PathSegment], keys: &[String]) -> bool {
+    match path.split_first() {
+        None => keys.is_empty(),
+        Some((PathSegment::Descendant, rest)) => {
+            (0..=keys.len()).any(|skipped| matches_path(rest, &keys[skipped..]))
+        }
+        Some((segment, rest)) => match keys.split_first() {
+            Some((first, others)) => segment.accepts(first) && matches_path(rest, others),
+            None => false,
+        },
+    }
+}
+
+#[cfg(feature = "cli")]
+fn leads_to(path: &[PathSegment], keys: &[String]) -> bool {
+    match (path.split_first(), keys.split_first()) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        // what follows the dots can be anywhere below
+        (Some((PathSegment::Descendant, _)), Some(_)) => true,
+        (Some((segment, rest)), Some((first, others))) => {
+            segment.accepts(first) && leads_to(rest, others)
+        }
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("invalid JSON path `{}`: {}", path, reason);
+        let mut rest = path
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("it should start with `$`"))?;
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                segments.push(PathSegment::Descendant);
+                // `$..id` names the member straight after the dots
+                rest = if after.starts_with('[') {
+                    after
+                } else {
+                    &rest[1..]
+                };
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                segments.push(match &after[..end] {
+                    "" => return Err(invalid("a `.` isn't followed by a name")),
+                    "*" => PathSegment::Wildcard,
+                    name => PathSegment::Member(name.to_string()),
+                });
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let (segment, after) = if let Some(after) = after.strip_prefix("*]") {
+                    (PathSegment::Wildcard, after)
+                } else if let Some(quote) = after.chars().next().filter(|c| *c == '\'' || *c == '"')
+                {
+                    let quoted = &after[1..];
+                    let end = quoted
+                        .find(quote)
+                        .ok_or_else(|| invalid("a quoted name isn't closed"))?;
+                    let after = quoted[end + 1..]
+                        .strip_prefix(']')
+                        .ok_or_else(|| invalid("a `[` isn't closed"))?;
+                    (PathSegment::Member(quoted[..end].to_string()), after)
+                } else {
+                    let end = after
+                        .find(']')
+                        .ok_or_else(|| invalid("a `[` isn't closed"))?;
+                    let index = &after[..end];
+                    if index.is_empty() || !index.chars().all(|c| c.is_ascii_digit()) {
+                        return Err(invalid(&format!(
+                            "`[{}]` isn't an index, `[*]` or a quoted name",
+                            index
+                        )));
+                    }
+                    (PathSegment::Member(index.to_string()), &after[end + 1..])
+                };
+                segments.push(segment);
+                rest = after;
+            } else {
+                return Err(invalid(&format!("unexpected `{}`", rest)));
+            }
+        }
+        Ok(JsonPath { segments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pointer_segments, JsonPath};
+
+    #[test]
+    fn matches_paths_against_pointers() {
+        let path = |path: &str| path.parse::<JsonPath>().unwrap();
+        let matches = |path: &JsonPath, pointer: &str| path.matches(&pointer_segments(pointer));
+        assert!(matches(&path("$.items[*].id"), "/items/3/id"));
+        assert!(matches(&path("$.items[3].id"), "/items/3/id"));
+        assert!(!matches(&path("$.items[2].id"), "/items/3/id"));
+        assert!(!matches(&path("$.items[*].id"), "/items/3/id/x"));
+        assert!(matches(&path("$..id"), "/id"));
+        assert!(matches(&path("$..id"), "/a/0/b/id"));
+        assert!(matches(&path("$['a/b'].c"), "/a~1b/c"));
+        assert!(matches(&path("$"), ""));
+        for invalid in ["items.id", "$.items[x]", "$.items[*", "$..", "$['id]"] {
+            assert!(invalid.parse::<JsonPath>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn leads_to_the_values_at_paths() {
+        let path = |path: &str| path.parse::<JsonPath>().unwrap();
+        assert!(path("$.items[*].id").leads_to(&pointer_segments("/items/3")));
+        assert!(!path("$.items[*].id").leads_to(&pointer_segments("/meta")));
+        assert!(path("$..id").leads_to(&pointer_segments("/meta/a")));
+    }
+}
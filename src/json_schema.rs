@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::formats::StringFormat;
+use crate::typescript_node::{Fingerprint, RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Renders the tree as a draft 2020-12 JSON Schema titled after the root type, with each common
+/// type under `$defs` by its name
+pub(crate) fn to_json_schema_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let mut type_names = node.type_names(options);
+    // a recursive root is the document itself rather than one of its `$defs`
+    if type_names.get(&node.hash()) == Some(&options.root_name) {
+        type_names.remove(&node.hash());
+    }
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DRAFT_2020_12));
+    document.insert("title".to_string(), json!(options.root_name));
+    let root = match schema(node, &type_names, true) {
+        Value::Object(root) => root,
+        _ => unreachable!("schemas are objects"),
+    };
+    document.extend(root);
+    let defs: Map<String, Value> = node
+        .named_types(&type_names)
+        .into_iter()
+        .map(|(name, named_node)| (name.to_string(), object_schema(named_node, &type_names)))
+        .collect();
+    if !defs.is_empty() {
+        document.insert("$defs".to_string(), Value::Object(defs));
+    }
+    let mut output = serde_json::to_string_pretty(&Value::Object(document)).unwrap();
+    output.push('\n');
+    output
+}
+
+fn schema(node: &TypeScriptNode, type_names: &HashMap<Fingerprint, String>, root: bool) -> Value {
+    let schema = match node.type_signature() {
+        TypeScriptPrimativeType::String => match (node.string_format(), node.literals()) {
+            (Some(StringFormat::Base64), _) => {
+                json!({ "type": "string", "contentEncoding": "base64" })
+            }
+            (Some(format), _) => json!({ "type": "string", "format": format.format_name() }),
+            (None, Some(literals)) => json!({ "enum": literals }),
+            (None, None) => json!({ "type": "string" }),
+        },
+        TypeScriptPrimativeType::Number if node.is_integer() => json!({ "type": "integer" }),
+        TypeScriptPrimativeType::Number => json!({ "type": "number" }),
+        TypeScriptPrimativeType::Boolean => json!({ "type": "boolean" }),
+        TypeScriptPrimativeType::Null => json!({ "type": "null" }),
+        TypeScriptPrimativeType::Unknown => json!({}),
+        TypeScriptPrimativeType::Union => {
+            let members: Vec<Value> = node
+                .sub_items()
+                .iter()
+                .map(|member| schema(member, type_names, false))
+                .collect();
+            json!({ "anyOf": members })
+        }
+        TypeScriptPrimativeType::Object => match type_names.get(&node.hash()) {
+            None if node.is_self_reference() => json!({ "$ref": "#" }),
+            Some(name) if !root => json!({ "$ref": format!("#/$defs/{}", name) }),
+            _ => object_schema(node, type_names),
+        },
+        TypeScriptPrimativeType::Array => {
+            let mut elements: Vec<Value> = Vec::new();
+            for element in node.elements() {
+                let element = schema(element, type_names, false);
+                if !elements.contains(&element) {
+                    elements.push(element);
+                }
+            }
+            match elements.len() {
+                0 => json!({ "type": "array" }),
+                1 => json!({ "type": "array", "items": elements.pop().unwrap() }),
+                _ => json!({ "type": "array", "items": { "anyOf": elements } }),
+            }
+        }
+    };
+    if node.is_nullable() {
+        json!({ "anyOf": [schema, { "type": "null" }] })
+    } else {
+        schema
+    }
+}
+
+fn object_schema(node: &TypeScriptNode, type_names: &HashMap<Fingerprint, String>) -> Value {
+    let properties: Map<String, Value> = node
+        .sub_items()
+        .iter()
+        .map(|sub_item| {
+            (
+                sub_item.name().unwrap_or_default().to_string(),
+                schema(sub_item, type_names, false),
+            )
+        })
+        .collect();
+    let required: Vec<&str> = node
+        .sub_items()
+        .iter()
+        .filter(|sub_item| !sub_item.is_optional())
+        .map(|sub_item| sub_item.name().unwrap_or_default())
+        .collect();
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::to_json_schema_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn renders_json_schema_with_defs() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": null }, { "amount": 2, "note": "a" }], "refunds": [{ "amount": 1, "note": "b" }, { "amount": 1, "note": null }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let schema: serde_json::Value =
+            serde_json::from_str(&to_json_schema_string(&result, &RenderOptions::default()))
+                .unwrap();
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "DefaultType",
+                "type": "object",
+                "properties": {
+                    "payments": { "type": "array", "items": { "$ref": "#/$defs/DefaultType_0" } },
+                    "refunds": { "type": "array", "items": { "$ref": "#/$defs/DefaultType_0" } }
+                },
+                "required": ["payments", "refunds"],
+                "$defs": {
+                    "DefaultType_0": {
+                        "type": "object",
+                        "properties": {
+                            "amount": { "type": "number" },
+                            "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+                        },
+                        "required": ["amount", "note"]
+                    }
+                }
+            })
+        );
+    }
+}
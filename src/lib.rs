@@ -0,0 +1,1873 @@
+//! Infers TypeScript types from JSON samples. [`infer`] walks a sample into a [`TypeTree`],
+//! trees of further samples can be merged into it, and [`render_typescript`] renders the
+//! declarations.
+
+#[cfg(feature = "cli")]
+mod asserts;
+#[cfg(feature = "cli")]
+mod avro;
+#[cfg(feature = "cli")]
+mod bench;
+#[cfg(feature = "cli")]
+mod check;
+#[doc(hidden)]
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+mod compare;
+#[cfg(feature = "cli")]
+mod config;
+#[cfg(feature = "cli")]
+mod csharp;
+#[cfg(feature = "cli")]
+mod declarations;
+#[cfg(feature = "cli")]
+mod decompress;
+mod diagnostics;
+#[cfg(feature = "cli")]
+mod errors;
+#[cfg(feature = "cli")]
+mod factories;
+#[cfg(feature = "cli")]
+mod fetch;
+#[cfg(feature = "cli")]
+mod flow;
+mod formats;
+#[cfg(feature = "cli")]
+mod graphql;
+#[cfg(feature = "cli")]
+mod guards;
+#[cfg(feature = "cli")]
+mod highlight;
+#[cfg(feature = "cli")]
+mod html;
+mod interner;
+#[cfg(feature = "cli")]
+mod ir;
+#[cfg(feature = "cli")]
+mod java;
+mod json_path;
+#[cfg(feature = "cli")]
+mod json_schema;
+mod literals;
+#[cfg(feature = "cli")]
+mod lsp;
+mod naming;
+#[cfg(feature = "cli")]
+mod normalizers;
+#[cfg(feature = "cli")]
+mod openapi;
+mod overrides;
+#[cfg(feature = "cli")]
+mod path_filter;
+#[cfg(feature = "cli")]
+mod poll;
+#[cfg(feature = "cli")]
+mod postformat;
+#[cfg(feature = "cli")]
+mod progress;
+#[cfg(feature = "cli")]
+mod proto;
+mod provenance;
+#[cfg(feature = "cli")]
+mod python;
+mod rename;
+#[cfg(feature = "cli")]
+mod report;
+#[cfg(any(feature = "cli", feature = "wasm"))]
+mod request;
+#[cfg(feature = "cli")]
+mod sarif;
+#[cfg(feature = "cli")]
+mod schema;
+#[cfg(feature = "cli")]
+mod stats;
+#[cfg(feature = "cli")]
+mod swift;
+pub mod typescript_node;
+#[cfg(feature = "cli")]
+mod typescript_parser;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "cli")]
+mod watch;
+#[cfg(feature = "cli")]
+mod zod;
+
+#[cfg(any(feature = "cli", feature = "wasm"))]
+pub(crate) use anyhow::{Context, Result};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+pub use diagnostics::{Diagnostic, DiagnosticKind, Diagnostics};
+#[cfg(feature = "cli")]
+use errors::ErrorKind;
+pub use formats::{BigIntMode, DateType, FallbackType, NumberMode, StringFormatMode};
+use interner::Interner;
+pub use naming::{NamingStrategy, TypeNameParts, TypeNameTemplate, TypeNamer};
+pub use overrides::TypeOverride;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "cli")]
+use std::io::BufRead;
+use std::sync::{Arc, Mutex};
+use tracing::{event, Level};
+pub use typescript_node::{BraceStyle, DeclarationStyle, FormatOptions, RenderOptions};
+use typescript_node::{TypeScriptNode, TypeScriptPrimativeType};
+
+/// The types inferred from one or more JSON samples, serializable as the same IR `--emit ir`
+/// writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TypeTree(TypeScriptNode);
+
+impl TypeTree {
+    /// Merges in the types inferred from another sample, marking fields missing from either
+    /// of them optional
+    pub fn merge(self, other: TypeTree) -> TypeTree {
+        TypeTree(self.0.merge(other.0))
+    }
+}
+
+/// Infers the types of a JSON value
+pub fn infer(value: &Value, options: &InferOptions) -> TypeTree {
+    TypeTree(walk_value_tree_with_options(value, None, options))
+}
+
+/// Renders the types as TypeScript declarations, with object types seen more than once
+/// declared separately under the names given by the naming strategy, and objects nested in
+/// objects with the same fields declared as one type referring to itself
+pub fn render_typescript(tree: &TypeTree, options: &RenderOptions) -> String {
+    let mut root = tree.0.clone();
+    root.fold_recursive_objects();
+    root.unify_repeated_objects();
+    root.calculate_hash();
+    render_types(&root, options)
+}
+
+/// The stages of inferring and rendering types one at a time, for the benchmarks. Not a stable
+/// API.
+#[doc(hidden)]
+pub mod stages {
+    use serde_json::Value;
+
+    use crate::{walk_value_tree, RenderOptions, TypeScriptNode, TypeTree};
+
+    /// Walks a JSON value into a tree, before anything is squashed or hashed
+    pub fn walk(value: &Value) -> TypeTree {
+        TypeTree(walk_value_tree(value, None))
+    }
+
+    /// Folds recursive objects and unifies repeated ones, as is done before rendering
+    pub fn squash(tree: &mut TypeTree) {
+        tree.0.fold_recursive_objects();
+        tree.0.unify_repeated_objects();
+    }
+
+    /// Calculates the hashes the common types are found and named by
+    pub fn calculate_hash(tree: &mut TypeTree) {
+        tree.0.calculate_hash();
+    }
+
+    /// Renders a hashed tree as TypeScript declarations
+    pub fn to_type_string(tree: &TypeTree, options: &RenderOptions) -> String {
+        TypeScriptNode::to_type_string_with_options(&tree.0, options)
+    }
+}
+
+/// JavaScript reserved words, including those only reserved in strict mode
+const RESERVED_WORDS: [&str; 45] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+/// TypeScript's own type names, which can't name a declaration
+const PREDEFINED_TYPES: [&str; 10] = [
+    "any",
+    "bigint",
+    "boolean",
+    "never",
+    "number",
+    "object",
+    "string",
+    "symbol",
+    "undefined",
+    "unknown",
+];
+
+/// Whether `name` can be written as is, rather than quoted, as a property name: ASCII letters,
+/// digits, `_` and `$`, not starting with a digit and not a reserved word
+pub(crate) fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !RESERVED_WORDS.contains(&name)
+}
+
+/// Whether `name` can name a declaration
+pub(crate) fn is_type_name(name: &str) -> bool {
+    is_identifier(name) && !PREDEFINED_TYPES.contains(&name)
+}
+
+pub(crate) fn render_types(result: &TypeScriptNode, options: &RenderOptions) -> String {
+    TypeScriptNode::to_type_string_with_options(result, options)
+}
+
+/// Parses a JSON5 document, which may hold comments, trailing commas, unquoted keys and
+/// single-quoted strings. JSONC is a subset of JSON5.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_json5(content: &str) -> Result<Value> {
+    json5::from_str(content).context(ErrorKind::Parse.because("could not parse json5"))
+}
+
+/// Parses a TOML document into the JSON it would be, so it is walked like any other sample.
+/// Dates and times become strings, which `--detect-dates` recognises, and `nan` and `inf`,
+/// which JSON can't hold, become `null`.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_toml(content: &str) -> Result<Value> {
+    fn to_json(value: toml::Value) -> Value {
+        match value {
+            toml::Value::String(string) => Value::String(string),
+            toml::Value::Integer(integer) => Value::from(integer),
+            toml::Value::Float(float) => Value::from(float),
+            toml::Value::Boolean(boolean) => Value::Bool(boolean),
+            toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+            toml::Value::Array(array) => Value::Array(array.into_iter().map(to_json).collect()),
+            toml::Value::Table(table) => Value::Object(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, to_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+    let table: toml::Table =
+        toml::from_str(content).context(ErrorKind::Parse.because("could not parse toml"))?;
+    Ok(to_json(toml::Value::Table(table)))
+}
+
+/// Decodes a MessagePack document into the JSON it would be. Binary data becomes an array of
+/// its bytes, and keys which aren't strings the JSON of the key.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_msgpack(content: &[u8]) -> Result<Value> {
+    fn to_json(value: rmpv::Value) -> Value {
+        match value {
+            rmpv::Value::Nil => Value::Null,
+            rmpv::Value::Boolean(boolean) => Value::Bool(boolean),
+            rmpv::Value::Integer(integer) => match (integer.as_i64(), integer.as_u64()) {
+                (Some(integer), _) => Value::from(integer),
+                (None, Some(integer)) => Value::from(integer),
+                (None, None) => Value::from(integer.as_f64()),
+            },
+            rmpv::Value::F32(float) => Value::from(float),
+            rmpv::Value::F64(float) => Value::from(float),
+            rmpv::Value::String(string) => {
+                Value::String(String::from_utf8_lossy(string.as_bytes()).into_owned())
+            }
+            rmpv::Value::Binary(bytes) | rmpv::Value::Ext(_, bytes) => Value::from(bytes),
+            rmpv::Value::Array(array) => Value::Array(array.into_iter().map(to_json).collect()),
+            rmpv::Value::Map(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (object_key(to_json(key)), to_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+    let value = rmpv::decode::read_value(&mut &content[..])
+        .context(ErrorKind::Parse.because("could not decode msgpack"))?;
+    Ok(to_json(value))
+}
+
+/// Decodes a CBOR document into the JSON it would be. Binary data becomes an array of its
+/// bytes, keys which aren't strings the JSON of the key, and tagged values the value itself.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_cbor(content: &[u8]) -> Result<Value> {
+    fn to_json(value: ciborium::Value) -> Value {
+        use std::convert::TryFrom;
+
+        match value {
+            ciborium::Value::Integer(integer) => {
+                let integer = i128::from(integer);
+                i64::try_from(integer)
+                    .map(Value::from)
+                    .or_else(|_| u64::try_from(integer).map(Value::from))
+                    .unwrap_or_else(|_| Value::from(integer as f64))
+            }
+            ciborium::Value::Bytes(bytes) => Value::from(bytes),
+            ciborium::Value::Float(float) => Value::from(float),
+            ciborium::Value::Text(text) => Value::String(text),
+            ciborium::Value::Bool(boolean) => Value::Bool(boolean),
+            ciborium::Value::Tag(_, value) => to_json(*value),
+            ciborium::Value::Array(array) => Value::Array(array.into_iter().map(to_json).collect()),
+            ciborium::Value::Map(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (object_key(to_json(key)), to_json(value)))
+                    .collect(),
+            ),
+            _ => Value::Null,
+        }
+    }
+    let value: ciborium::Value = ciborium::de::from_reader(content)
+        .context(ErrorKind::Parse.because("could not decode cbor"))?;
+    Ok(to_json(value))
+}
+
+/// The JSON key a decoded map key becomes, a string as it is and anything else as JSON
+#[cfg(feature = "cli")]
+fn object_key(key: Value) -> String {
+    match key {
+        Value::String(key) => key,
+        key => key.to_string(),
+    }
+}
+
+/// Parses one JSON document per line as the lines are read, so only one line is held in
+/// memory at a time, skipping blank lines. Documents are returned with their line number.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_ndjson<R: BufRead>(reader: R) -> impl Iterator<Item = Result<(usize, Value)>> {
+    reader.lines().enumerate().filter_map(|(index, line)| {
+        let line = match line
+            .with_context(|| ErrorKind::Input.because(format!("could not read line {}", index + 1)))
+        {
+            Ok(line) => line,
+            Err(error) => return Some(Err(error)),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        Some(errors::parse_json_line(&line, index + 1).map(|sample| (index + 1, sample)))
+    })
+}
+
+/// Parses the rows of a CSV file, or TSV with `b'\t'` as the delimiter, as the rows are read.
+/// The first row names the fields of the objects the other rows become. Rows are returned with
+/// their line number.
+#[cfg(feature = "cli")]
+pub(crate) fn parse_csv<R: std::io::Read>(
+    reader: R,
+    delimiter: u8,
+) -> Result<impl Iterator<Item = Result<(usize, Value)>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(reader);
+    let headers = reader
+        .headers()
+        .context(ErrorKind::Parse.because("could not read the header row"))?
+        .clone();
+    Ok(reader.into_records().map(move |record| {
+        let record = record.context(ErrorKind::Parse.because("could not parse csv"))?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let row = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(header, cell)| (header.to_string(), csv_cell(cell)))
+            .collect();
+        Ok((line, Value::Object(row)))
+    }))
+}
+
+/// The value a CSV cell holds: `null` when empty, then a boolean or number if it reads as one,
+/// otherwise a string. Numbers with leading zeros, like zip codes, are kept as strings.
+#[cfg(feature = "cli")]
+fn csv_cell(cell: &str) -> Value {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return Value::Null;
+    }
+    if cell.eq_ignore_ascii_case("true") || cell.eq_ignore_ascii_case("false") {
+        return Value::Bool(cell.eq_ignore_ascii_case("true"));
+    }
+    let digits = cell.trim_start_matches('-');
+    let numeric = digits.starts_with(|c: char| c.is_ascii_digit())
+        && !(digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0."))
+        && cell
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+    if numeric {
+        if let Ok(integer) = cell.parse::<i64>() {
+            return Value::from(integer);
+        }
+        if let Some(number) = cell.parse().ok().and_then(serde_json::Number::from_f64) {
+            return Value::Number(number);
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+/// Arrays with at least this many elements have them walked in parallel
+const PARALLEL_ELEMENTS: usize = 256;
+
+/// The order object fields are declared in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyOrder {
+    /// By name, so the output doesn't depend on the order of the keys in the input
+    #[default]
+    #[cfg_attr(feature = "cli", value(alias = "alpha"))]
+    #[serde(alias = "alpha")]
+    Sorted,
+    /// In the order the keys were first seen, fields only seen in later samples last
+    #[cfg_attr(feature = "cli", value(alias = "input"))]
+    #[serde(alias = "input")]
+    Insertion,
+}
+
+/// Which elements of a large array are walked under `--sample`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SampleStrategy {
+    /// The first elements
+    #[default]
+    First,
+    /// Elements picked at random from across the whole array, the same ones every run
+    Random,
+}
+
+/// Arrays skipping elements under `--sample` listed in the warning, the ones skipping the most
+const LISTED_SAMPLED_ARRAYS: usize = 5;
+
+/// Heuristics applied while walking the value tree
+#[derive(Debug, Clone, Default)]
+pub struct InferOptions {
+    /// Tag strings holding ISO 8601 dates and timestamps
+    pub detect_dates: bool,
+    /// Tag strings holding UUIDs, email addresses, URLs and IP addresses
+    pub detect_string_formats: bool,
+    /// Tag long strings holding base64 encoded data
+    pub detect_base64: bool,
+    /// Tag numbers which are integers
+    pub detect_integers: bool,
+    pub key_order: KeyOrder,
+    /// Levels of objects and arrays walked, counting the root. Any nested deeper are left opaque
+    /// and typed with the fallback type.
+    pub max_depth: Option<usize>,
+    /// Field tagging the objects of an array with their kind, which keeps objects with different
+    /// tags apart as members of a tagged union. Fields such as `type` and `kind` are tried when
+    /// it's not given.
+    pub discriminator: Option<String>,
+    /// Elements of an array walked at most, the others skipped. Every element is walked when not
+    /// set.
+    pub sample: Option<usize>,
+    pub sample_strategy: SampleStrategy,
+    /// Types forced on the values at JSON paths instead of inferring them, the last one given
+    /// winning where several apply
+    pub overrides: Vec<TypeOverride>,
+    /// Collects the arrays `sample` skipped elements of, rather than summing them up in a
+    /// warning
+    pub diagnostics: Option<Arc<Diagnostics>>,
+}
+
+pub(crate) fn walk_value_tree(v: &Value, key_name: Option<String>) -> TypeScriptNode {
+    walk_value_tree_with_options(v, key_name, &InferOptions::default())
+}
+
+pub(crate) fn walk_value_tree_with_options(
+    v: &Value,
+    key_name: Option<String>,
+    options: &InferOptions,
+) -> TypeScriptNode {
+    let context = InferenceContext {
+        options,
+        sampled: Mutex::new(Vec::new()),
+    };
+    let mut tree =
+        walk_value_tree_helper(v, key_name.map(Arc::from), true, String::new(), 0, &context);
+    tree.intern_names(&mut Interner::default());
+    let mut sampled = context
+        .sampled
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner());
+    if let (Some(diagnostics), false) = (&options.diagnostics, sampled.is_empty()) {
+        for (pointer, skipped) in sampled {
+            diagnostics.record(
+                DiagnosticKind::SampledArray,
+                diagnostics::pointer_path(&pointer),
+                format!(
+                    "--sample skipped {} elements, shapes only they have are missing",
+                    skipped
+                ),
+            );
+        }
+    } else if !sampled.is_empty() {
+        sampled.sort_by(|(a, a_skipped), (b, b_skipped)| b_skipped.cmp(a_skipped).then(a.cmp(b)));
+        let skipped: usize = sampled.iter().map(|(_, skipped)| skipped).sum();
+        let mut largest = sampled
+            .iter()
+            .take(LISTED_SAMPLED_ARRAYS)
+            .map(|(pointer, skipped)| format!("{} skipped {}", pointer_label(pointer), skipped))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if sampled.len() > LISTED_SAMPLED_ARRAYS {
+            largest.push_str(&format!(
+                " and {} more",
+                sampled.len() - LISTED_SAMPLED_ARRAYS
+            ));
+        }
+        event!(
+            Level::WARN,
+            arrays = sampled.len(),
+            skipped,
+            largest = largest.as_str(),
+            "sampled large arrays, shapes only the skipped elements have are missing"
+        );
+    }
+    tree
+}
+
+/// A JSON pointer as it reads in a message, the root being empty
+fn pointer_label(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "the root"
+    } else {
+        pointer
+    }
+}
+
+/// What every step of a walk reads, and the arrays it sampled, so the walk can be split across
+/// threads
+struct InferenceContext<'a> {
+    options: &'a InferOptions,
+    /// The pointers of the arrays `--sample` skipped elements of, with how many were skipped
+    sampled: Mutex<Vec<(String, usize)>>,
+}
+
+/// The elements of an array which are walked, with their index, all of them unless it's larger
+/// than `--sample`
+fn sample_elements<'v>(
+    array: &'v [Value],
+    pointer: &str,
+    context: &InferenceContext,
+) -> Vec<(usize, &'v Value)> {
+    let size = match context.options.sample {
+        Some(size) if array.len() > size => size,
+        _ => return array.iter().enumerate().collect(),
+    };
+    if let Ok(mut sampled) = context.sampled.lock() {
+        sampled.push((pointer.to_string(), array.len() - size));
+    }
+    match context.options.sample_strategy {
+        SampleStrategy::First => array.iter().enumerate().take(size).collect(),
+        SampleStrategy::Random => {
+            // seeded by the length, so a run over the same input picks the same elements
+            let mut rng = rand::rngs::StdRng::seed_from_u64(array.len() as u64);
+            let mut indices = rand::seq::index::sample(&mut rng, array.len(), size).into_vec();
+            indices.sort_unstable();
+            indices
+                .into_iter()
+                .map(|index| (index, &array[index]))
+                .collect()
+        }
+    }
+}
+
+fn walk_value_tree_helper(
+    v: &Value,
+    key_name: Option<Arc<str>>,
+    root_node: bool,
+    pointer: String,
+    depth: usize,
+    context: &InferenceContext,
+) -> TypeScriptNode {
+    let options = context.options;
+    if let Some(type_override) = overrides::find(&options.overrides, &pointer) {
+        let mut node = type_override.node(root_node);
+        if let Some(name) = key_name {
+            node = node.with_name(name);
+        }
+        return node.with_source(pointer);
+    }
+    match v {
+        Value::Array(_) | Value::Object(_)
+            if options
+                .max_depth
+                .is_some_and(|max_depth| depth >= max_depth) =>
+        {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Unknown,
+                false,
+                false,
+                false,
+                root_node,
+            )
+            .with_example(v.clone());
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node.with_source(pointer)
+        }
+        Value::String(s) => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::String,
+                false,
+                false,
+                false,
+                root_node,
+            )
+            .with_example(v.clone())
+            .with_string_value(s);
+            let date_format = options
+                .detect_dates
+                .then(|| formats::detect_date(s))
+                .flatten();
+            let string_format = date_format.or_else(|| {
+                options
+                    .detect_string_formats
+                    .then(|| formats::detect_string_format(s))
+                    .flatten()
+            });
+            let string_format = string_format.or_else(|| {
+                options
+                    .detect_base64
+                    .then(|| formats::detect_base64(s))
+                    .flatten()
+            });
+            node = node.with_string_format(string_format);
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node.with_source(pointer)
+        }
+        Value::Number(n) => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Number,
+                false,
+                false,
+                false,
+                root_node,
+            )
+            .with_example(v.clone())
+            .with_big(formats::is_big_integer(n));
+            if options.detect_integers {
+                node = node.with_integer(n.is_i64() || n.is_u64());
+            }
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node.with_source(pointer)
+        }
+        Value::Bool(_b) => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Boolean,
+                false,
+                false,
+                false,
+                root_node,
+            )
+            .with_example(v.clone());
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node.with_source(pointer)
+        }
+        Value::Null => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Null,
+                false,
+                false,
+                false,
+                root_node,
+            );
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node.with_source(pointer)
+        }
+        Value::Array(a) => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Array,
+                false,
+                false,
+                true,
+                root_node,
+            );
+            let walk_element = |(index, v): (usize, &Value)| {
+                walk_value_tree_helper(
+                    v,
+                    None,
+                    false,
+                    format!("{}/{}", pointer, index),
+                    depth + 1,
+                    context,
+                )
+            };
+            let elements = sample_elements(a, &pointer, context);
+            let sub_items = if elements.len() >= PARALLEL_ELEMENTS {
+                elements
+                    .into_par_iter()
+                    .map(walk_element)
+                    .collect::<Vec<_>>()
+            } else {
+                elements.into_iter().map(walk_element).collect::<Vec<_>>()
+            };
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+
+            node = node.with_elements(sub_items, options.discriminator.as_deref());
+            node.with_source(pointer)
+        }
+        Value::Object(o) => {
+            let mut node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Object,
+                false,
+                false,
+                false,
+                root_node,
+            );
+            let mut sub_items = Vec::new();
+            for (k, v) in o {
+                sub_items.push(walk_value_tree_helper(
+                    v,
+                    Some(Arc::from(k.as_str())),
+                    false,
+                    provenance::child_pointer(&pointer, k),
+                    depth + 1,
+                    context,
+                ));
+            }
+            if options.key_order == KeyOrder::Sorted {
+                sub_items.sort_by(|a, b| a.name().cmp(&b.name()));
+            }
+            if let Some(name) = key_name {
+                node = node.with_name(name);
+            }
+            node = node.with_sub_items(sub_items);
+            node.with_source(pointer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        formats::{BigIntMode, DateType, FallbackType, NumberMode, StringFormatMode},
+        walk_value_tree, walk_value_tree_with_options, BraceStyle, DeclarationStyle, FormatOptions,
+        InferOptions, RenderOptions, SampleStrategy, TypeScriptNode,
+    };
+    #[cfg(feature = "cli")]
+    use crate::{
+        parse_cbor, parse_csv, parse_json5, parse_msgpack, parse_ndjson, parse_toml, KeyOrder,
+    };
+
+    #[test]
+    fn shares_the_names_of_fields_with_the_same_key() {
+        let val_tree = serde_json::json!({ "from": { "id": 1 }, "to": { "id": 2 } });
+        let result = walk_value_tree(&val_tree, None);
+        let ids: Vec<_> = result
+            .sub_items()
+            .iter()
+            .map(|sub_item| sub_item.sub_items()[0].name().unwrap().as_ptr())
+            .collect();
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn samples_large_arrays() {
+        let val_tree = serde_json::json!({
+            "ids": (0..100).map(|id| if id == 50 { serde_json::json!("fifty") } else { id.into() }).collect::<Vec<_>>(),
+            "tags": ["a", "b"],
+        });
+        let walk = |sample_strategy| {
+            let options = InferOptions {
+                sample: Some(10),
+                sample_strategy,
+                ..InferOptions::default()
+            };
+            let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+            result.calculate_hash();
+            TypeScriptNode::to_type_string(&result)
+        };
+        assert_eq!(
+            walk(SampleStrategy::First),
+            "type DefaultType = {\n  ids: number[];\n  tags: string[];\n};\n"
+        );
+        // the same elements are picked every run
+        assert_eq!(walk(SampleStrategy::Random), walk(SampleStrategy::Random));
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert!(TypeScriptNode::to_type_string(&result).contains("ids: (number | string)[];"));
+    }
+
+    #[test]
+    fn parses_string() {
+        let val_tree = serde_json::from_str(r#""hello""#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = string;\n");
+    }
+
+    #[test]
+    fn semi_complex_arrays() {
+        let val_tree = serde_json::from_str(
+            r#"{
+            "woah lol": {
+              "test": ["woah"],
+              "test2": ["woaher", { "test": "example" }]
+            }
+          }
+          "#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  \"woah lol\": {\n    test: string[];\n    test2: (string | {\n      test: string;\n    })[];\n  };\n};\n".to_string()
+        );
+    }
+
+    #[test]
+    fn parses_number() {
+        let val_tree = serde_json::from_str(r#"1"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = number;\n");
+    }
+
+    #[test]
+    fn parses_bool() {
+        let val_tree = serde_json::from_str(r#"true"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = boolean;\n");
+    }
+
+    #[test]
+    fn parses_null() {
+        let val_tree = serde_json::from_str(r#"null"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = null;\n");
+    }
+
+    #[test]
+    fn parses_object() {
+        let val_tree = serde_json::from_str(r#"{}"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = {};\n");
+    }
+
+    #[test]
+    fn parses_array() {
+        let val_tree = serde_json::from_str(r#"[]"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(output_string, "type DefaultType = unknown[];\n");
+    }
+
+    #[test]
+    fn parses_object_with_array() {
+        let val_tree = serde_json::from_str(r#"{ "test": [] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  test: unknown[];\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_empty_arrays_with_the_fallback_type() {
+        let val_tree = serde_json::from_str(r#"{ "tags": [], "grid": [[]] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let render = |fallback_type, empty_array_type: Option<&str>| {
+            let options = RenderOptions {
+                fallback_type,
+                empty_array_type: empty_array_type.map(str::to_string),
+                ..RenderOptions::default()
+            };
+            TypeScriptNode::to_type_string_with_options(&result, &options)
+        };
+        assert_eq!(
+            render(FallbackType::Any, None),
+            "type DefaultType = {\n  grid: any[][];\n  tags: any[];\n};\n"
+        );
+        assert_eq!(
+            render(FallbackType::JsonValue, Some("never[]")),
+            "type DefaultType = {\n  grid: never[][];\n  tags: never[];\n};\n"
+        );
+    }
+
+    #[test]
+    fn squashes_equal_objects_across_arrays() {
+        let val_tree = serde_json::from_str(
+            r#"{ "meta": {}, "count": 1, "lines": [{ "id": 1 }], "grid": [[{ "id": 2 }]], "order": { "refunds": [{ "id": 3 }] } }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {
+  count: number;
+  grid: DefaultType_0[][];
+  lines: DefaultType_0[];
+  meta: {};
+  order: {
+    refunds: DefaultType_0[];
+  };
+};
+
+type DefaultType_0 = {
+  id: number;
+};
+"
+        );
+    }
+
+    #[test]
+    fn keeps_small_or_rare_types_inline() {
+        let val_tree = serde_json::from_str(
+            r#"{ "from": { "x": 1 }, "to": { "x": 2 }, "lines": [{ "id": 1, "sku": "a" }, { "id": 2, "sku": "b" }, { "id": 3, "sku": "c" }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            inline_max_fields: Some(1),
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  from: {\n    x: number;\n  };\n  lines: DefaultType_0[];\n  to: {\n    x: number;\n  };\n};\n\ntype DefaultType_0 = {\n  id: number;\n  sku: string;\n};\n"
+        );
+        let options = RenderOptions {
+            extract_min_occurrences: 3,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  from: {\n    x: number;\n  };\n  lines: DefaultType_0[];\n  to: {\n    x: number;\n  };\n};\n\ntype DefaultType_0 = {\n  id: number;\n  sku: string;\n};\n"
+        );
+    }
+
+    #[test]
+    fn declares_recursive_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "id": 1, "replies": [{ "id": 2, "replies": [{ "id": 3, "replies": [] }] }], "thread": { "title": "a", "comments": [{ "body": "b", "replies": [{ "body": "c", "replies": [] }] }] } }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {
+  id: number;
+  replies: DefaultType_0[];
+  thread: {
+    comments: DefaultType_1[];
+    title: string;
+  };
+};
+
+type DefaultType_0 = {
+  id: number;
+  replies: DefaultType_0[];
+};
+
+type DefaultType_1 = {
+  body: string;
+  replies: DefaultType_1[];
+};
+"
+        );
+    }
+
+    #[test]
+    fn parses_object_with_object() {
+        let val_tree = serde_json::from_str(r#"{ "test": { "test": "test" } }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  test: {\n    test: string;\n  };\n};\n"
+        );
+    }
+
+    #[test]
+    fn parses_object_with_array_of_objects() {
+        let val_tree =
+            serde_json::from_str(r#"{ "test": [{ "test": "test" }, { "test": "test" }] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  test: DefaultType_0[];\n};\n\ntype DefaultType_0 = {\n  test: string;\n};\n"
+        );
+    }
+
+    #[test]
+    fn parses_object_with_array_of_arrays() {
+        let val_tree = serde_json::from_str(r#"{ "test": [[], []] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  test: unknown[][];\n};\n"
+        );
+    }
+
+    #[test]
+    fn readme_example() {
+        let val_tree = serde_json::from_str(
+            r#"{
+                "paymentOne": {
+                  "amount": 1337,
+                  "status": "paid"
+                },
+                "paymentTwo": {
+                  "amount": 1337,
+                  "status": "paid"
+                }
+              }              
+          "#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  paymentOne: DefaultType_0;\n  paymentTwo: DefaultType_0;\n};\n\ntype DefaultType_0 = {\n  amount: number;\n  status: string;\n};\n".to_string()
+        );
+    }
+
+    #[test]
+    fn merges_observations() {
+        let first =
+            serde_json::from_str(r#"{ "amount": 1, "note": null, "tags": ["a"] }"#).unwrap();
+        let second =
+            serde_json::from_str(r#"{ "amount": 2, "note": "late", "fee": 3, "tags": [1] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&first, None).merge(walk_value_tree(&second, None));
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  amount: number;\n  note: string | null;\n  tags: (number | string)[];\n  fee?: number;\n};\n"
+        );
+    }
+
+    #[test]
+    fn unions_samples_of_different_shapes() {
+        let samples = [
+            r#"{ "user": { "id": 1 } }"#,
+            r#"{ "error": { "code": "E1" } }"#,
+            r#"{ "user": { "id": 2, "name": "a" } }"#,
+            "null",
+        ];
+        let mut result = samples
+            .iter()
+            .map(|sample| walk_value_tree(&serde_json::from_str(sample).unwrap(), None))
+            .reduce(|merged, tree| merged.merge(tree))
+            .unwrap();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = DefaultType_0 | DefaultType_1 | null;
+
+type DefaultType_0 = {
+  user: {
+    id: number;
+    name?: string;
+  };
+};
+
+type DefaultType_1 = {
+  error: {
+    code: string;
+  };
+};
+"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn merges_ndjson_lines() {
+        let lines = parse_ndjson("{ \"id\": 1 }\n\n{ \"id\": 2, \"retry\": true }\n".as_bytes())
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            lines.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            [1, 3]
+        );
+        let mut result = lines
+            .iter()
+            .map(|(_, sample)| walk_value_tree(sample, None))
+            .reduce(|merged, tree| merged.merge(tree))
+            .unwrap();
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  id: number;\n  retry?: boolean;\n};\n"
+        );
+        assert!(parse_ndjson("{}\n{".as_bytes()).any(|line| line.is_err()));
+    }
+
+    #[test]
+    fn quotes_keys_which_are_not_identifiers() {
+        let val_tree = serde_json::from_str(
+            r#"{ "123abc": 1, "class": 2, "naïve": 3, "a\"b\\c": 4, "$ok_1": 5 }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  $ok_1: number;\n  \"123abc\": number;\n  \"a\\\"b\\\\c\": number;\n  \"class\": number;\n  \"naïve\": number;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn parses_json5_with_comments_and_trailing_commas() {
+        let val_tree =
+            parse_json5("{\n  // the order id\n  \"id\": 1,\n  /* tags */ tags: ['a', 'b',],\n}\n")
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  id: number;\n  tags: string[];\n};\n"
+        );
+        assert!(parse_json5("{ \"id\": }").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn parses_csv_rows() {
+        let csv = "id,name,zip,paid,amount,note\n1,\"Smith, J\",01234,true,9.5,\n2,Lee,90210,FALSE,10,late\n";
+        let rows: Vec<_> = parse_csv(csv.as_bytes(), b',')
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(rows[0].0, 2);
+        assert_eq!(
+            rows[0].1,
+            serde_json::json!({ "id": 1, "name": "Smith, J", "zip": "01234", "paid": true, "amount": 9.5, "note": null })
+        );
+        let mut result = rows
+            .iter()
+            .map(|(_, row)| walk_value_tree(row, None))
+            .reduce(TypeScriptNode::merge)
+            .unwrap();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  amount: number;\n  id: number;\n  name: string;\n  note: string | null;\n  paid: boolean;\n  zip: string | number;\n};\n"
+        );
+        let tsv: Vec<_> = parse_csv("a\tb\n-1\tinf\n".as_bytes(), b'\t')
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(tsv[0].1, serde_json::json!({ "a": -1, "b": "inf" }));
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn decodes_msgpack_and_cbor() {
+        let expected = serde_json::json!({ "id": 1, "big": u64::MAX, "ratio": 0.5, "tags": ["a"], "blob": [1, 2], "1": null });
+        let mut msgpack = Vec::new();
+        rmpv::encode::write_value(
+            &mut msgpack,
+            &rmpv::Value::Map(vec![
+                ("id".into(), 1.into()),
+                ("big".into(), u64::MAX.into()),
+                ("ratio".into(), 0.5.into()),
+                ("tags".into(), rmpv::Value::Array(vec!["a".into()])),
+                ("blob".into(), rmpv::Value::Binary(vec![1, 2])),
+                (1.into(), rmpv::Value::Nil),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(parse_msgpack(&msgpack).unwrap(), expected);
+        let mut cbor = Vec::new();
+        ciborium::ser::into_writer(
+            &ciborium::Value::Map(vec![
+                ("id".into(), 1.into()),
+                ("big".into(), u64::MAX.into()),
+                ("ratio".into(), 0.5.into()),
+                (
+                    "tags".into(),
+                    ciborium::Value::Tag(1, Box::new(ciborium::Value::Array(vec!["a".into()]))),
+                ),
+                ("blob".into(), ciborium::Value::Bytes(vec![1, 2])),
+                (1.into(), ciborium::Value::Null),
+            ]),
+            &mut cbor,
+        )
+        .unwrap();
+        assert_eq!(parse_cbor(&cbor).unwrap(), expected);
+        assert!(parse_msgpack(&[0x92, 0x01]).is_err());
+        assert!(parse_cbor(&[0xff]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn parses_toml() {
+        let val_tree = parse_toml(
+            "[package]\nname = \"myrrh\"\nversion = \"0.1.0\"\nreleased = 2024-01-02\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n\n[[bin]]\nname = \"myrrh\"\nratio = nan\n",
+        )
+        .unwrap();
+        assert_eq!(val_tree["package"]["released"], "2024-01-02");
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  bin: {\n    name: string;\n    ratio: null;\n  }[];\n  dependencies: {\n    serde: {\n      features: string[];\n      version: string;\n    };\n  };\n  \"package\": {\n    name: string;\n    released: string;\n    version: string;\n  };\n};\n"
+        );
+        assert!(parse_toml("name = ").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn types_nullable_fields_as_unions() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "note": "a" }, { "note": null }], "refund": null }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        let mut optional = result.clone();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  payments: DefaultType_0[];\n  refund: null;\n};\n\ntype DefaultType_0 = {\n  note: string | null;\n};\n"
+        );
+        optional.null_as_optional();
+        optional.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&optional),
+            "type DefaultType = {\n  payments: DefaultType_0[];\n  refund: null;\n};\n\ntype DefaultType_0 = {\n  note?: string;\n};\n"
+        );
+    }
+
+    #[test]
+    fn unifies_repeated_objects() {
+        let val_tree = serde_json::from_str(
+            r#"{ "order": { "customer": { "id": 1, "email": "a" } }, "refunds": [{ "customer": { "id": 2 } }], "tags": [{ "id": "x" }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.unify_repeated_objects();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  order: DefaultType_1;\n  refunds: DefaultType_1[];\n  tags: {\n    id: string;\n  }[];\n};\n\ntype DefaultType_0 = {\n  email?: string;\n  id: number;\n};\n\ntype DefaultType_1 = {\n  customer: DefaultType_0;\n};\n"
+        );
+    }
+
+    #[test]
+    fn leaves_objects_nested_in_a_namesake_alone() {
+        let unified = |json: &str| {
+            let val_tree = serde_json::from_str(json).unwrap();
+            let mut result = walk_value_tree(&val_tree, None);
+            result.fold_recursive_objects();
+            result.unify_repeated_objects();
+            result.calculate_hash();
+            TypeScriptNode::to_type_string(&result)
+        };
+        assert_eq!(
+            unified(r#"{"c":{"id":1,"c":{"id":"x","c":{"id":2}}}}"#),
+            "type DefaultType = {\n  c: {\n    c: {\n      c: {\n        id: number;\n      };\n      id: string;\n    };\n    id: number;\n  };\n};\n"
+        );
+        assert_eq!(
+            unified(
+                r#"{"child":{"name":"a","child":{"name":"b","age":3,"child":{"name":"c"}}}}"#
+            ),
+            "type DefaultType = {\n  child: {\n    child: {\n      age: number;\n      child: {\n        name: string;\n      };\n      name: string;\n    };\n    name: string;\n  };\n};\n"
+        );
+    }
+
+    #[test]
+    fn merges_conflicting_types_into_a_union() {
+        let samples = [
+            serde_json::json!({ "a": 1, "meta": { "id": 1 } }),
+            serde_json::json!({ "a": "s", "meta": { "name": "n" }, "b": [1] }),
+            serde_json::json!({ "a": null, "meta": "none", "b": "x" }),
+        ];
+        let mut result = samples
+            .iter()
+            .map(|sample| walk_value_tree(sample, None))
+            .reduce(TypeScriptNode::merge)
+            .unwrap();
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  a: number | string | null;\n  meta: {\n    id?: number;\n    name?: string;\n  } | string;\n  b?: number[] | string;\n};\n"
+        );
+    }
+
+    #[test]
+    fn counts_array_elements_whatever_their_shapes() {
+        let rendered = |json: &str| {
+            let val_tree = serde_json::from_str(json).unwrap();
+            let mut result = walk_value_tree(&val_tree, None);
+            result.calculate_hash();
+            TypeScriptNode::to_type_string(&result)
+        };
+        assert_eq!(
+            rendered(r#"{"xs":[{"a":1},{"a":2}]}"#),
+            "type DefaultType = {\n  xs: DefaultType_0[];\n};\n\ntype DefaultType_0 = {\n  a: number;\n};\n"
+        );
+        assert_eq!(
+            rendered(r#"{"xs":[{"a":1},{"a":2,"b":1}]}"#),
+            "type DefaultType = {\n  xs: DefaultType_0[];\n};\n\ntype DefaultType_0 = {\n  a: number;\n  b?: number;\n};\n"
+        );
+    }
+
+    #[test]
+    fn merges_overlapping_array_elements() {
+        let val_tree = serde_json::from_str(
+            r#"{ "items": [{ "id": 1, "sku": "a" }, { "id": 2, "qty": 3 }, { "id": "x" }, { "name": "n" }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  items: (DefaultType_0 | {\n    id: string;\n  } | {\n    name: string;\n  })[];\n};\n\ntype DefaultType_0 = {\n  id: number;\n  sku?: string;\n  qty?: number;\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_tagged_objects_as_a_tagged_union() {
+        let val_tree = serde_json::from_str(
+            r#"{ "events": [{ "type": "click", "x": 1 }, { "type": "key", "key": "a" }, { "type": "click", "x": 2 }], "ops": [{ "op": "add", "v": 1 }, { "op": "del", "v": 2 }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  events: (DefaultType_0 | {\n    key: string;\n    type: \"key\";\n  })[];\n  ops: DefaultType_1[];\n};\n\ntype DefaultType_0 = {\n  type: \"click\";\n  x: number;\n};\n\ntype DefaultType_1 = {\n  op: string;\n  v: number;\n};\n"
+        );
+        let options = InferOptions {
+            discriminator: Some("op".to_string()),
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert!(TypeScriptNode::to_type_string(&result).contains(
+            "ops: ({\n    op: \"add\";\n    v: number;\n  } | {\n    op: \"del\";\n    v: number;\n  })[];"
+        ));
+    }
+
+    #[test]
+    fn merges_large_arrays_in_order() {
+        let mut items = vec![serde_json::json!({ "id": 1, "sku": "a" })];
+        items.extend(
+            (0..super::PARALLEL_ELEMENTS * 4).map(|id| serde_json::json!({ "id": id, "qty": 3 })),
+        );
+        items.push(serde_json::json!({ "id": "x" }));
+        items.push(serde_json::json!({ "name": "n" }));
+        let val_tree = serde_json::json!({ "items": items });
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  items: (DefaultType_0 | {\n    id: string;\n  } | {\n    name: string;\n  })[];\n};\n\ntype DefaultType_0 = {\n  id: number;\n  sku?: string;\n  qty?: number;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn orders_keys() {
+        let render = |key_order| {
+            let options = InferOptions {
+                key_order,
+                ..InferOptions::default()
+            };
+            let first = serde_json::from_str(r#"{ "b": { "y": 1, "x": 2 }, "a": 1 }"#).unwrap();
+            let second = serde_json::from_str(r#"{ "c": { "x": 1, "y": 2 }, "a": 2 }"#).unwrap();
+            let mut result = walk_value_tree_with_options(&first, None, &options)
+                .merge(walk_value_tree_with_options(&second, None, &options));
+            if key_order == KeyOrder::Sorted {
+                result.sort_fields();
+            }
+            result.calculate_hash();
+            TypeScriptNode::to_type_string(&result)
+        };
+        assert_eq!(
+            render(KeyOrder::Sorted),
+            "type DefaultType = {\n  a: number;\n  b?: DefaultType_0;\n  c?: DefaultType_0;\n};\n\ntype DefaultType_0 = {\n  x: number;\n  y: number;\n};\n"
+        );
+        assert_eq!(
+            render(KeyOrder::Insertion),
+            "type DefaultType = {\n  b?: DefaultType_0;\n  a: number;\n  c?: DefaultType_0;\n};\n\ntype DefaultType_0 = {\n  y: number;\n  x: number;\n};\n"
+        );
+        assert_eq!(
+            clap::ValueEnum::from_str("input", false),
+            Ok(KeyOrder::Insertion)
+        );
+        assert_eq!(
+            clap::ValueEnum::from_str("alpha", false),
+            Ok(KeyOrder::Sorted)
+        );
+    }
+
+    #[test]
+    fn emits_interfaces() {
+        let val_tree =
+            serde_json::from_str(r#"{ "test": [{ "test": "test" }, { "test": "test" }] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            declaration_style: DeclarationStyle::Interface,
+            ..RenderOptions::default()
+        };
+        let output_string = TypeScriptNode::to_type_string_with_options(&result, &options);
+        assert_eq!(
+            output_string,
+            "interface DefaultType {\n  test: DefaultType_0[];\n}\n\ninterface DefaultType_0 {\n  test: string;\n}\n"
+        );
+    }
+
+    #[test]
+    fn names_types_after_the_root_name() {
+        let val_tree =
+            serde_json::from_str(r#"{ "test": [{ "test": "test" }, { "test": "test" }] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            root_name: "Order".to_string(),
+            ..RenderOptions::default()
+        };
+        let output_string = TypeScriptNode::to_type_string_with_options(&result, &options);
+        assert_eq!(
+            output_string,
+            "type Order = {\n  test: Order_0[];\n};\n\ntype Order_0 = {\n  test: string;\n};\n"
+        );
+        assert!(crate::is_type_name("Order"));
+        assert!(!crate::is_type_name("1Order"));
+        assert!(!crate::is_type_name("string"));
+    }
+
+    #[test]
+    fn infers_and_renders_through_the_public_api() {
+        let options = InferOptions::default();
+        let tree = crate::infer(&serde_json::json!({ "id": 1, "note": "a" }), &options)
+            .merge(crate::infer(&serde_json::json!({ "id": 2 }), &options));
+        assert_eq!(
+            crate::render_typescript(&tree, &RenderOptions::default()),
+            "type DefaultType = {\n  id: number;\n  note?: string;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn documents_original_names() {
+        let val_tree = serde_json::from_str(r#"{ "user_id": 1, "name": "a" }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.rename_fields(&crate::rename::to_camel_case);
+        result.calculate_hash();
+        let options = RenderOptions {
+            original_names: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  name: string;\n  /** @originalName user_id */\n  userId: number;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn keeps_the_names_of_fields_a_rename_would_clash_with() {
+        let val_tree = serde_json::from_str(r#"{ "user_id": 1, "userId": "x" }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.rename_fields(&crate::rename::to_camel_case);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  userId: string;\n  user_id: number;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn documents_examples() {
+        let first = serde_json::from_str(r#"{ "status": "paid", "user_id": 1 }"#).unwrap();
+        let second = serde_json::from_str(r#"{ "status": "pending", "user_id": 1 }"#).unwrap();
+        let third = serde_json::from_str(r#"{ "status": "refunded", "user_id": 2 }"#).unwrap();
+        let mut result = walk_value_tree(&first, None)
+            .merge(walk_value_tree(&second, None))
+            .merge(walk_value_tree(&third, None));
+        result.rename_fields(&crate::rename::to_camel_case);
+        result.calculate_hash();
+        let options = RenderOptions {
+            examples: true,
+            original_names: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  /** e.g. \"paid\", \"pending\" */\n  status: string;\n  /**\n   * e.g. 1, 2\n   * @originalName user_id\n   */\n  userId: number;\n};\n"
+        );
+    }
+
+    #[test]
+    fn declares_readonly_properties() {
+        let val_tree =
+            serde_json::from_str(r#"{ "ids": [1], "grid": [[1]], "tags": ["a", null] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            readonly: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  readonly grid: readonly (readonly number[])[];\n  readonly ids: readonly number[];\n  readonly tags: readonly (null | string)[];\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_values_below_the_max_depth_as_the_fallback_type() {
+        let val_tree = serde_json::from_str(
+            r#"[{ "id": 1, "trace": { "spans": [{ "id": 2 }] } }, { "id": 3, "trace": null }]"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            max_depth: Some(2),
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = DefaultType_0[];\n\ntype DefaultType_0 = {\n  id: number;\n  trace: unknown | null;\n};\n"
+        );
+        let options = RenderOptions {
+            fallback_type: FallbackType::JsonValue,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = DefaultType_0[];\n\ntype DefaultType_0 = {\n  id: number;\n  trace: JsonValue | null;\n};\n\ntype JsonValue = string | number | boolean | null | JsonValue[] | { [key: string]: JsonValue };\n"
+        );
+    }
+
+    #[test]
+    fn exports_declarations() {
+        let val_tree =
+            serde_json::from_str(r#"{ "test": [{ "test": 1 }, { "test": 2 }] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            export: true,
+            declaration_style: DeclarationStyle::Interface,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "export interface DefaultType {\n  test: DefaultType_0[];\n}\n\nexport interface DefaultType_0 {\n  test: number;\n}\n"
+        );
+    }
+
+    #[test]
+    fn formats_declarations() {
+        let val_tree =
+            serde_json::from_str(r#"{ "test": [{ "test": 1 }, { "test": 2 }] }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            format: FormatOptions {
+                use_tabs: true,
+                semicolons: false,
+                brace_style: BraceStyle::NextLine,
+                ..FormatOptions::default()
+            },
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType =\n{\n\ttest: DefaultType_0[]\n}\n\ntype DefaultType_0 =\n{\n\ttest: number\n}\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn infers_string_literals() {
+        let val_tree = serde_json::from_str(
+            r#"{ "orders": [{ "status": "paid", "tags": ["a"] }, { "status": "pending", "tags": ["a", "b"] }, { "status": "paid", "tags": [] }], "name": "x" }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.infer_literals(8);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(
+            output_string,
+            "type DefaultType = {\n  name: string;\n  orders: DefaultType_0[];\n};\n\ntype DefaultType_0 = {\n  status: \"paid\" | \"pending\";\n  tags: (\"a\" | \"b\")[];\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_iso_dates() {
+        let val_tree = serde_json::from_str(
+            r#"{ "created": "2024-01-31T12:00:00Z", "due": "2024-02-01", "note": "soon" }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_dates: true,
+            ..InferOptions::default()
+        };
+        let render = |date_type| {
+            let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+            result.calculate_hash();
+            let options = RenderOptions {
+                date_type: Some(date_type),
+                ..RenderOptions::default()
+            };
+            TypeScriptNode::to_type_string_with_options(&result, &options)
+        };
+        assert_eq!(
+            render(DateType::Comment),
+            "type DefaultType = {\n  created: string /* ISO date-time */;\n  due: string /* ISO date */;\n  note: string;\n};\n"
+        );
+        assert_eq!(
+            render(DateType::Branded("IsoDate".to_string())),
+            "type DefaultType = {\n  created: IsoDate;\n  due: IsoDate;\n  note: string;\n};\n\ntype IsoDate = string & { readonly __brand: \"IsoDate\" };\n"
+        );
+    }
+
+    #[test]
+    fn types_string_formats() {
+        let val_tree = serde_json::from_str(
+            r#"{ "id": "0f8fad5b-d9cb-469f-a165-70867728950e", "email": "a@b.io", "site": "https://example.com", "ip": "10.0.0.1", "name": "a" }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_string_formats: true,
+            ..InferOptions::default()
+        };
+        let render = |mode| {
+            let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+            result.calculate_hash();
+            let options = RenderOptions {
+                string_formats: Some(mode),
+                ..RenderOptions::default()
+            };
+            TypeScriptNode::to_type_string_with_options(&result, &options)
+        };
+        assert_eq!(
+            render(StringFormatMode::Branded),
+            "type DefaultType = {\n  email: Email;\n  id: UUID;\n  ip: IPv4;\n  name: string;\n  site: Url;\n};\n\ntype UUID = string & { readonly __brand: \"UUID\" };\n\ntype Email = string & { readonly __brand: \"Email\" };\n\ntype Url = string & { readonly __brand: \"Url\" };\n\ntype IPv4 = string & { readonly __brand: \"IPv4\" };\n"
+        );
+        assert_eq!(
+            render(StringFormatMode::Jsdoc),
+            "type DefaultType = {\n  /** @format email */\n  email: string;\n  /** @format uuid */\n  id: string;\n  /** @format ipv4 */\n  ip: string;\n  name: string;\n  /** @format uri */\n  site: string;\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_base64_strings() {
+        let val_tree = serde_json::from_str(
+            r#"{ "avatar": "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJ", "name": "a" }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_base64: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        let options = RenderOptions {
+            base64_type: Some("Base64String".to_string()),
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  /** Base64 encoded, decode it before use */\n  avatar: Base64String;\n  name: string;\n};\n\ntype Base64String = string;\n"
+        );
+    }
+
+    #[test]
+    fn types_integers() {
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let infer = |json| {
+            walk_value_tree_with_options(&serde_json::from_str(json).unwrap(), None, &options)
+        };
+        let mut result = infer(r#"{ "amount": 100, "rate": 1 }"#)
+            .merge(infer(r#"{ "amount": 250, "rate": 0.5 }"#));
+        result.calculate_hash();
+        let options = RenderOptions {
+            number_mode: NumberMode::Branded,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  amount: Integer;\n  rate: number;\n};\n\ntype Integer = number & { readonly __brand: \"Integer\" };\n"
+        );
+        let options = RenderOptions {
+            number_mode: NumberMode::Jsdoc,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {\n  amount: /** @type {integer} */ number;\n  rate: number;\n};\n"
+        );
+    }
+
+    #[test]
+    fn types_big_integers() {
+        let val_tree =
+            serde_json::from_str(r#"{ "id": 1234567890123456789, "count": 3 }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let render = |bigint_mode| {
+            let options = RenderOptions {
+                bigint_mode,
+                ..RenderOptions::default()
+            };
+            TypeScriptNode::to_type_string_with_options(&result, &options)
+        };
+        assert_eq!(
+            render(BigIntMode::Number),
+            "type DefaultType = {\n  count: number;\n  id: number;\n};\n"
+        );
+        assert_eq!(
+            render(BigIntMode::Bigint),
+            "type DefaultType = {\n  count: number;\n  id: bigint;\n};\n"
+        );
+        assert_eq!(
+            render(BigIntMode::String),
+            "type DefaultType = {\n  count: number;\n  id: string;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn splits_types_into_files() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payment": { "card": { "last4": "1234" } }, "refund": { "card": { "last4": "5678" } } }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.unify_repeated_objects();
+        result.calculate_hash();
+        let files = TypeScriptNode::to_type_files(&result, &RenderOptions::default());
+        assert_eq!(
+            files,
+            vec![
+                (
+                    "DefaultType.ts".to_string(),
+                    "import type { DefaultType_1 } from \"./DefaultType_1\";\n\nexport type DefaultType = {\n  payment: DefaultType_1;\n  refund: DefaultType_1;\n};\n".to_string()
+                ),
+                (
+                    "DefaultType_0.ts".to_string(),
+                    "export type DefaultType_0 = {\n  last4: string;\n};\n".to_string()
+                ),
+                (
+                    "DefaultType_1.ts".to_string(),
+                    "import type { DefaultType_0 } from \"./DefaultType_0\";\n\nexport type DefaultType_1 = {\n  card: DefaultType_0;\n};\n".to_string()
+                ),
+                (
+                    "index.ts".to_string(),
+                    "export * from \"./DefaultType\";\nexport * from \"./DefaultType_0\";\nexport * from \"./DefaultType_1\";\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_types_inline_without_squashing() {
+        let val_tree =
+            serde_json::from_str(r#"{ "billing": { "city": "a" }, "shipping": { "city": "b" } }"#)
+                .unwrap();
+        // the hashes are only calculated when squashing
+        let result = walk_value_tree(&val_tree, None);
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  billing: {\n    city: string;\n  };\n  shipping: {\n    city: string;\n  };\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn renders_one_tree_to_several_targets() {
+        let val_tree = serde_json::from_str(r#"{ "id": 1 }"#).unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions::default();
+        let types = crate::render_types(&result, &options);
+        assert_eq!(
+            crate::zod::to_zod_string(&result, &options),
+            "import { z } from \"zod\";\n\nexport const DefaultTypeSchema = z.object({\n  id: z.number(),\n});\nexport type DefaultType = z.infer<typeof DefaultTypeSchema>;\n"
+        );
+        assert_eq!(crate::render_types(&result, &options), types);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Distinct values tracked per string node before giving up on literal inference
+const MAX_DISTINCT_VALUES: usize = 256;
+
+/// The values observed for a string node, used to infer string literal unions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct StringValues {
+    distinct: BTreeSet<String>,
+    observations: usize,
+    overflowed: bool,
+}
+
+impl StringValues {
+    pub(crate) fn observe(&mut self, value: &str) {
+        self.observations += 1;
+        if self.distinct.contains(value) {
+            return;
+        }
+        if self.distinct.len() >= MAX_DISTINCT_VALUES {
+            self.overflowed = true;
+        } else {
+            self.distinct.insert(value.to_string());
+        }
+    }
+
+    /// Whether no value was observed, as for nodes which aren't strings
+    pub(crate) fn is_empty(&self) -> bool {
+        self.observations == 0
+    }
+
+    pub(crate) fn absorb(&mut self, other: &StringValues) {
+        self.observations += other.observations;
+        self.overflowed |= other.overflowed;
+        for value in &other.distinct {
+            if self.distinct.len() >= MAX_DISTINCT_VALUES && !self.distinct.contains(value) {
+                self.overflowed = true;
+            } else {
+                self.distinct.insert(value.clone());
+            }
+        }
+    }
+
+    /// The observed values, when there are at most `limit` of them and at least one was seen
+    /// more than once, so a field observed once doesn't become a literal of its only value
+    #[cfg(feature = "cli")]
+    pub(crate) fn literals(&self, limit: usize) -> Option<Vec<String>> {
+        let repeated = self.observations > self.distinct.len();
+        if self.overflowed || self.distinct.is_empty() || self.distinct.len() > limit || !repeated {
+            return None;
+        }
+        Some(self.distinct.iter().cloned().collect())
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::StringValues;
+
+    #[test]
+    fn infers_literals_from_repeated_values() {
+        let mut values = StringValues::default();
+        values.observe("paid");
+        assert_eq!(values.literals(8), None);
+        let mut other = StringValues::default();
+        other.observe("pending");
+        other.observe("paid");
+        values.absorb(&other);
+        assert_eq!(
+            values.literals(8),
+            Some(vec!["paid".to_string(), "pending".to_string()])
+        );
+        assert_eq!(values.literals(1), None);
+    }
+}
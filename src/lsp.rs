@@ -0,0 +1,227 @@
+use std::io::{BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::request::{infer_types, RequestOptions};
+use crate::{Context, Result};
+
+/// The command editors run through `workspace/executeCommand`, with the JSON document and the
+/// options as its arguments
+const INFER_TYPES_COMMAND: &str = "myrrh.inferTypes";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// The params of `myrrh/inferTypes`
+#[derive(Deserialize)]
+struct InferTypesParams {
+    json: String,
+    #[serde(default)]
+    options: RequestOptions,
+}
+
+#[derive(Deserialize)]
+struct ExecuteCommandParams {
+    command: String,
+    #[serde(default)]
+    arguments: Vec<Value>,
+}
+
+type RpcResult = std::result::Result<Value, (i64, String)>;
+
+/// Answers the requests read from `input` until the client exits or closes it. Messages are
+/// framed as in the Language Server Protocol, a `Content-Length` header followed by the
+/// JSON-RPC message.
+pub(crate) fn serve(mut input: impl BufRead, mut output: impl Write) -> Result<()> {
+    while let Some(content) = read_message(&mut input)? {
+        let message: Value = match serde_json::from_slice(&content) {
+            Ok(message) => message,
+            Err(error) => {
+                write_message(
+                    &mut output,
+                    &Value::Null,
+                    Err((PARSE_ERROR, error.to_string())),
+                )?;
+                continue;
+            }
+        };
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if method == "exit" {
+            break;
+        }
+        // notifications, `initialized` among them, aren't answered
+        let Some(id) = message.get("id") else {
+            continue;
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        write_message(&mut output, id, handle(method, params))?;
+    }
+    Ok(())
+}
+
+fn handle(method: &str, params: Value) -> RpcResult {
+    match method {
+        "initialize" => Ok(json!({
+            "capabilities": {
+                "executeCommandProvider": { "commands": [INFER_TYPES_COMMAND] },
+            },
+            "serverInfo": { "name": "myrrh", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "shutdown" => Ok(Value::Null),
+        "myrrh/inferTypes" => {
+            let params: InferTypesParams = serde_json::from_value(params).map_err(invalid)?;
+            infer(&params.json, params.options)
+        }
+        "workspace/executeCommand" => {
+            let params: ExecuteCommandParams = serde_json::from_value(params).map_err(invalid)?;
+            if params.command != INFER_TYPES_COMMAND {
+                return Err((
+                    INVALID_PARAMS,
+                    format!("unknown command `{}`", params.command),
+                ));
+            }
+            let mut arguments = params.arguments.into_iter();
+            let json = match arguments.next() {
+                Some(Value::String(json)) => json,
+                _ => {
+                    return Err((
+                        INVALID_PARAMS,
+                        format!("{} takes the JSON document first", INFER_TYPES_COMMAND),
+                    ))
+                }
+            };
+            let options = match arguments.next() {
+                Some(options) if !options.is_null() => {
+                    serde_json::from_value(options).map_err(invalid)?
+                }
+                _ => RequestOptions::default(),
+            };
+            infer(&json, options)
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+    }
+}
+
+fn infer(json: &str, options: RequestOptions) -> RpcResult {
+    infer_types(json, options)
+        .map(Value::String)
+        .map_err(|error| (INVALID_PARAMS, format!("{:#}", error)))
+}
+
+fn invalid(error: serde_json::Error) -> (i64, String) {
+    (INVALID_PARAMS, error.to_string())
+}
+
+/// Reads the content of the next message, `None` once the input is closed
+fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input
+            .read_line(&mut header)
+            .context("could not read a header")?
+            == 0
+        {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .with_context(|| format!("invalid Content-Length `{}`", value.trim()))?,
+                );
+            }
+        }
+    }
+    let content_length = content_length.context("a message has no Content-Length header")?;
+    let mut content = vec![0; content_length];
+    input
+        .read_exact(&mut content)
+        .context("could not read a message")?;
+    Ok(Some(content))
+}
+
+fn write_message(output: &mut impl Write, id: &Value, result: RpcResult) -> Result<()> {
+    let message = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message },
+        }),
+    };
+    let content = serde_json::to_string(&message).unwrap();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|_| output.flush())
+    .context("could not write a response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::serve;
+
+    fn framed(messages: &[Value]) -> Vec<u8> {
+        messages
+            .iter()
+            .flat_map(|message| {
+                let content = message.to_string();
+                format!("Content-Length: {}\r\n\r\n{}", content.len(), content).into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn answers_requests_until_exit() {
+        let input = framed(&[
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "myrrh/inferTypes", "params": { "json": "{ \"id\": 1 }", "options": { "rootName": "Order" } } }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "workspace/executeCommand", "params": { "command": "myrrh.inferTypes", "arguments": ["{"] } }),
+            json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+            json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        ]);
+        let mut output = Vec::new();
+        serve(input.as_slice(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let responses: Vec<Value> = output
+            .split("Content-Length: ")
+            .skip(1)
+            .map(|message| serde_json::from_str(message.split_once("\r\n\r\n").unwrap().1).unwrap())
+            .collect();
+        assert_eq!(
+            responses
+                .iter()
+                .map(|response| response["id"].clone())
+                .collect::<Vec<_>>(),
+            vec![json!(1), json!(2), json!(3), json!(4)]
+        );
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["executeCommandProvider"]["commands"],
+            json!(["myrrh.inferTypes"])
+        );
+        assert_eq!(
+            responses[1]["result"],
+            json!("type Order = {\n  id: number;\n};\n")
+        );
+        assert_eq!(responses[2]["error"]["code"], json!(-32602));
+    }
+}
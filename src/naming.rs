@@ -0,0 +1,570 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use serde::Deserialize;
+use tracing::{event, Level};
+
+use crate::rename::{to_pascal_case, words};
+use crate::typescript_node::{Fingerprint, TypeScriptNode, TypeScriptPrimativeType};
+
+/// How the common object types which get their own declaration are named, as given to `--naming`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum NamingStrategy {
+    /// After the fields holding them, `Payment` for the objects under `paymentOne` and
+    /// `paymentTwo` or the elements of `payments`, `TestItem` for the elements of `test`
+    PathBased,
+    /// `{root_name}_N`, numbered in the order the renderer reaches them
+    #[default]
+    Sequential,
+    /// `{root_name}_` followed by a digest of the type's shape, which doesn't change as other
+    /// types come and go
+    Hashed,
+}
+
+/// Names the common object types in place of a [`NamingStrategy`], for names none of them give,
+/// such as a `Dto` suffix. A closure taking the [`TypeNameParts`] is a namer too.
+pub trait TypeNamer: Send + Sync {
+    /// The name of a common type. A number is appended to names which are already taken.
+    fn name(&self, parts: &TypeNameParts) -> String;
+}
+
+impl<F> TypeNamer for F
+where
+    F: Fn(&TypeNameParts) -> String + Send + Sync,
+{
+    fn name(&self, parts: &TypeNameParts) -> String {
+        self(parts)
+    }
+}
+
+impl fmt::Debug for dyn TypeNamer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TypeNamer")
+    }
+}
+
+/// What the name of a common type can be made of
+#[derive(Debug, Clone, Copy)]
+pub struct TypeNameParts<'a> {
+    /// Name of the root type
+    pub root: &'a str,
+    /// The PascalCase segments of the path to the first object of the type, after the root:
+    /// `["Order", "Line"]` for the elements of `order.lines`
+    pub path: &'a [String],
+    /// The name `--naming path-based` starts from, `Payment` for the objects under `paymentOne`
+    /// and `paymentTwo`
+    pub name: &'a str,
+    /// Position of the type in naming order, from 0
+    pub index: usize,
+    /// Digest of the type's shape, as `--naming hashed` appends it
+    pub hash: &'a str,
+}
+
+/// A `--type-name-template`, such as `{Root}{Path}` or `{Name}Dto`, made of the [`TypeNameParts`]
+/// `{Root}`, `{Path}`, `{Name}`, `{N}` and `{Hash}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeNameTemplate(String);
+
+const PLACEHOLDERS: [&str; 5] = ["{Root}", "{Path}", "{Name}", "{N}", "{Hash}"];
+
+impl FromStr for TypeNameTemplate {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let placeholder = match rest[start..].find('}') {
+                Some(end) => &rest[start..start + end + 1],
+                None => return Err(format!("unclosed `{{` in `{}`", template)),
+            };
+            if !PLACEHOLDERS.contains(&placeholder) {
+                return Err(format!(
+                    "unknown placeholder `{}`, use {}",
+                    placeholder,
+                    PLACEHOLDERS.join(", ")
+                ));
+            }
+            rest = &rest[start + placeholder.len()..];
+        }
+        if !template.contains('{') {
+            return Err(format!(
+                "`{}` would give every type the same name, use {}",
+                template,
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+        Ok(TypeNameTemplate(template.to_string()))
+    }
+}
+
+impl TypeNamer for TypeNameTemplate {
+    fn name(&self, parts: &TypeNameParts) -> String {
+        self.0
+            .replace("{Root}", parts.root)
+            .replace("{Path}", &parts.path.concat())
+            .replace("{Name}", parts.name)
+            .replace("{N}", &parts.index.to_string())
+            .replace("{Hash}", parts.hash)
+    }
+}
+
+/// Names the common types, given by hash in naming order, with `namer` when there is one
+pub(crate) fn name_types(
+    node: &TypeScriptNode,
+    root_name: &str,
+    naming: NamingStrategy,
+    namer: Option<&dyn TypeNamer>,
+    common_types: &[Fingerprint],
+) -> HashMap<Fingerprint, String> {
+    if let Some(namer) = namer {
+        return custom_names(node, root_name, namer, common_types);
+    }
+    match naming {
+        NamingStrategy::PathBased => path_based_names(node, root_name, common_types),
+        NamingStrategy::Sequential => common_types
+            .iter()
+            .enumerate()
+            .map(|(index, hash)| (*hash, format!("{}_{}", root_name, index)))
+            .collect(),
+        NamingStrategy::Hashed => common_types
+            .iter()
+            .map(|hash| (*hash, format!("{}_{}", root_name, digest(*hash))))
+            .collect(),
+    }
+}
+
+/// The digest of a type's shape its hashed name ends with
+fn digest(hash: Fingerprint) -> String {
+    format!("{:08x}", hash & 0xffff_ffff)
+}
+
+fn custom_names(
+    node: &TypeScriptNode,
+    root_name: &str,
+    namer: &dyn TypeNamer,
+    common_types: &[Fingerprint],
+) -> HashMap<Fingerprint, String> {
+    let mut occurrences = HashMap::new();
+    collect_paths(
+        node,
+        vec![root_name.to_string()],
+        common_types,
+        &mut occurrences,
+    );
+    let mut taken = HashSet::from([root_name.to_string()]);
+    let mut names = HashMap::new();
+    for (index, hash) in common_types.iter().enumerate() {
+        let paths = &occurrences[hash];
+        let mut name = namer.name(&TypeNameParts {
+            root: root_name,
+            path: &paths[0][1..],
+            name: &base_name(paths),
+            index,
+            hash: &digest(*hash),
+        });
+        if !crate::is_type_name(&name) {
+            let fallback = format!("{}_{}", root_name, index);
+            event!(
+                Level::WARN,
+                name = name.as_str(),
+                fallback = fallback.as_str(),
+                "a custom type name is not a valid TypeScript type name"
+            );
+            name = fallback;
+        }
+        if taken.contains(&name) {
+            name = (2..)
+                .map(|number| format!("{}{}", name, number))
+                .find(|numbered| !taken.contains(numbered))
+                .unwrap();
+        }
+        taken.insert(name.clone());
+        names.insert(*hash, name);
+    }
+    names
+}
+
+fn path_based_names(
+    node: &TypeScriptNode,
+    root_name: &str,
+    common_types: &[Fingerprint],
+) -> HashMap<Fingerprint, String> {
+    let mut occurrences = HashMap::new();
+    collect_paths(
+        node,
+        vec![root_name.to_string()],
+        common_types,
+        &mut occurrences,
+    );
+    let mut taken = HashSet::from([root_name.to_string()]);
+    let mut names = HashMap::new();
+    for hash in common_types {
+        let name = unique_name(&occurrences[hash], &taken);
+        taken.insert(name.clone());
+        names.insert(*hash, name);
+    }
+    names
+}
+
+/// Records the path of PascalCase segments leading to every occurrence of a common type,
+/// starting from the root name. Array elements take the singular of the segment of the array,
+/// or extend it with `Item` when it isn't a plural.
+fn collect_paths(
+    node: &TypeScriptNode,
+    path: Vec<String>,
+    common_types: &[Fingerprint],
+    occurrences: &mut HashMap<Fingerprint, Vec<Vec<String>>>,
+) {
+    match node.type_signature() {
+        TypeScriptPrimativeType::Object => {
+            if common_types.contains(&node.hash()) && !node.is_self_reference() {
+                occurrences
+                    .entry(node.hash())
+                    .or_default()
+                    .push(path.clone());
+            }
+            for sub_item in node.sub_items() {
+                let mut sub_path = path.clone();
+                let segment = segment(sub_item.name().unwrap_or_default());
+                if !segment.is_empty() {
+                    sub_path.push(segment);
+                }
+                collect_paths(sub_item, sub_path, common_types, occurrences);
+            }
+        }
+        TypeScriptPrimativeType::Union => {
+            // the members of a root union would otherwise be named after the root itself
+            let mut member_path = path;
+            if let (true, Some(last)) = (node.is_root(), member_path.last_mut()) {
+                last.push_str("Variant");
+            }
+            for member in node.sub_items() {
+                collect_paths(member, member_path.clone(), common_types, occurrences);
+            }
+        }
+        TypeScriptPrimativeType::Array => {
+            let mut element_path = path;
+            if let Some(last) = element_path.last_mut() {
+                match singular(last) {
+                    Some(singular) => *last = singular,
+                    None => last.push_str("Item"),
+                }
+            }
+            for element in node.sub_items() {
+                collect_paths(element, element_path.clone(), common_types, occurrences);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// `payment-intent` -> `PaymentIntent`, dropping anything which can't appear in a type name
+pub(crate) fn segment(key: &str) -> String {
+    to_pascal_case(key)
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect()
+}
+
+/// Plurals which don't end in a plain `s`, `es` or `ies`
+const IRREGULAR_PLURALS: [(&str, &str); 16] = [
+    ("people", "person"),
+    ("children", "child"),
+    ("men", "man"),
+    ("women", "woman"),
+    ("mice", "mouse"),
+    ("geese", "goose"),
+    ("feet", "foot"),
+    ("teeth", "tooth"),
+    ("indices", "index"),
+    ("matrices", "matrix"),
+    ("vertices", "vertex"),
+    ("criteria", "criterion"),
+    ("analyses", "analysis"),
+    ("leaves", "leaf"),
+    ("lives", "life"),
+    ("halves", "half"),
+];
+
+/// Words ending in `ies` whose singular ends in `ie` rather than `y`
+const IE_WORDS: [&str; 9] = [
+    "movie", "cookie", "zombie", "calorie", "selfie", "tie", "pie", "lie", "die",
+];
+
+/// Words ending in `s` which are already singular, or have no singular
+const UNCOUNTABLE: [&str; 6] = ["news", "series", "species", "alias", "canvas", "gas"];
+
+/// The segment of an array with its last word made singular, `LineItems` -> `LineItem` and
+/// `Categories` -> `Category`, or `None` when it doesn't look like a plural
+pub(crate) fn singular(segment: &str) -> Option<String> {
+    let last = words(segment).pop()?;
+    let prefix = segment.strip_suffix(last.as_str())?;
+    let lower = last.to_lowercase();
+    let singular = singular_word(&lower)?;
+    // keep the case of the plural, `Users` -> `User` and `URLS` -> `URL`
+    let singular: String = if last.chars().skip(1).all(|c| !c.is_lowercase()) {
+        singular.to_uppercase()
+    } else if last.starts_with(char::is_uppercase) {
+        let mut chars = singular.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        singular
+    };
+    Some(format!("{}{}", prefix, singular))
+}
+
+fn singular_word(word: &str) -> Option<String> {
+    if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(plural, _)| *plural == word) {
+        return Some(singular.to_string());
+    }
+    if UNCOUNTABLE.contains(&word)
+        || word.len() < 3
+        || word.ends_with("ss")
+        || word.ends_with("us")
+        || word.ends_with("is")
+    {
+        return None;
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        let ie_word = format!("{}ie", stem);
+        if IE_WORDS.contains(&ie_word.as_str()) {
+            return Some(ie_word);
+        }
+        return Some(format!("{}y", stem));
+    }
+    for suffix in ["sses", "xes", "zzes", "ches", "shes", "uses"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            // `statuses`, but not `houses` or `causes`
+            if suffix == "uses" && !stem.ends_with(['t', 'b', 'n', 'r']) {
+                break;
+            }
+            return Some(format!("{}{}", stem, &suffix[..suffix.len() - 2]));
+        }
+    }
+    word.strip_suffix('s').map(str::to_string)
+}
+
+/// The words the last segments of every path start with, so `PaymentOne` and `PaymentTwo`
+/// give `Payment`, falling back to the last segment of the first path
+fn base_name(paths: &[Vec<String>]) -> String {
+    let last_segments: Vec<&String> = paths.iter().filter_map(|path| path.last()).collect();
+    let first = last_segments[0];
+    if last_segments.iter().all(|segment| *segment == first) {
+        return first.clone();
+    }
+    let split: Vec<Vec<String>> = last_segments.iter().map(|segment| words(segment)).collect();
+    let common: String = split[0]
+        .iter()
+        .enumerate()
+        .take_while(|(index, word)| split.iter().all(|words| words.get(*index) == Some(word)))
+        .map(|(_, word)| word.as_str())
+        .collect();
+    if common.is_empty() {
+        first.clone()
+    } else {
+        common
+    }
+}
+
+/// The base name, qualified by the segments leading to its first occurrence until it no longer
+/// clashes with a name already taken, then numbered
+fn unique_name(paths: &[Vec<String>], taken: &HashSet<String>) -> String {
+    let base = base_name(paths);
+    let ancestors = &paths[0][..paths[0].len() - 1];
+    let mut candidate = base.clone();
+    for prefix_length in 1..=ancestors.len() + 1 {
+        if crate::is_type_name(&candidate) && !taken.contains(&candidate) {
+            return candidate;
+        }
+        if prefix_length > ancestors.len() {
+            break;
+        }
+        candidate = format!(
+            "{}{}",
+            ancestors[ancestors.len() - prefix_length..].concat(),
+            base
+        );
+    }
+    (2..)
+        .map(|number| format!("{}{}", candidate, number))
+        .find(|numbered| !taken.contains(numbered))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NamingStrategy, TypeNameParts, TypeNameTemplate};
+    use crate::typescript_node::{RenderOptions, TypeScriptNode};
+    use crate::walk_value_tree;
+    use std::sync::Arc;
+
+    #[test]
+    fn names_types_with_a_template_or_a_namer() {
+        let val_tree = serde_json::from_str(
+            r#"{ "paymentOne": { "amount": 1 }, "paymentTwo": { "amount": 2 }, "order": { "lines": [{ "sku": 1 }, { "sku": 2 }] } }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let render = |options: RenderOptions| {
+            TypeScriptNode::to_type_string_with_options(
+                &result,
+                &RenderOptions {
+                    root_name: "Root".to_string(),
+                    ..options
+                },
+            )
+        };
+        let template: TypeNameTemplate = "{Root}{Path}".parse().unwrap();
+        assert_eq!(
+            render(RenderOptions {
+                type_namer: Some(Arc::new(template)),
+                ..RenderOptions::default()
+            }),
+            "type Root = {
+  order: {
+    lines: RootOrderLine[];
+  };
+  paymentOne: RootPaymentOne;
+  paymentTwo: RootPaymentOne;
+};
+
+type RootOrderLine = {
+  sku: number;
+};
+
+type RootPaymentOne = {
+  amount: number;
+};
+"
+        );
+        // every name is the same, so the later ones are numbered
+        let namer = |parts: &TypeNameParts| format!("{}Dto", parts.root);
+        assert_eq!(
+            render(RenderOptions {
+                type_namer: Some(Arc::new(namer)),
+                ..RenderOptions::default()
+            }),
+            "type Root = {
+  order: {
+    lines: RootDto[];
+  };
+  paymentOne: RootDto2;
+  paymentTwo: RootDto2;
+};
+
+type RootDto = {
+  sku: number;
+};
+
+type RootDto2 = {
+  amount: number;
+};
+"
+        );
+        assert!("{Name}Dto".parse::<TypeNameTemplate>().is_ok());
+        assert!("{name}".parse::<TypeNameTemplate>().is_err());
+        assert!("Dto".parse::<TypeNameTemplate>().is_err());
+    }
+
+    #[test]
+    fn names_types_after_their_paths() {
+        let val_tree = serde_json::from_str(
+            r#"{ "paymentOne": { "amount": 1 }, "paymentTwo": { "amount": 2 }, "test": [{ "id": "a" }, { "id": "b" }], "order": { "test": [{ "sku": 1 }, { "sku": 2 }] } }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            root_name: "Root".to_string(),
+            naming: NamingStrategy::PathBased,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type Root = {
+  order: {
+    test: TestItem[];
+  };
+  paymentOne: Payment;
+  paymentTwo: Payment;
+  test: RootTestItem[];
+};
+
+type TestItem = {
+  sku: number;
+};
+
+type Payment = {
+  amount: number;
+};
+
+type RootTestItem = {
+  id: string;
+};
+"
+        );
+    }
+
+    #[test]
+    fn names_elements_after_the_singular_of_their_array() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1 }, { "amount": 2 }], "categories": [{ "id": 1, "lineItems": [{ "sku": "a" }, { "sku": "b" }] }, { "id": 2, "lineItems": [{ "sku": "c" }] }], "data": [{ "ok": true }, { "ok": false }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            naming: NamingStrategy::PathBased,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {
+  categories: Category[];
+  data: DataItem[];
+  payments: Payment[];
+};
+
+type LineItem = {
+  sku: string;
+};
+
+type Category = {
+  id: number;
+  lineItems: LineItem[];
+};
+
+type DataItem = {
+  ok: boolean;
+};
+
+type Payment = {
+  amount: number;
+};
+"
+        );
+        for (plural, singular) in [
+            ("Users", Some("User")),
+            ("Addresses", Some("Address")),
+            ("Statuses", Some("Status")),
+            ("Houses", Some("House")),
+            ("Movies", Some("Movie")),
+            ("People", Some("Person")),
+            ("URLS", Some("URL")),
+            ("Status", None),
+            ("Series", None),
+            ("Test", None),
+        ] {
+            assert_eq!(super::singular(plural).as_deref(), singular, "{}", plural);
+        }
+    }
+}
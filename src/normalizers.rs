@@ -0,0 +1,242 @@
+use itertools::Itertools;
+
+use crate::guards::check_expression_with_keys;
+use crate::typescript_node::{Fingerprint, TypeScriptNode, TypeScriptPrimativeType};
+
+#[derive(Clone, Copy)]
+enum Direction {
+    /// Wire keys to renamed keys
+    FromWire,
+    /// Renamed keys back to wire keys
+    ToWire,
+}
+
+impl Direction {
+    fn source_key(self, node: &TypeScriptNode) -> &str {
+        match self {
+            Direction::FromWire => node.wire_name().unwrap_or_default(),
+            Direction::ToWire => node.name().unwrap_or_default(),
+        }
+    }
+
+    fn target_key(self, node: &TypeScriptNode) -> &str {
+        match self {
+            Direction::FromWire => node.name().unwrap_or_default(),
+            Direction::ToWire => node.wire_name().unwrap_or_default(),
+        }
+    }
+}
+
+/// Renders `to{root_name}` / `from{root_name}` functions converting between the wire format
+/// `Raw{root_name}` and the renamed `{root_name}`, performing the key renames at runtime.
+/// Recursive types are converted by a named function calling itself for each reference to the
+/// type, so the tree has to be hashed for those to be told apart.
+pub(crate) fn to_normalizer_string(node: &TypeScriptNode, root_name: &str) -> String {
+    let to = convert(node, "raw", Direction::FromWire, 1, 0, &mut Vec::new())
+        .unwrap_or_else(|| "raw".to_string());
+    let from = convert(node, "value", Direction::ToWire, 1, 0, &mut Vec::new())
+        .unwrap_or_else(|| "value".to_string());
+    format!(
+        "\nexport function to{root}(raw: Raw{root}): {root} {{\n  return {to};\n}}\n\nexport function from{root}(value: {root}): Raw{root} {{\n  return {from};\n}}\n",
+        root = root_name,
+        to = to,
+        from = from
+    )
+}
+
+/// Whether a key of the node, or of a value nested in it, is renamed. A reference to a
+/// recursive type being converted, one of `recursive`, is.
+fn needs_rename(node: &TypeScriptNode, recursive: &[(Fingerprint, String)]) -> bool {
+    if node.is_self_reference() {
+        return recursive.iter().any(|(hash, _)| *hash == node.hash());
+    }
+    node.sub_items().iter().any(|sub_item| {
+        sub_item.wire_name() != sub_item.name() || needs_rename(sub_item, recursive)
+    })
+}
+
+/// Expression converting `value` between key styles, or `None` when nothing below the node
+/// is renamed and the value can be passed through as is. `recursive` holds the recursive
+/// types enclosing the node with the names of the functions converting them.
+fn convert(
+    node: &TypeScriptNode,
+    value: &str,
+    direction: Direction,
+    indent: usize,
+    depth: usize,
+    recursive: &mut Vec<(Fingerprint, String)>,
+) -> Option<String> {
+    if !needs_rename(node, recursive) {
+        return None;
+    }
+    match node.type_signature() {
+        TypeScriptPrimativeType::Object if node.is_self_reference() => {
+            let (_, function) = recursive.iter().find(|(hash, _)| *hash == node.hash())?;
+            Some(format!("{}({})", function, value))
+        }
+        TypeScriptPrimativeType::Object if node.is_recursive() => {
+            let function = format!("convert{}", recursive.len());
+            let argument = format!("node{}", recursive.len());
+            recursive.push((node.hash(), function.clone()));
+            let body = convert_object(node, &argument, direction, indent, depth, recursive);
+            recursive.pop();
+            Some(format!(
+                "(function {}({}: any): any {{ return {}; }})({})",
+                function, argument, body, value
+            ))
+        }
+        TypeScriptPrimativeType::Object => Some(convert_object(
+            node, value, direction, indent, depth, recursive,
+        )),
+        TypeScriptPrimativeType::Array => {
+            let item = format!("item{}", depth);
+            let alternatives: Vec<&TypeScriptNode> = node
+                .elements()
+                .iter()
+                .unique_by(|sub_item| {
+                    check_expression_with_keys(sub_item, &item, depth + 1, &|n| {
+                        direction.source_key(n)
+                    })
+                })
+                .collect();
+            let element = if let [element] = alternatives.as_slice() {
+                convert(element, &item, direction, indent, depth + 1, recursive)?
+            } else {
+                // differently shaped elements are told apart at runtime, so read them untyped
+                let untyped = format!("({} as any)", item);
+                alternatives
+                    .iter()
+                    .filter_map(|alternative| {
+                        let converted = convert(
+                            alternative,
+                            &untyped,
+                            direction,
+                            indent,
+                            depth + 1,
+                            recursive,
+                        )?;
+                        let check =
+                            check_expression_with_keys(alternative, &item, depth + 1, &|n| {
+                                direction.source_key(n)
+                            });
+                        Some(format!("{} ? {} : ", check, converted))
+                    })
+                    .collect::<String>()
+                    + &item
+            };
+            Some(format!("{}.map(({}) => ({}))", value, item, element))
+        }
+        TypeScriptPrimativeType::Union => {
+            let untyped = format!("({} as any)", value);
+            let converted = node
+                .sub_items()
+                .iter()
+                .filter_map(|member| {
+                    let converted = convert(member, &untyped, direction, indent, depth, recursive)?;
+                    let check = check_expression_with_keys(member, value, depth, &|n| {
+                        direction.source_key(n)
+                    });
+                    Some(format!("{} ? {} : ", check, converted))
+                })
+                .collect::<String>();
+            Some(format!("({}{})", converted, value))
+        }
+        _ => None,
+    }
+}
+
+/// Converts the fields of an object between key styles
+fn convert_object(
+    node: &TypeScriptNode,
+    value: &str,
+    direction: Direction,
+    indent: usize,
+    depth: usize,
+    recursive: &mut Vec<(Fingerprint, String)>,
+) -> String {
+    let pad = "  ".repeat(indent + 1);
+    let fields: Vec<String> = node
+        .sub_items()
+        .iter()
+        .map(|sub_item| {
+            let field = format!(
+                "{}[{}]",
+                value,
+                serde_json::to_string(direction.source_key(sub_item)).unwrap()
+            );
+            let converted = match convert(sub_item, &field, direction, indent + 1, depth, recursive)
+            {
+                None => field,
+                Some(converted) if sub_item.is_optional() || sub_item.is_nullable() => {
+                    format!("{} == null ? {} : {}", field, field, converted)
+                }
+                Some(converted) => converted,
+            };
+            format!(
+                "{}{}: {},\n",
+                pad,
+                serde_json::to_string(direction.target_key(sub_item)).unwrap(),
+                converted
+            )
+        })
+        .collect();
+    format!("{{\n{}{}}}", fields.concat(), "  ".repeat(indent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_normalizer_string;
+    use crate::rename::to_camel_case;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn converts_renamed_keys() {
+        let val_tree = serde_json::from_str(
+            r#"{ "user_id": 1, "line_items": [{ "unit_price": 2, "sku": "a" }], "note": null }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.rename_fields(&to_camel_case);
+        assert_eq!(
+            to_normalizer_string(&result, "DefaultType"),
+            "
+export function toDefaultType(raw: RawDefaultType): DefaultType {
+  return {
+    \"lineItems\": raw[\"line_items\"].map((item0) => ({
+      \"sku\": item0[\"sku\"],
+      \"unitPrice\": item0[\"unit_price\"],
+    })),
+    \"note\": raw[\"note\"],
+    \"userId\": raw[\"user_id\"],
+  };
+}
+
+export function fromDefaultType(value: DefaultType): RawDefaultType {
+  return {
+    \"line_items\": value[\"lineItems\"].map((item0) => ({
+      \"sku\": item0[\"sku\"],
+      \"unit_price\": item0[\"unitPrice\"],
+    })),
+    \"note\": value[\"note\"],
+    \"user_id\": value[\"userId\"],
+  };
+}
+"
+        );
+    }
+
+    #[test]
+    fn converts_recursive_types() {
+        let val_tree = serde_json::from_str(
+            r#"{ "comment_id": 1, "child_comments": [{ "comment_id": 2, "child_comments": [] }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.rename_fields(&to_camel_case);
+        result.calculate_hash();
+        assert_eq!(to_normalizer_string(&result, "DefaultType"),
+            "\nexport function toDefaultType(raw: RawDefaultType): DefaultType {\n  return (function convert0(node0: any): any { return {\n    \"childComments\": node0[\"child_comments\"].map((item0) => (convert0(item0))),\n    \"commentId\": node0[\"comment_id\"],\n  }; })(raw);\n}\n\nexport function fromDefaultType(value: DefaultType): RawDefaultType {\n  return (function convert0(node0: any): any { return {\n    \"child_comments\": node0[\"childComments\"].map((item0) => (convert0(item0))),\n    \"comment_id\": node0[\"commentId\"],\n  }; })(value);\n}\n"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use serde_json::{Map, Value};
+
+use crate::errors::ErrorKind;
+use crate::formats::StringFormat;
+use crate::naming::segment;
+use crate::typescript_node::{TypeScriptNode, TypeScriptPrimativeType};
+use crate::Result;
+
+const SCHEMA_PREFIX: &str = "#/components/schemas/";
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The types declared by an OpenAPI 3 document: one per schema component, named after it, and
+/// one per JSON response body, named after its operation, `ListUsersResponse` for the 200
+/// response of `listUsers` and `ListUsers404Response` for its 404. References to schema
+/// components are rendered by name rather than inlined.
+pub(crate) fn type_trees(spec: &Value) -> Result<Vec<(String, TypeScriptNode)>> {
+    if !spec.get("openapi").is_some_and(Value::is_string) {
+        anyhow::bail!(
+            ErrorKind::Parse.because("not an OpenAPI 3 document, there is no `openapi` version")
+        );
+    }
+    let mut trees = Vec::new();
+    let empty = Map::new();
+    let schemas = spec
+        .pointer("/components/schemas")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    for (name, schema) in schemas {
+        trees.push((schema_name(name), root(spec, schema)?));
+    }
+    let paths = spec
+        .get("paths")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    for (path, item) in paths {
+        for method in METHODS {
+            let Some(operation) = item.get(method) else {
+                continue;
+            };
+            let operation_name = match operation.get("operationId").and_then(Value::as_str) {
+                Some(operation_id) => segment(operation_id),
+                None => segment(&format!("{} {}", method, path)),
+            };
+            let responses = operation
+                .get("responses")
+                .and_then(Value::as_object)
+                .unwrap_or(&empty);
+            for (status, response) in responses {
+                let response = resolve(spec, response)?;
+                let Some(schema) = json_schema(response) else {
+                    continue;
+                };
+                let status = match status.as_str() {
+                    "200" => String::new(),
+                    status => segment(status),
+                };
+                trees.push((
+                    format!("{}{}Response", operation_name, status),
+                    root(spec, schema)?,
+                ));
+            }
+        }
+    }
+    Ok(trees)
+}
+
+/// The type name of a schema component, `Schema` prefixed to names which can't be one
+fn schema_name(name: &str) -> String {
+    let name = segment(name);
+    if crate::is_type_name(&name) {
+        name
+    } else {
+        format!("Schema{}", name)
+    }
+}
+
+/// The schema of the first JSON content of a response
+fn json_schema(response: &Value) -> Option<&Value> {
+    response
+        .get("content")?
+        .as_object()?
+        .iter()
+        .find(|(media_type, _)| media_type.contains("json"))?
+        .1
+        .get("schema")
+}
+
+/// Follows a local `$ref`, if there is one
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> Result<&'a Value> {
+    match value.get("$ref").and_then(Value::as_str) {
+        Some(reference) => {
+            let pointer = reference.strip_prefix('#').ok_or_else(|| {
+                ErrorKind::Parse.because(format!(
+                    "only local references are supported, not `{}`",
+                    reference
+                ))
+            })?;
+            Ok(spec.pointer(pointer).ok_or_else(|| {
+                ErrorKind::Parse.because(format!("`{}` does not exist", reference))
+            })?)
+        }
+        None => Ok(value),
+    }
+}
+
+fn root(spec: &Value, schema: &Value) -> Result<TypeScriptNode> {
+    node(spec, schema, true, 0)
+}
+
+/// Converts a schema into a node. `oneOf` and `anyOf` alternatives are merged as if each was an
+/// observed sample, and `allOf` parts combined into one object.
+fn node(spec: &Value, schema: &Value, root_node: bool, depth: usize) -> Result<TypeScriptNode> {
+    let new_node = |type_signature, is_array| {
+        TypeScriptNode::new(type_signature, false, false, is_array, root_node)
+    };
+    if depth > 64 {
+        anyhow::bail!(ErrorKind::Parse
+            .because("schemas nest too deeply, is there a reference cycle outside components?"));
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return match reference.strip_prefix(SCHEMA_PREFIX) {
+            Some(name) => {
+                Ok(new_node(TypeScriptPrimativeType::Object, false)
+                    .with_reference(schema_name(name)))
+            }
+            None => node(spec, resolve(spec, schema)?, root_node, depth + 1),
+        };
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(alternatives) = schema.get(keyword).and_then(Value::as_array) {
+            let mut merged: Option<TypeScriptNode> = None;
+            for alternative in alternatives {
+                // components are inlined, as the merged type can't name them all
+                let alternative = resolve(spec, alternative)?;
+                let alternative = node(spec, alternative, root_node, depth + 1)?;
+                merged = Some(match merged {
+                    Some(merged) => merged.merge(alternative),
+                    None => alternative,
+                });
+            }
+            if let Some(merged) = merged {
+                return Ok(merged);
+            }
+        }
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        let mut combined = Map::new();
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for part in parts {
+            let part = resolve(spec, part)?;
+            if let Some(part_properties) = part.get("properties").and_then(Value::as_object) {
+                properties.extend(part_properties.clone());
+            }
+            if let Some(part_required) = part.get("required").and_then(Value::as_array) {
+                required.extend(part_required.iter().cloned());
+            }
+        }
+        combined.insert("type".to_string(), Value::from("object"));
+        combined.insert("properties".to_string(), Value::Object(properties));
+        combined.insert("required".to_string(), Value::Array(required));
+        return node(spec, &Value::Object(combined), root_node, depth + 1);
+    }
+    let (type_name, nullable) = match schema.get("type") {
+        Some(Value::String(type_name)) => (Some(type_name.as_str()), false),
+        // OpenAPI 3.1 spells nullable types as `["string", "null"]`
+        Some(Value::Array(types)) => (
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|type_name| *type_name != "null"),
+            types.iter().any(|type_name| type_name == "null"),
+        ),
+        _ if schema.get("properties").is_some() => (Some("object"), false),
+        _ if schema.get("items").is_some() => (Some("array"), false),
+        _ => (None, false),
+    };
+    let nullable = nullable || schema.get("nullable") == Some(&Value::Bool(true));
+    let node = match type_name {
+        Some("string") => {
+            let mut node = new_node(TypeScriptPrimativeType::String, false);
+            node = node.with_string_format(match schema.get("format").and_then(Value::as_str) {
+                Some("date") => Some(StringFormat::Date),
+                Some("date-time") => Some(StringFormat::DateTime),
+                _ => None,
+            });
+            let literals: Option<Vec<String>> = schema
+                .get("enum")
+                .and_then(Value::as_array)
+                .and_then(|values| {
+                    values
+                        .iter()
+                        .map(|value| value.as_str().map(str::to_string))
+                        .collect()
+                });
+            match literals {
+                Some(literals) if !literals.is_empty() => node.with_literals(literals),
+                _ => node,
+            }
+        }
+        Some("integer") => new_node(TypeScriptPrimativeType::Number, false).with_integer(true),
+        Some("number") => new_node(TypeScriptPrimativeType::Number, false),
+        Some("boolean") => new_node(TypeScriptPrimativeType::Boolean, false),
+        Some("null") => new_node(TypeScriptPrimativeType::Null, false),
+        Some("array") => {
+            let elements = match schema.get("items") {
+                Some(items) => vec![node(spec, items, false, depth + 1)?],
+                None => Vec::new(),
+            };
+            new_node(TypeScriptPrimativeType::Array, true).with_elements(elements, None)
+        }
+        Some("object") | None => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let mut sub_items = Vec::new();
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (key, property) in properties {
+                    let mut sub_item =
+                        node(spec, property, false, depth + 1)?.with_name(key.as_str().into());
+                    if !required.contains(&key.as_str()) {
+                        sub_item = sub_item.with_optional();
+                    }
+                    sub_items.push(sub_item);
+                }
+            }
+            new_node(TypeScriptPrimativeType::Object, false).with_sub_items(sub_items)
+        }
+        Some(other) => {
+            anyhow::bail!(ErrorKind::Parse.because(format!("unknown schema type `{}`", other)))
+        }
+    };
+    Ok(if nullable { node.with_nullable() } else { node })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::type_trees;
+    use crate::render_types;
+    use crate::typescript_node::RenderOptions;
+
+    #[test]
+    fn declares_components_and_response_bodies() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.3",
+            "components": { "schemas": {
+                "User": {
+                    "type": "object",
+                    "required": ["id", "status"],
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "status": { "type": "string", "enum": ["active", "banned"] },
+                        "manager": { "$ref": "#/components/schemas/User" },
+                        "address": { "type": "object", "nullable": true, "properties": { "city": { "type": "string" } } }
+                    }
+                }
+            } },
+            "paths": { "/users": { "get": {
+                "operationId": "listUsers",
+                "responses": {
+                    "200": { "content": { "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/User" } } } } },
+                    "404": { "content": { "application/json": { "schema": { "type": "object", "required": ["error"], "properties": { "error": { "type": "string" } } } } } }
+                }
+            } } }
+        });
+        let rendered: Vec<String> = type_trees(&spec)
+            .unwrap()
+            .into_iter()
+            .map(|(name, mut tree)| {
+                tree.sort_fields();
+                tree.calculate_hash();
+                render_types(
+                    &tree,
+                    &RenderOptions {
+                        root_name: name,
+                        ..RenderOptions::default()
+                    },
+                )
+            })
+            .collect();
+        assert_eq!(
+            rendered.join("\n"),
+            "type User = {
+  address?: {
+    city?: string;
+  } | null;
+  id: number;
+  manager?: User;
+  status: \"active\" | \"banned\";
+};
+
+type ListUsersResponse = User[];
+
+type ListUsers404Response = {
+  error: string;
+};
+"
+        );
+    }
+}
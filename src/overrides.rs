@@ -0,0 +1,209 @@
+use std::str::FromStr;
+
+use crate::json_path::JsonPath;
+use crate::typescript_node::{TypeScriptNode, TypeScriptPrimativeType};
+
+/// A type forced on the values at a JSON path, whatever was inferred from them, as given to
+/// `--override '$.items[*].id=string'`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeOverride {
+    path: JsonPath,
+    type_override: OverrideType,
+}
+
+/// The types an override can force, which every target can declare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverrideKind {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Null,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OverrideType {
+    kind: OverrideKind,
+    /// Levels of arrays around the kind, 2 for `string[][]`
+    arrays: usize,
+    nullable: bool,
+}
+
+impl FromStr for TypeOverride {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (path, type_override) = value
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` isn't `PATH=TYPE`, e.g. `$.items[*].id=string`", value))?;
+        Ok(TypeOverride {
+            path: path.trim().parse()?,
+            type_override: type_override.parse()?,
+        })
+    }
+}
+
+impl TypeOverride {
+    /// Whether the override applies to the value at a JSON pointer
+    fn matches(&self, pointer: &str) -> bool {
+        self.path
+            .matches(&crate::json_path::pointer_segments(pointer))
+    }
+
+    /// The node standing for a value at the path
+    pub(crate) fn node(&self, root_node: bool) -> TypeScriptNode {
+        let OverrideType {
+            kind,
+            arrays,
+            nullable,
+        } = self.type_override;
+        let signature = match kind {
+            OverrideKind::String => TypeScriptPrimativeType::String,
+            OverrideKind::Number | OverrideKind::Integer => TypeScriptPrimativeType::Number,
+            OverrideKind::Boolean => TypeScriptPrimativeType::Boolean,
+            OverrideKind::Null => TypeScriptPrimativeType::Null,
+            OverrideKind::Unknown => TypeScriptPrimativeType::Unknown,
+        };
+        let mut node =
+            TypeScriptNode::new(signature, false, false, false, root_node && arrays == 0)
+                .with_integer(kind == OverrideKind::Integer);
+        for level in 1..=arrays {
+            node = TypeScriptNode::new(
+                TypeScriptPrimativeType::Array,
+                false,
+                false,
+                true,
+                root_node && level == arrays,
+            )
+            .with_elements(vec![node], None);
+        }
+        if nullable {
+            node = node.with_nullable();
+        }
+        node
+    }
+}
+
+/// The override applying to the value at a JSON pointer, the last one given when several do
+pub(crate) fn find<'a>(overrides: &'a [TypeOverride], pointer: &str) -> Option<&'a TypeOverride> {
+    overrides
+        .iter()
+        .rev()
+        .find(|type_override| type_override.matches(pointer))
+}
+
+impl FromStr for OverrideType {
+    type Err = String;
+
+    /// Parses `string`, `integer[]` or `boolean | null`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let members: Vec<&str> = value.split('|').map(str::trim).collect();
+        let nullable = members.len() > 1 && members.contains(&"null");
+        let others: Vec<&str> = members
+            .iter()
+            .copied()
+            .filter(|member| !(nullable && *member == "null"))
+            .collect();
+        let member = match others.as_slice() {
+            [member] => *member,
+            _ => {
+                return Err(format!(
+                "`{}` isn't a type --override can force, which is one type, optionally `| null`",
+                value
+            ))
+            }
+        };
+        let mut kind = member;
+        let mut arrays = 0;
+        while let Some(element) = kind.strip_suffix("[]") {
+            kind = element.trim_end();
+            arrays += 1;
+        }
+        let kind = match kind {
+            "string" => OverrideKind::String,
+            "number" => OverrideKind::Number,
+            "integer" => OverrideKind::Integer,
+            "boolean" => OverrideKind::Boolean,
+            "null" => OverrideKind::Null,
+            "unknown" => OverrideKind::Unknown,
+            _ => {
+                return Err(format!(
+                    "`{}` isn't a type --override can force, use string, number, integer, boolean, null or unknown, e.g. `string[]` or `number | null`",
+                    member
+                ))
+            }
+        };
+        Ok(OverrideType {
+            kind,
+            arrays,
+            nullable,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeOverride;
+    use crate::{walk_value_tree_with_options, InferOptions, TypeScriptNode};
+
+    #[test]
+    fn forces_types_at_paths() {
+        let val_tree = serde_json::json!({
+            "items": [{ "id": 1, "tags": [] }, { "id": 2, "tags": [], "note": null }],
+            "meta": { "id": 3, "counts": { "a": 1.5 } },
+        });
+        let overrides = [
+            "$.items[*].id=string",
+            "$.items[*].tags=string[]",
+            "$..note=string | null",
+            "$.meta['counts'].*=integer",
+        ];
+        let options = InferOptions {
+            detect_integers: true,
+            overrides: overrides
+                .iter()
+                .map(|type_override| type_override.parse().unwrap())
+                .collect(),
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {
+  items: DefaultType_0[];
+  meta: {
+    counts: {
+      a: number;
+    };
+    id: number;
+  };
+};
+
+type DefaultType_0 = {
+  id: string;
+  tags: string[];
+  note?: string | null;
+};
+"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_overrides() {
+        for invalid in [
+            "items.id=string",
+            "$.items[*=string",
+            "$.id=Date",
+            "$.id=string | number",
+            "$.id",
+        ] {
+            assert!(invalid.parse::<TypeOverride>().is_err(), "{}", invalid);
+        }
+        assert!("$.items[*].id=number[] | null"
+            .parse::<TypeOverride>()
+            .unwrap()
+            .matches("/items/3/id"));
+    }
+}
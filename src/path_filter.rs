@@ -0,0 +1,91 @@
+use serde_json::Value;
+
+use crate::json_path::JsonPath;
+
+/// The parts of a sample types are inferred from, as picked by `--include` and `--exclude`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PathFilter<'a> {
+    /// Only these values, and the objects and arrays leading to them, are kept when any are given
+    pub(crate) include: &'a [JsonPath],
+    /// These values are dropped, even when included
+    pub(crate) exclude: &'a [JsonPath],
+}
+
+impl PathFilter<'_> {
+    /// Drops the members and elements of a sample which aren't included or are excluded
+    pub(crate) fn apply(&self, sample: &mut Value) {
+        if !self.include.is_empty() || !self.exclude.is_empty() {
+            self.filter(sample, &mut Vec::new(), self.include.is_empty());
+        }
+    }
+
+    fn filter(&self, value: &mut Value, keys: &mut Vec<String>, included: bool) {
+        if included && !self.exclude.iter().any(|path| path.leads_to(keys)) {
+            return;
+        }
+        match value {
+            Value::Object(object) => {
+                object.retain(|key, member| self.keep(key.clone(), member, keys, included))
+            }
+            Value::Array(array) => {
+                let mut index = 0;
+                array.retain_mut(|element| {
+                    index += 1;
+                    self.keep((index - 1).to_string(), element, keys, included)
+                })
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether the member or element `key` of the value at `keys` is kept, filtering what is
+    /// nested in it when it is
+    fn keep(&self, key: String, value: &mut Value, keys: &mut Vec<String>, included: bool) -> bool {
+        keys.push(key);
+        let included = included || self.include.iter().any(|path| path.matches(keys));
+        let leads_to_included = (value.is_object() || value.is_array())
+            && self.include.iter().any(|path| path.leads_to(keys));
+        let keep =
+            (included || leads_to_included) && !self.exclude.iter().any(|path| path.matches(keys));
+        if keep {
+            self.filter(value, keys, included);
+        }
+        keys.pop();
+        keep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathFilter;
+    use crate::json_path::JsonPath;
+
+    #[test]
+    fn drops_excluded_and_keeps_included_paths() {
+        let paths = |paths: &[&str]| -> Vec<JsonPath> {
+            paths.iter().map(|path| path.parse().unwrap()).collect()
+        };
+        let sample = serde_json::json!({
+            "id": 1,
+            "debug": { "trace": "abc" },
+            "items": [{ "id": 1, "_internal": true, "name": "a" }],
+            "meta": { "page": 1 },
+        });
+        let filtered = |include: &[JsonPath], exclude: &[JsonPath]| {
+            let mut sample = sample.clone();
+            PathFilter { include, exclude }.apply(&mut sample);
+            sample
+        };
+        assert_eq!(
+            filtered(&[], &paths(&["$.debug", "$.._internal"])),
+            serde_json::json!({ "id": 1, "items": [{ "id": 1, "name": "a" }], "meta": { "page": 1 } })
+        );
+        assert_eq!(
+            filtered(
+                &paths(&["$.items[*].id", "$.meta"]),
+                &paths(&["$.meta.page"])
+            ),
+            serde_json::json!({ "items": [{ "id": 1 }], "meta": {} })
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::errors::ErrorKind;
+use crate::{Context, Result};
+
+/// Parses a polling interval such as `500ms`, `60s`, `5m` or `1h`. Bare numbers are seconds.
+pub(crate) fn parse_interval(interval: &str) -> Result<Duration> {
+    let interval = interval.trim();
+    let split = interval
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(interval.len());
+    let (amount, unit) = interval.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .with_context(|| ErrorKind::Usage.because(format!("invalid interval `{}`", interval)))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "" | "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount * 60)),
+        "h" => Ok(Duration::from_secs(amount * 60 * 60)),
+        _ => Err(ErrorKind::Usage
+            .because(format!(
+                "invalid interval unit `{}`, expected one of ms, s, m or h",
+                unit
+            ))
+            .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_interval;
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(parse_interval("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_interval("60s").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("5m").unwrap(), Duration::from_secs(300));
+        assert!(parse_interval("1d").is_err());
+        assert!(parse_interval("s").is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::errors::ErrorKind;
+use crate::{Context, Result};
+
+/// Pipes `contents` through `command`, run by the shell, and returns what it prints. `{file}` in
+/// the command is replaced by the path the output is written to, for formatters which pick a
+/// parser by extension, e.g. `prettier --stdin-filepath {file}`.
+pub(crate) fn format_with(command: &str, file: &str, contents: &str) -> Result<String> {
+    let command = command.replace("{file}", file);
+    let mut child = shell(&command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| ErrorKind::Render.because(format!("could not run `{}`", command)))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .context(ErrorKind::Render.because("could not write to the format command"))?;
+    // written from another thread so a formatter printing as it reads can't block on a full pipe
+    let input = contents.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .with_context(|| ErrorKind::Render.because(format!("could not run `{}`", command)))?;
+    let written = writer
+        .join()
+        .map_err(|_| ErrorKind::Render.because(format!("could not write to `{}`", command)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(ErrorKind::Render.because(format!(
+            "`{}` failed with {}{}",
+            command,
+            output.status,
+            if stderr.trim().is_empty() {
+                String::new()
+            } else {
+                format!(": {}", stderr.trim())
+            }
+        )));
+    }
+    // a command which doesn't read its input closes the pipe early
+    match written {
+        Err(error) if error.kind() != std::io::ErrorKind::BrokenPipe => {
+            return Err(error).with_context(|| {
+                ErrorKind::Render.because(format!("could not write to `{}`", command))
+            });
+        }
+        _ => (),
+    }
+    String::from_utf8(output.stdout)
+        .with_context(|| ErrorKind::Render.because(format!("`{}` printed invalid UTF-8", command)))
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::format_with;
+
+    #[test]
+    fn pipes_output_through_the_command() {
+        assert_eq!(
+            format_with("tr a-z A-Z", "types.d.ts", "type a = {};\n").unwrap(),
+            "TYPE A = {};\n"
+        );
+        assert_eq!(
+            format_with("echo {file}", "types.d.ts", "").unwrap(),
+            "types.d.ts\n"
+        );
+        assert!(format_with("cat >/dev/null; exit 3", "types.d.ts", "type A = {};\n").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use std::io::BufRead;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// A progress bar on stderr for `--progress`, counting the bytes read and the samples walked.
+/// It's only drawn on a terminal.
+pub(crate) struct Progress {
+    bar: ProgressBar,
+    samples: AtomicU64,
+}
+
+impl Progress {
+    /// A bar filling up as `total_bytes` are read, or a spinner when the size of the inputs
+    /// isn't known up front, as with stdin and URLs
+    pub(crate) fn new(total_bytes: Option<u64>) -> Self {
+        let (bar, template) = match total_bytes {
+            Some(total_bytes) => (
+                ProgressBar::with_draw_target(Some(total_bytes), ProgressDrawTarget::stderr()),
+                "{spinner} [{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({bytes_per_sec}) {msg}",
+            ),
+            None => (
+                ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr()),
+                "{spinner} [{elapsed_precise}] {bytes} ({bytes_per_sec}) {msg}",
+            ),
+        };
+        if let Ok(style) = ProgressStyle::with_template(template) {
+            bar.set_style(style.progress_chars("=> "));
+        }
+        bar.enable_steady_tick(std::time::Duration::from_millis(100));
+        Progress {
+            bar,
+            samples: AtomicU64::new(0),
+        }
+    }
+
+    /// `reader`, advancing the bar by what is read from it
+    pub(crate) fn wrap(&self, reader: Box<dyn BufRead>) -> Box<dyn BufRead> {
+        Box::new(self.bar.wrap_read(reader))
+    }
+
+    /// Counts a sample walked
+    pub(crate) fn sampled(&self) {
+        let samples = self.samples.fetch_add(1, Ordering::Relaxed) + 1;
+        self.bar.set_message(format!(
+            "{} sample{}",
+            samples,
+            if samples == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// Clears the bar, before the output is written
+    pub(crate) fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Read};
+
+    use super::Progress;
+
+    #[test]
+    fn counts_bytes_and_samples() {
+        let progress = Progress::new(Some(11));
+        let reader: Box<dyn BufRead> = Box::new(&b"{ \"id\": 1 }"[..]);
+        let mut content = String::new();
+        progress.wrap(reader).read_to_string(&mut content).unwrap();
+        progress.sampled();
+        progress.sampled();
+        assert_eq!(progress.bar.position(), 11);
+        assert_eq!(progress.bar.message(), "2 samples");
+        progress.finish();
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::declarations::{numbered, Declarations};
+use crate::naming::segment;
+use crate::rename::to_snake_case;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const INDENT: &str = "  ";
+
+/// The type of values proto3 has no type for: `null` on its own, arrays holding `null`, mixed
+/// types or other arrays, unions, and values below `--max-depth`
+const VALUE: &str = "google.protobuf.Value";
+
+/// The type of objects without any fields
+const STRUCT: &str = "google.protobuf.Struct";
+
+/// Renders the tree as proto3 `message` definitions, a message per object type, named as the
+/// Python classes are. Fields are numbered in the order of their JSON keys, so adding a key only
+/// renumbers those sorting after it, and named in snake_case with a `json_name` wherever the
+/// default JSON name wouldn't be the key.
+pub(crate) fn to_proto_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let mut renderer = Renderer {
+        messages: Declarations::new(node, options, &[]),
+        uses_struct: false,
+    };
+    let mut root_comment = None;
+    if node.type_signature() == &TypeScriptPrimativeType::Object && !node.sub_items().is_empty() {
+        renderer.message(node, options.root_name.clone(), true);
+    } else {
+        // only objects can be messages, so the type of the root is noted instead
+        let (label, field_type) = renderer.field_type(node, &options.root_name);
+        root_comment = Some(format!(
+            "// {}: {}{}\n",
+            options.root_name, label, field_type
+        ));
+    }
+    let mut output = String::from("syntax = \"proto3\";\n");
+    if renderer.uses_struct {
+        output.push_str("\nimport \"google/protobuf/struct.proto\";\n");
+    }
+    for declaration in root_comment.iter().chain(renderer.messages.iter()) {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    output
+}
+
+/// `user-id` -> `user_id`, as names are limited to ASCII letters, digits and `_`, starting with
+/// a letter
+fn to_proto_name(name: &str, prefix: &str) -> String {
+    let mut proto_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !proto_name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        proto_name.insert_str(0, prefix);
+    }
+    proto_name
+}
+
+/// The JSON name protoc gives a field, `user_id` -> `userId`
+fn default_json_name(field_name: &str) -> String {
+    let mut json_name = String::new();
+    let mut capitalize = false;
+    for c in field_name.chars() {
+        match c {
+            '_' => capitalize = true,
+            _ if capitalize => {
+                json_name.extend(c.to_uppercase());
+                capitalize = false;
+            }
+            _ => json_name.push(c),
+        }
+    }
+    json_name
+}
+
+struct Renderer {
+    messages: Declarations,
+    uses_struct: bool,
+}
+
+impl Renderer {
+    /// The label and type of a field, `hint` naming the message of an inline object
+    fn field_type(&mut self, node: &TypeScriptNode, hint: &str) -> (&'static str, String) {
+        if node.type_signature() != &TypeScriptPrimativeType::Array {
+            let field_type = self.value_type(node, hint);
+            let label = match node.type_signature() {
+                // messages, `google.protobuf.Value` among them, always track presence
+                TypeScriptPrimativeType::Object
+                | TypeScriptPrimativeType::Null
+                | TypeScriptPrimativeType::Unknown
+                | TypeScriptPrimativeType::Union => "",
+                _ if node.is_nullable() || node.is_optional() => "optional ",
+                _ => "",
+            };
+            return (label, field_type);
+        }
+        // the messages of elements mixed with others aren't emitted, as they end up as values
+        let element_type = match node.elements() {
+            [element] => match element.type_signature() {
+                TypeScriptPrimativeType::Array | TypeScriptPrimativeType::Null => VALUE.to_string(),
+                _ if element.is_nullable() => VALUE.to_string(),
+                _ => self.value_type(element, &format!("{}Item", hint)),
+            },
+            _ => VALUE.to_string(),
+        };
+        if element_type == VALUE {
+            self.uses_struct = true;
+        }
+        ("repeated ", element_type)
+    }
+
+    /// The type of a value which isn't an array
+    fn value_type(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        match node.type_signature() {
+            TypeScriptPrimativeType::String => "string".to_string(),
+            TypeScriptPrimativeType::Number if node.is_integer() => "int64".to_string(),
+            TypeScriptPrimativeType::Number => "double".to_string(),
+            TypeScriptPrimativeType::Boolean => "bool".to_string(),
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                self.messages.referenced_name(node).to_string()
+            }
+            TypeScriptPrimativeType::Object if node.sub_items().is_empty() => {
+                self.uses_struct = true;
+                STRUCT.to_string()
+            }
+            TypeScriptPrimativeType::Object => match self.messages.emitted_name(node) {
+                Some(name) => name.to_string(),
+                None => self.message(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array
+            | TypeScriptPrimativeType::Null
+            | TypeScriptPrimativeType::Unknown
+            | TypeScriptPrimativeType::Union => {
+                self.uses_struct = true;
+                VALUE.to_string()
+            }
+        }
+    }
+
+    /// Emits the message of an object ahead of the messages it refers to, returning its name.
+    /// The root is named `hint`, common types by their type name and other objects after
+    /// `hint`.
+    fn message(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self
+            .messages
+            .name(node, to_proto_name(&hint, "Message"), root);
+        let index = self.messages.reserve();
+        let mut field_names = HashSet::new();
+        let mut body = String::new();
+        let fields = node
+            .sub_items()
+            .iter()
+            .sorted_by_key(|sub_item| sub_item.name().unwrap_or_default());
+        for (number, sub_item) in (1..).zip(fields) {
+            let key = sub_item.name().unwrap_or_default();
+            let base_name = to_proto_name(&to_snake_case(key), "field_");
+            let field_name = numbered(&base_name, "_", &field_names);
+            field_names.insert(field_name.clone());
+            let (label, field_type) =
+                self.field_type(sub_item, &format!("{}{}", name, segment(key)));
+            let json_name = if default_json_name(&field_name) == key {
+                String::new()
+            } else {
+                format!(" [json_name = {}]", serde_json::to_string(key).unwrap())
+            };
+            body.push_str(&format!(
+                "{}{}{} {} = {}{};\n",
+                INDENT, label, field_type, field_name, number, json_name
+            ));
+        }
+        self.messages
+            .fill(index, format!("message {} {{\n{}}}\n", name, body));
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_proto_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_proto_messages() {
+        let val_tree = serde_json::from_str(
+            r#"{ "userId": 1, "payments": [{ "amount": 2.5, "note": null }, { "amount": 1, "note": "a" }], "grid": [[1]], "Display-Name": "a", "meta": {} }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            to_proto_string(&result, &RenderOptions::default()),
+            "syntax = \"proto3\";
+
+import \"google/protobuf/struct.proto\";
+
+message DefaultType {
+  string display_name = 1 [json_name = \"Display-Name\"];
+  repeated google.protobuf.Value grid = 2;
+  google.protobuf.Struct meta = 3;
+  repeated DefaultType_0 payments = 4;
+  int64 user_id = 5;
+}
+
+message DefaultType_0 {
+  double amount = 1;
+  optional string note = 2;
+}
+"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_messages_of_mixed_elements() {
+        let val_tree = serde_json::from_str(
+            r#"{ "items": [{ "a": 1, "c": true }, { "b": "x" }], "lines": [{ "qty": 1 }] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &InferOptions::default());
+        result.calculate_hash();
+        assert_eq!(
+            to_proto_string(&result, &RenderOptions::default()),
+            "syntax = \"proto3\";
+
+import \"google/protobuf/struct.proto\";
+
+message DefaultType {
+  repeated google.protobuf.Value items = 1;
+  repeated DefaultTypeLinesItem lines = 2;
+}
+
+message DefaultTypeLinesItem {
+  double qty = 1;
+}
+"
+        );
+    }
+}
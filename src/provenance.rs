@@ -0,0 +1,193 @@
+#[cfg(feature = "cli")]
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "cli")]
+use serde_json::{json, Map, Value};
+
+#[cfg(feature = "cli")]
+use crate::schema::child_path;
+#[cfg(feature = "cli")]
+use crate::typescript_node::{Fingerprint, RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+/// Observations kept per node or field, enough to jump to an example without the provenance
+/// growing with the size of the input
+pub(crate) const MAX_SOURCES: usize = 64;
+
+/// Where a value was observed, as a JSON pointer (RFC 6901) into an input file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Source {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<Arc<str>>,
+    pub(crate) pointer: String,
+}
+
+#[cfg(feature = "cli")]
+impl Source {
+    fn to_json(&self) -> Value {
+        let mut source = Map::new();
+        if let Some(file) = &self.file {
+            source.insert("file".to_string(), json!(file.as_ref()));
+        }
+        source.insert("pointer".to_string(), json!(self.pointer));
+        Value::Object(source)
+    }
+}
+
+/// Appends the sources not already present, keeping at most `MAX_SOURCES`
+pub(crate) fn add_sources(target: &mut Vec<Source>, sources: impl IntoIterator<Item = Source>) {
+    for source in sources {
+        if target.len() >= MAX_SOURCES {
+            break;
+        }
+        if !target.contains(&source) {
+            target.push(source);
+        }
+    }
+}
+
+/// Pointer to the member `key` of the value at `pointer`
+pub(crate) fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(feature = "cli")]
+/// `<output>.provenance.json` next to the generated file
+pub(crate) fn provenance_path(output_file: &str) -> std::path::PathBuf {
+    std::path::Path::new(output_file).with_extension("provenance.json")
+}
+
+#[cfg(feature = "cli")]
+#[derive(Default)]
+struct TypeProvenance {
+    sources: Vec<Source>,
+    fields: BTreeMap<String, Vec<Source>>,
+}
+
+#[cfg(feature = "cli")]
+/// Maps every emitted type, and every field path within it, to where it was observed in the
+/// input. The tree must already be hashed when common types are squashed, so the type names
+/// match the rendered output.
+pub(crate) fn to_provenance_json(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let type_names = node.type_names(options);
+    let mut types = BTreeMap::new();
+    visit_type(node, &options.root_name, &type_names, &mut types);
+    let types: Map<String, Value> = types
+        .into_iter()
+        .map(|(type_name, provenance)| {
+            let fields: Map<String, Value> = provenance
+                .fields
+                .into_iter()
+                .map(|(path, sources)| (path, sources_to_json(&sources)))
+                .collect();
+            (
+                type_name,
+                json!({ "sources": sources_to_json(&provenance.sources), "fields": fields }),
+            )
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "types": types })).unwrap()
+}
+
+#[cfg(feature = "cli")]
+fn sources_to_json(sources: &[Source]) -> Value {
+    Value::Array(sources.iter().map(Source::to_json).collect())
+}
+
+#[cfg(feature = "cli")]
+fn visit_type(
+    node: &TypeScriptNode,
+    type_name: &str,
+    type_names: &HashMap<Fingerprint, String>,
+    types: &mut BTreeMap<String, TypeProvenance>,
+) {
+    let provenance = types.entry(type_name.to_string()).or_default();
+    add_sources(&mut provenance.sources, node.sources().iter().cloned());
+    visit_members(node, type_name, "", type_names, types);
+}
+
+#[cfg(feature = "cli")]
+/// Records the members of `node`, found at `path` within `type_name`, descending into inline
+/// objects and starting a new type at every named one
+fn visit_members(
+    node: &TypeScriptNode,
+    type_name: &str,
+    path: &str,
+    type_names: &HashMap<Fingerprint, String>,
+    types: &mut BTreeMap<String, TypeProvenance>,
+) {
+    for sub_item in node.sub_items() {
+        // the members of a union are alternatives for the value at `path`
+        if node.type_signature() == &TypeScriptPrimativeType::Union {
+            match type_names.get(&sub_item.hash()) {
+                Some(sub_type_name)
+                    if sub_item.type_signature() == &TypeScriptPrimativeType::Object =>
+                {
+                    visit_type(sub_item, sub_type_name, type_names, types)
+                }
+                _ => visit_members(sub_item, type_name, path, type_names, types),
+            }
+            continue;
+        }
+        let sub_path = match node.type_signature() {
+            TypeScriptPrimativeType::Object => {
+                child_path(path, sub_item.name().unwrap_or_default())
+            }
+            _ => format!("{}[*]", path),
+        };
+        let fields = &mut types.get_mut(type_name).unwrap().fields;
+        add_sources(
+            fields
+                .entry(sub_path.trim_start_matches('.').to_string())
+                .or_default(),
+            sub_item.sources().iter().cloned(),
+        );
+        match type_names.get(&sub_item.hash()) {
+            Some(sub_type_name)
+                if sub_item.type_signature() == &TypeScriptPrimativeType::Object =>
+            {
+                visit_type(sub_item, sub_type_name, type_names, types)
+            }
+            _ => visit_members(sub_item, type_name, &sub_path, type_names, types),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use super::to_provenance_json;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn maps_types_to_pointers() {
+        let val_tree =
+            serde_json::from_str(r#"{ "from": { "id": 1 }, "to": { "id": 2 }, "a/b": [true] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.attribute_sources_to(&"in.json".into());
+        result.calculate_hash();
+        let provenance: serde_json::Value =
+            serde_json::from_str(&to_provenance_json(&result, &RenderOptions::default())).unwrap();
+        assert_eq!(
+            provenance["types"]["DefaultType"]["fields"]["[\"a/b\"][*]"],
+            serde_json::json!([{ "file": "in.json", "pointer": "/a~1b/0" }])
+        );
+        assert_eq!(
+            provenance["types"]["DefaultType_0"],
+            serde_json::json!({
+                "sources": [
+                    { "file": "in.json", "pointer": "/from" },
+                    { "file": "in.json", "pointer": "/to" }
+                ],
+                "fields": {
+                    "id": [
+                        { "file": "in.json", "pointer": "/from/id" },
+                        { "file": "in.json", "pointer": "/to/id" }
+                    ]
+                }
+            })
+        );
+    }
+}
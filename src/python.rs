@@ -0,0 +1,326 @@
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use crate::declarations::Declarations;
+use crate::naming::segment;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const INDENT: &str = "    ";
+
+/// How the Python classes are declared, as given to `--python-style`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum PythonStyle {
+    /// `class Order(TypedDict)`, describing the parsed JSON as it is
+    #[default]
+    TypedDict,
+    /// `@dataclass class Order`, for payloads converted into objects
+    Dataclass,
+}
+
+/// Renders the tree as Python classes, a class per object type as Python has no inline object
+/// types. Common types take their usual names; other objects are named after the path leading
+/// to them, `{root_name}Order` for the object under `order`.
+pub(crate) fn to_python_string(
+    node: &TypeScriptNode,
+    options: &RenderOptions,
+    style: PythonStyle,
+) -> String {
+    let mut renderer = Renderer {
+        classes: Declarations::new(node, options, &[]),
+        style,
+        imports: BTreeSet::new(),
+    };
+    if node.type_signature() == &TypeScriptPrimativeType::Object {
+        renderer.class(node, options.root_name.clone(), true);
+    } else {
+        let annotation = renderer.annotation(node, &options.root_name);
+        renderer
+            .classes
+            .push(format!("{} = {}\n", options.root_name, annotation));
+    }
+    let mut output = String::new();
+    if style == PythonStyle::Dataclass {
+        output.push_str("from dataclasses import dataclass\n");
+    }
+    if !renderer.imports.is_empty() {
+        output.push_str(&format!(
+            "from typing import {}\n",
+            renderer.imports.iter().join(", ")
+        ));
+    }
+    for class in renderer.classes.iter() {
+        if !output.is_empty() {
+            output.push_str("\n\n");
+        }
+        output.push_str(class);
+    }
+    output
+}
+
+/// `user id` -> `user_id`, for keys which can't be attribute names
+fn to_python_identifier(key: &str) -> String {
+    let mut identifier: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    if !identifier.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+fn is_python_identifier(key: &str) -> bool {
+    to_python_identifier(key) == key
+}
+
+struct Renderer {
+    classes: Declarations,
+    style: PythonStyle,
+    /// Names imported from `typing`
+    imports: BTreeSet<&'static str>,
+}
+
+impl Renderer {
+    /// The annotation of a value, `hint` naming the class of an inline object
+    fn annotation(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        let annotation = match node.type_signature() {
+            TypeScriptPrimativeType::String => match node.literals() {
+                Some(literals) => {
+                    self.imports.insert("Literal");
+                    format!(
+                        "Literal[{}]",
+                        literals
+                            .iter()
+                            .map(|literal| serde_json::to_string(literal).unwrap())
+                            .join(", ")
+                    )
+                }
+                None => "str".to_string(),
+            },
+            TypeScriptPrimativeType::Number if node.is_integer() => "int".to_string(),
+            TypeScriptPrimativeType::Number => "float".to_string(),
+            TypeScriptPrimativeType::Boolean => "bool".to_string(),
+            TypeScriptPrimativeType::Null => "None".to_string(),
+            TypeScriptPrimativeType::Unknown => {
+                self.imports.insert("Any");
+                "Any".to_string()
+            }
+            TypeScriptPrimativeType::Union => {
+                let members: Vec<String> = node
+                    .sub_items()
+                    .iter()
+                    .map(|member| self.annotation(member, hint))
+                    .unique()
+                    .collect();
+                self.imports.insert("Union");
+                format!("Union[{}]", members.join(", "))
+            }
+            // the class isn't defined yet where it refers to itself
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                format!("\"{}\"", self.classes.referenced_name(node))
+            }
+            TypeScriptPrimativeType::Object => match self.classes.emitted_name(node) {
+                Some(name) => name.to_string(),
+                None => self.class(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array => {
+                let element_hint = format!("{}Item", hint);
+                let elements: Vec<String> = node
+                    .elements()
+                    .iter()
+                    .map(|element| self.annotation(element, &element_hint))
+                    .unique()
+                    .sorted()
+                    .collect();
+                match elements.as_slice() {
+                    [] => {
+                        self.imports.insert("Any");
+                        "list[Any]".to_string()
+                    }
+                    [element] => format!("list[{}]", element),
+                    _ => {
+                        self.imports.insert("Union");
+                        format!("list[Union[{}]]", elements.join(", "))
+                    }
+                }
+            }
+        };
+        if node.is_nullable() && annotation != "None" {
+            self.imports.insert("Optional");
+            format!("Optional[{}]", annotation)
+        } else {
+            annotation
+        }
+    }
+
+    /// Emits the class of an object after the classes it refers to, returning its name. The
+    /// root is named `hint`, common types by their type name and other objects after `hint`.
+    fn class(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self.classes.name(node, hint, root);
+        let mut fields = Vec::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let annotation = self.annotation(sub_item, &format!("{}{}", name, segment(key)));
+            fields.push((key, annotation, sub_item.is_optional()));
+        }
+        let class = match self.style {
+            PythonStyle::TypedDict => self.typed_dict(&name, &fields),
+            PythonStyle::Dataclass => self.dataclass(&name, &fields),
+        };
+        self.classes.push(class);
+        name
+    }
+
+    fn typed_dict(&mut self, name: &str, fields: &[(&str, String, bool)]) -> String {
+        self.imports.insert("TypedDict");
+        let fields: Vec<(&str, String)> = fields
+            .iter()
+            .map(|(key, annotation, optional)| {
+                if *optional {
+                    self.imports.insert("NotRequired");
+                    (*key, format!("NotRequired[{}]", annotation))
+                } else {
+                    (*key, annotation.clone())
+                }
+            })
+            .collect();
+        if fields.iter().all(|(key, _)| is_python_identifier(key)) {
+            let body: String = fields
+                .iter()
+                .map(|(key, annotation)| format!("{}{}: {}\n", INDENT, key, annotation))
+                .collect();
+            format!("class {}(TypedDict):\n{}", name, or_pass(body))
+        } else {
+            // keys which aren't identifiers need the functional syntax
+            let body: String = fields
+                .iter()
+                .map(|(key, annotation)| {
+                    format!(
+                        "{}{}: {},\n",
+                        INDENT,
+                        serde_json::to_string(key).unwrap(),
+                        annotation
+                    )
+                })
+                .collect();
+            format!("{} = TypedDict(\"{}\", {{\n{}}})\n", name, name, body)
+        }
+    }
+
+    fn dataclass(&mut self, name: &str, fields: &[(&str, String, bool)]) -> String {
+        // fields with defaults have to come after those without
+        let body: String = fields
+            .iter()
+            .sorted_by_key(|(_, _, optional)| *optional)
+            .map(|(key, annotation, optional)| {
+                let attribute = to_python_identifier(key);
+                if !optional {
+                    format!("{}{}: {}\n", INDENT, attribute, annotation)
+                } else if annotation.starts_with("Optional[") {
+                    format!("{}{}: {} = None\n", INDENT, attribute, annotation)
+                } else {
+                    self.imports.insert("Optional");
+                    format!("{}{}: Optional[{}] = None\n", INDENT, attribute, annotation)
+                }
+            })
+            .collect();
+        format!("@dataclass\nclass {}:\n{}", name, or_pass(body))
+    }
+}
+
+fn or_pass(body: String) -> String {
+    if body.is_empty() {
+        format!("{}pass\n", INDENT)
+    } else {
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_python_string, PythonStyle};
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_python_classes() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "note": null }, { "amount": 2.5, "note": "a", "refund": { "id": 1 } }], "order": { "class": "a", "woah lol": [] } }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        assert_eq!(
+            to_python_string(&result, &RenderOptions::default(), PythonStyle::TypedDict),
+            "from typing import Any, NotRequired, Optional, TypedDict
+
+
+DefaultTypeOrder = TypedDict(\"DefaultTypeOrder\", {
+    \"class\": str,
+    \"woah lol\": list[Any],
+})
+
+
+class DefaultType_0Refund(TypedDict):
+    id: int
+
+
+class DefaultType_0(TypedDict):
+    amount: float
+    note: Optional[str]
+    refund: NotRequired[DefaultType_0Refund]
+
+
+class DefaultType(TypedDict):
+    order: DefaultTypeOrder
+    payments: list[DefaultType_0]
+"
+        );
+        assert_eq!(
+            to_python_string(&result, &RenderOptions::default(), PythonStyle::Dataclass),
+            "from dataclasses import dataclass
+from typing import Any, Optional
+
+
+@dataclass
+class DefaultTypeOrder:
+    class_: str
+    woah_lol: list[Any]
+
+
+@dataclass
+class DefaultType_0Refund:
+    id: int
+
+
+@dataclass
+class DefaultType_0:
+    amount: float
+    note: Optional[str]
+    refund: Optional[DefaultType_0Refund] = None
+
+
+@dataclass
+class DefaultType:
+    order: DefaultTypeOrder
+    payments: list[DefaultType_0]
+"
+        );
+    }
+}
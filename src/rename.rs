@@ -0,0 +1,135 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "cli")]
+use itertools::Itertools;
+
+/// A naming convention for properties, as given to `--rename`
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum KeyCase {
+    #[value(name = "camelCase")]
+    CamelCase,
+    #[value(name = "snake_case")]
+    SnakeCase,
+}
+
+#[cfg(feature = "cli")]
+impl KeyCase {
+    pub(crate) fn apply(&self, key: &str) -> String {
+        match self {
+            KeyCase::CamelCase => to_camel_case(key),
+            KeyCase::SnakeCase => to_snake_case(key),
+        }
+    }
+}
+
+/// How the wire names of renamed properties are kept, as given to `--original-names`
+#[cfg(feature = "cli")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OriginalNames {
+    /// A `/** @originalName user_id */` comment on every renamed property
+    Jsdoc,
+    /// The wire format as `RawDefaultType`, with converters between it and the renamed types
+    Converters,
+}
+
+/// Splits a key into its words on separators and case boundaries:
+/// `user_id` -> `user id`, `HTTPStatus` -> `HTTP Status`, `createdAt` -> `created At`
+pub(crate) fn words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let boundary = index > 0 && !current.is_empty() && {
+            let previous = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|n| n.is_lowercase());
+            c.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_is_lower))
+        };
+        if boundary {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// `user_id` -> `userId`. Keys without any word characters are left alone.
+pub(crate) fn to_camel_case(key: &str) -> String {
+    let words = words(key);
+    if words.is_empty() {
+        return key.to_string();
+    }
+    let mut camel = words[0].to_lowercase();
+    for word in &words[1..] {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    camel
+}
+
+/// `createdAt` -> `created_at`. Keys without any word characters are left alone.
+#[cfg(feature = "cli")]
+pub(crate) fn to_snake_case(key: &str) -> String {
+    let words = words(key);
+    if words.is_empty() {
+        return key.to_string();
+    }
+    words.iter().map(|word| word.to_lowercase()).join("_")
+}
+
+/// `payment-intent` -> `PaymentIntent`. Keys without any word characters are left alone.
+pub(crate) fn to_pascal_case(key: &str) -> String {
+    let camel = to_camel_case(key);
+    let mut chars = camel.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => camel,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "cli")]
+    use super::to_snake_case;
+    use super::{to_camel_case, to_pascal_case};
+
+    #[test]
+    fn camel_cases_keys() {
+        assert_eq!(to_camel_case("user_id"), "userId");
+        assert_eq!(to_camel_case("created-at"), "createdAt");
+        assert_eq!(to_camel_case("alreadyCamel"), "alreadyCamel");
+        assert_eq!(to_camel_case("HTTPStatus"), "httpStatus");
+        assert_eq!(to_camel_case("woah lol"), "woahLol");
+        assert_eq!(to_camel_case("__"), "__");
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn snake_cases_keys() {
+        assert_eq!(to_snake_case("userId"), "user_id");
+        assert_eq!(to_snake_case("HTTPStatus"), "http_status");
+        assert_eq!(to_snake_case("created-at"), "created_at");
+        assert_eq!(to_snake_case("__"), "__");
+    }
+
+    #[test]
+    fn pascal_cases_file_names() {
+        assert_eq!(to_pascal_case("payment-intent"), "PaymentIntent");
+        assert_eq!(to_pascal_case("order_v2"), "OrderV2");
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// The warnings logged so far, whatever the log level, for `--report json`
+static WARNINGS: Mutex<Vec<Warning>> = Mutex::new(Vec::new());
+
+/// A warning logged while generating, with the fields logged alongside its message
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Warning {
+    pub(crate) message: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) fields: BTreeMap<String, String>,
+}
+
+impl Visit for Warning {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// Keeps every warning and error logged, to be reported by [`take_warnings`]
+pub(crate) struct WarningLayer;
+
+impl<S: Subscriber> Layer<S> for WarningLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        // levels compare by verbosity, errors being the least verbose
+        if *event.metadata().level() > Level::WARN {
+            return;
+        }
+        let mut warning = Warning::default();
+        event.record(&mut warning);
+        WARNINGS.lock().unwrap().push(warning);
+    }
+}
+
+/// The warnings logged since the last call
+pub(crate) fn take_warnings() -> Vec<Warning> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::{event, Level};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::{take_warnings, WarningLayer};
+
+    #[test]
+    fn keeps_warnings() {
+        let subscriber = tracing_subscriber::registry().with(WarningLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            event!(Level::INFO, "reading");
+            event!(Level::WARN, field = "id", "conflicting types");
+        });
+        let warnings = take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "conflicting types");
+        assert_eq!(warnings[0].fields["field"], "id");
+    }
+}
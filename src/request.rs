@@ -0,0 +1,110 @@
+use anyhow::anyhow;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    infer, render_typescript, DeclarationStyle, FallbackType, InferOptions, KeyOrder,
+    NamingStrategy, RenderOptions,
+};
+use crate::{Context, Result};
+
+/// The options of a request for types from JavaScript, through `inferTypes` or the language
+/// server, all of them optional. Enumerations are spelled as on the command line,
+/// `naming: "path-based"`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct RequestOptions {
+    root_name: Option<String>,
+    /// `type` or `interface`
+    declaration_style: Option<String>,
+    naming: Option<String>,
+    key_order: Option<String>,
+    max_depth: Option<usize>,
+    fallback_type: Option<String>,
+    empty_array_type: Option<String>,
+    export: bool,
+    readonly: bool,
+    examples: bool,
+}
+
+/// Renders the TypeScript declarations of a JSON document. An array of samples is typed as an
+/// array, as on the command line.
+pub(crate) fn infer_types(json: &str, options: RequestOptions) -> Result<String> {
+    let value: Value = serde_json::from_str(json).context("could not parse json")?;
+    let infer_options = InferOptions {
+        key_order: value_enum::<KeyOrder>(options.key_order.as_deref(), "keyOrder")?
+            .unwrap_or_default(),
+        max_depth: options.max_depth,
+        ..InferOptions::default()
+    };
+    let root_name = options
+        .root_name
+        .unwrap_or_else(|| RenderOptions::default().root_name);
+    if !crate::is_type_name(&root_name) {
+        anyhow::bail!("`{}` can't name a type", root_name);
+    }
+    let render_options = RenderOptions {
+        root_name,
+        declaration_style: match options.declaration_style.as_deref() {
+            None | Some("type") => DeclarationStyle::TypeAlias,
+            Some("interface") => DeclarationStyle::Interface,
+            Some(other) => {
+                anyhow::bail!("declarationStyle is `type` or `interface`, not `{}`", other)
+            }
+        },
+        naming: value_enum::<NamingStrategy>(options.naming.as_deref(), "naming")?
+            .unwrap_or_default(),
+        fallback_type: value_enum::<FallbackType>(
+            options.fallback_type.as_deref(),
+            "fallbackType",
+        )?
+        .unwrap_or_default(),
+        empty_array_type: options.empty_array_type,
+        export: options.export,
+        readonly: options.readonly,
+        examples: options.examples,
+        ..RenderOptions::default()
+    };
+    Ok(render_typescript(
+        &infer(&value, &infer_options),
+        &render_options,
+    ))
+}
+
+/// Reads an option spelled like the command line flag taking it
+fn value_enum<T: DeserializeOwned>(value: Option<&str>, option: &str) -> Result<Option<T>> {
+    value
+        .map(|value| {
+            serde_json::from_value(Value::String(value.to_string()))
+                .map_err(|_| anyhow!("invalid {} `{}`", option, value))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infer_types, RequestOptions};
+
+    #[test]
+    fn infers_types_with_js_options() {
+        let options: RequestOptions = serde_json::from_str(
+            r#"{ "rootName": "Order", "declarationStyle": "interface", "export": true, "maxDepth": 1 }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            infer_types(r#"{ "id": 1, "lines": [{ "sku": "a" }] }"#, options).unwrap(),
+            "export interface Order {\n  id: number;\n  lines: unknown;\n}\n"
+        );
+        let options: RequestOptions = serde_json::from_str(r#"{ "naming": "by-path" }"#).unwrap();
+        assert!(infer_types("{}", options).is_err());
+        let options: RequestOptions = serde_json::from_str(
+            r#"{ "naming": "path-based", "keyOrder": "input", "fallbackType": "json-value" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            infer_types(r#"{ "b": [], "a": 1 }"#, options).unwrap(),
+            "type DefaultType = {\n  b: JsonValue[];\n  a: number;\n};\n\ntype JsonValue = string | number | boolean | null | JsonValue[] | { [key: string]: JsonValue };\n"
+        );
+    }
+}
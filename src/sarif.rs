@@ -0,0 +1,62 @@
+use serde_json::{json, Value};
+
+/// A static analysis rule findings can be reported against
+pub(crate) struct SarifRule {
+    pub(crate) id: &'static str,
+    pub(crate) description: &'static str,
+}
+
+/// A single finding, located at a line of an artifact
+pub(crate) struct SarifResult {
+    pub(crate) rule_id: &'static str,
+    /// `error`, `warning` or `note`
+    pub(crate) level: &'static str,
+    pub(crate) message: String,
+    pub(crate) uri: String,
+    pub(crate) line: usize,
+}
+
+/// Renders the findings of a run as a SARIF 2.1.0 log
+pub(crate) fn to_sarif_log(rules: &[SarifRule], results: &[SarifResult]) -> String {
+    let rules: Vec<Value> = rules
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            json!({
+                "ruleId": result.rule_id,
+                "level": result.level,
+                "message": { "text": result.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": result.uri },
+                        "region": { "startLine": result.line.max(1) },
+                    }
+                }],
+            })
+        })
+        .collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "myrrh",
+                    "informationUri": "https://github.com/rorymalcolm/myrrh-rs",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    });
+    format!("{:#}\n", log)
+}
@@ -0,0 +1,535 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::ErrorKind;
+use crate::typescript_node::{TypeScriptNode, TypeScriptPrimativeType};
+use crate::{Context, Result};
+
+/// Field level description of an inferred type, keyed by the JSON path of every value
+/// (`$.payments[*].amount`) as it appears on the wire. This is what gets written to the
+/// `.myrrh.json` sidecar.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FieldSchema {
+    pub(crate) fields: BTreeMap<String, FieldType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FieldType {
+    #[serde(rename = "type")]
+    pub(crate) type_name: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) optional: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum FieldChange {
+    Added {
+        path: String,
+        new: FieldType,
+    },
+    Removed {
+        path: String,
+        old: FieldType,
+    },
+    Changed {
+        path: String,
+        old: FieldType,
+        new: FieldType,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compatibility {
+    Breaking,
+    NonBreaking,
+}
+
+/// A way in which a sample does not conform to a stored schema
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Violation {
+    pub(crate) path: String,
+    pub(crate) message: String,
+}
+
+/// Collects every type seen at a path before they are joined into a union
+#[derive(Default)]
+pub(crate) struct SchemaBuilder {
+    types: BTreeMap<String, BTreeSet<String>>,
+    optional: BTreeMap<String, bool>,
+}
+
+impl SchemaBuilder {
+    pub(crate) fn add(&mut self, path: &str, type_name: &str, optional: bool) {
+        self.types
+            .entry(path.to_string())
+            .or_default()
+            .insert(type_name.to_string());
+        *self.optional.entry(path.to_string()).or_default() |= optional;
+    }
+
+    pub(crate) fn finish(self) -> FieldSchema {
+        let optional = self.optional;
+        FieldSchema {
+            fields: self
+                .types
+                .into_iter()
+                .map(|(path, types)| {
+                    let field_type = FieldType {
+                        type_name: types.into_iter().join(" | "),
+                        optional: optional[&path],
+                    };
+                    (path, field_type)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FieldSchema {
+    pub(crate) fn from_node(node: &TypeScriptNode) -> Self {
+        let mut builder = SchemaBuilder::default();
+        Self::add_node(&mut builder, node, "$");
+        builder.finish()
+    }
+
+    fn add_node(builder: &mut SchemaBuilder, node: &TypeScriptNode, path: &str) {
+        if node.type_signature() == &TypeScriptPrimativeType::Union {
+            for member in node.sub_items() {
+                Self::add_node(builder, member, path);
+            }
+            if node.is_nullable() {
+                builder.add(path, "null", node.is_optional());
+            }
+            return;
+        }
+        builder.add(path, node.type_signature().as_str(), node.is_optional());
+        if node.is_nullable() {
+            builder.add(path, "null", node.is_optional());
+        }
+        match node.type_signature() {
+            TypeScriptPrimativeType::Object => {
+                for sub_item in node.sub_items() {
+                    let key = sub_item.wire_name().unwrap_or_default();
+                    Self::add_node(builder, sub_item, &child_path(path, key));
+                }
+            }
+            TypeScriptPrimativeType::Array => {
+                for sub_item in node.sub_items() {
+                    Self::add_node(builder, sub_item, &format!("{}[*]", path));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Reads the schema describing a generated file, preferring its `.myrrh.json` sidecar and
+    /// falling back to parsing the generated TypeScript itself
+    pub(crate) fn read_for(generated_file: &str) -> Result<Self> {
+        let sidecar = sidecar_path(generated_file);
+        if sidecar.exists() {
+            return Self::read_sidecar(&sidecar);
+        }
+        let content = std::fs::read_to_string(generated_file).with_context(|| {
+            ErrorKind::Input.because(format!("could not read file `{}`", generated_file))
+        })?;
+        crate::typescript_parser::parse_schema(&content).with_context(|| {
+            ErrorKind::Parse.because(format!(
+                "could not parse generated types in `{}`",
+                generated_file
+            ))
+        })
+    }
+
+    pub(crate) fn read_sidecar(sidecar: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(sidecar).with_context(|| {
+            ErrorKind::Input.because(format!("could not read file `{}`", sidecar.display()))
+        })?;
+        serde_json::from_str(&content).with_context(|| {
+            ErrorKind::Parse.because(format!("could not parse sidecar `{}`", sidecar.display()))
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        format!("{}\n", serde_json::to_string_pretty(self).unwrap())
+    }
+
+    pub(crate) fn changes_to(&self, new: &FieldSchema) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        for (path, old_type) in &self.fields {
+            match new.fields.get(path) {
+                None => changes.push(FieldChange::Removed {
+                    path: path.clone(),
+                    old: old_type.clone(),
+                }),
+                Some(new_type) if new_type != old_type => changes.push(FieldChange::Changed {
+                    path: path.clone(),
+                    old: old_type.clone(),
+                    new: new_type.clone(),
+                }),
+                Some(_) => (),
+            }
+        }
+        for (path, new_type) in &new.fields {
+            if !self.fields.contains_key(path) {
+                changes.push(FieldChange::Added {
+                    path: path.clone(),
+                    new: new_type.clone(),
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+}
+
+impl FieldSchema {
+    /// Checks that `value` fits within this schema: every value in it must be at a known path
+    /// with a compatible type, and every required field must be present in each object holding
+    /// it. Array elements are never required as arrays may be empty. The violations are reported
+    /// at the concrete path of the value, `$.tags[1]` rather than `$.tags[*]`.
+    pub(crate) fn violations(&self, value: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.check_value(value, "$", "$", &mut violations);
+        violations
+    }
+
+    fn check_value(
+        &self,
+        value: &Value,
+        schema_path: &str,
+        path: &str,
+        violations: &mut Vec<Violation>,
+    ) {
+        let found = json_type(value);
+        match self.fields.get(schema_path) {
+            None => {
+                violations.push(Violation {
+                    path: path.to_string(),
+                    message: format!("unexpected field of type {}", found),
+                });
+                return;
+            }
+            Some(expected) if expected.types().contains("unknown") => return,
+            Some(expected) if !expected.types().contains(found) => {
+                violations.push(Violation {
+                    path: path.to_string(),
+                    message: format!("expected {} but found {}", expected.type_name, found),
+                });
+                return;
+            }
+            Some(_) => (),
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    self.check_value(
+                        value,
+                        &child_path(schema_path, key),
+                        &child_path(path, key),
+                        violations,
+                    );
+                }
+                let present: BTreeSet<String> =
+                    map.keys().map(|key| child_path(schema_path, key)).collect();
+                let children = self
+                    .fields
+                    .range::<str, _>((Bound::Excluded(schema_path), Bound::Unbounded))
+                    .take_while(|(field, _)| field.starts_with(schema_path))
+                    .filter(|(field, _)| parent_path(field) == Some(schema_path));
+                for (field, expected) in children {
+                    if !expected.optional && !field.ends_with("[*]") && !present.contains(field) {
+                        violations.push(Violation {
+                            path: format!("{}{}", path, &field[schema_path.len()..]),
+                            message: format!(
+                                "missing required field of type {}",
+                                expected.type_name
+                            ),
+                        });
+                    }
+                }
+            }
+            Value::Array(elements) => {
+                let element_path = format!("{}[*]", schema_path);
+                for (index, element) in elements.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    self.check_value(element, &element_path, &path, violations);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The type of a JSON value as it is named in the schema
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+impl FieldType {
+    fn types(&self) -> BTreeSet<&str> {
+        self.type_name.split(" | ").collect()
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        if self.optional {
+            format!("{} (optional)", self.type_name)
+        } else {
+            self.type_name.clone()
+        }
+    }
+}
+
+impl FieldChange {
+    pub(crate) fn path(&self) -> &str {
+        match self {
+            FieldChange::Added { path, .. }
+            | FieldChange::Removed { path, .. }
+            | FieldChange::Changed { path, .. } => path,
+        }
+    }
+
+    /// Classifies the change for consumers gating on the schema: removing a field, changing or
+    /// narrowing its type or making it required is breaking, anything which only widens is not
+    pub(crate) fn classify(&self) -> (Compatibility, &'static str) {
+        match self {
+            FieldChange::Removed { .. } => (Compatibility::Breaking, "field removed"),
+            FieldChange::Added { new, .. } if new.optional => {
+                (Compatibility::NonBreaking, "optional field added")
+            }
+            FieldChange::Added { .. } => (Compatibility::Breaking, "required field added"),
+            FieldChange::Changed { old, new, .. } => {
+                if new.types().is_subset(&old.types()) && new.types() != old.types() {
+                    (Compatibility::Breaking, "type narrowed")
+                } else if !new.types().is_superset(&old.types()) {
+                    (Compatibility::Breaking, "type changed")
+                } else if old.optional && !new.optional {
+                    (Compatibility::Breaking, "field made required")
+                } else if new.types() != old.types() {
+                    (Compatibility::NonBreaking, "type widened")
+                } else {
+                    (Compatibility::NonBreaking, "field made optional")
+                }
+            }
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            FieldChange::Added { path, new } => format!("+ {}: {}", path, new.describe()),
+            FieldChange::Removed { path, old } => format!("- {}: {}", path, old.describe()),
+            FieldChange::Changed { path, old, new } => {
+                format!("~ {}: {} -> {}", path, old.describe(), new.describe())
+            }
+        }
+    }
+}
+
+/// JSON path of `key` inside the object at `path`, bracket quoting keys which aren't identifiers
+pub(crate) fn child_path(path: &str, key: &str) -> String {
+    let is_identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}[{}]", path, serde_json::to_string(key).unwrap())
+    }
+}
+
+/// Path of the value containing the one at `path`, `None` for the root
+pub(crate) fn parent_path(path: &str) -> Option<&str> {
+    if let Some(parent) = path.strip_suffix("[*]") {
+        return Some(parent);
+    }
+    if path.ends_with("\"]") {
+        return path.rfind("[\"").map(|index| &path[..index]);
+    }
+    path.rfind('.').map(|index| &path[..index])
+}
+
+/// `types/user.ts` -> `types/user.myrrh.json`
+pub(crate) fn sidecar_path(generated_file: &str) -> PathBuf {
+    Path::new(generated_file).with_extension("myrrh.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compatibility, FieldChange, FieldSchema, FieldType, Violation};
+    use crate::walk_value_tree;
+
+    fn schema(json: &str) -> FieldSchema {
+        let val_tree = serde_json::from_str(json).unwrap();
+        FieldSchema::from_node(&walk_value_tree(&val_tree, None))
+    }
+
+    fn field(type_name: &str) -> FieldType {
+        FieldType {
+            type_name: type_name.to_string(),
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn flattens_nested_paths() {
+        let schema = schema(r#"{ "payments": [{ "amount": 1, "is paid": true }, "n/a"] }"#);
+        let fields: Vec<(&str, &str)> = schema
+            .fields
+            .iter()
+            .map(|(path, field)| (path.as_str(), field.type_name.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("$", "object"),
+                ("$.payments", "array"),
+                ("$.payments[*]", "object | string"),
+                ("$.payments[*].amount", "number"),
+                ("$.payments[*][\"is paid\"]", "boolean"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_field_changes() {
+        let old = schema(r#"{ "amount": 1, "currency": "USD" }"#);
+        let new = schema(r#"{ "amount": "1", "fee": 2 }"#);
+        assert_eq!(
+            old.changes_to(&new),
+            vec![
+                FieldChange::Changed {
+                    path: "$.amount".to_string(),
+                    old: field("number"),
+                    new: field("string"),
+                },
+                FieldChange::Removed {
+                    path: "$.currency".to_string(),
+                    old: field("string"),
+                },
+                FieldChange::Added {
+                    path: "$.fee".to_string(),
+                    new: field("number"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_breaking_changes() {
+        let old = schema(r#"{ "id": 1, "tags": ["a"], "note": "x", "ref": [1, "a"] }"#);
+        let new = schema(r#"{ "id": "1", "tags": ["a", 1], "extra": true, "ref": ["a"] }"#);
+        let classified: Vec<(String, Compatibility, &str)> = old
+            .changes_to(&new)
+            .iter()
+            .map(|change| {
+                let (compatibility, reason) = change.classify();
+                (change.path().to_string(), compatibility, reason)
+            })
+            .collect();
+        assert_eq!(
+            classified,
+            vec![
+                (
+                    "$.extra".to_string(),
+                    Compatibility::Breaking,
+                    "required field added"
+                ),
+                ("$.id".to_string(), Compatibility::Breaking, "type changed"),
+                (
+                    "$.note".to_string(),
+                    Compatibility::Breaking,
+                    "field removed"
+                ),
+                (
+                    "$.ref[*]".to_string(),
+                    Compatibility::Breaking,
+                    "type narrowed"
+                ),
+                (
+                    "$.tags[*]".to_string(),
+                    Compatibility::NonBreaking,
+                    "type widened"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_fields_are_non_breaking() {
+        let old = schema(r#"{ "id": 1 }"#);
+        let mut new = schema(r#"{ "id": 1, "extra": true }"#);
+        new.fields.get_mut("$.extra").unwrap().optional = true;
+        new.fields.get_mut("$.id").unwrap().optional = true;
+        let classified: Vec<Compatibility> = old
+            .changes_to(&new)
+            .iter()
+            .map(|change| change.classify().0)
+            .collect();
+        assert_eq!(
+            classified,
+            vec![Compatibility::NonBreaking, Compatibility::NonBreaking]
+        );
+    }
+
+    #[test]
+    fn reports_violations_against_schema() {
+        let stored = schema(r#"{ "id": 1, "user": { "name": "a" }, "tags": ["a"] }"#);
+        let sample = serde_json::json!({ "debug": true, "id": "1", "tags": [] });
+        assert_eq!(
+            stored.violations(&sample),
+            vec![
+                Violation {
+                    path: "$.debug".to_string(),
+                    message: "unexpected field of type boolean".to_string(),
+                },
+                Violation {
+                    path: "$.id".to_string(),
+                    message: "expected number but found string".to_string(),
+                },
+                Violation {
+                    path: "$.user".to_string(),
+                    message: "missing required field of type object".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_violations_at_the_failing_element() {
+        let stored = schema(r#"{ "tags": ["a"], "users": [{ "name": "a" }] }"#);
+        let sample = serde_json::json!({
+            "tags": ["a", 1, "b"],
+            "users": [{ "name": "a" }, {}, { "name": "c" }]
+        });
+        assert_eq!(
+            stored.violations(&sample),
+            vec![
+                Violation {
+                    path: "$.tags[1]".to_string(),
+                    message: "expected string but found number".to_string(),
+                },
+                Violation {
+                    path: "$.users[1].name".to_string(),
+                    message: "missing required field of type string".to_string(),
+                },
+            ]
+        );
+    }
+}
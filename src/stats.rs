@@ -0,0 +1,186 @@
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use serde_json::{json, Value};
+
+use crate::render_types;
+use crate::typescript_node::{Fingerprint, RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+/// What is counted walking a tree
+#[derive(Default)]
+struct Counts {
+    /// Places in the tree holding an object
+    objects: usize,
+    /// The field count of each distinct object shape, by its hash
+    shapes: HashMap<Fingerprint, usize>,
+    max_depth: usize,
+    field_names: HashMap<String, usize>,
+}
+
+impl Counts {
+    fn visit(&mut self, node: &TypeScriptNode, depth: usize) {
+        let depth = match node.type_signature() {
+            TypeScriptPrimativeType::Object => {
+                self.objects += 1;
+                self.shapes.insert(node.hash(), node.sub_items().len());
+                for field in node.sub_items() {
+                    *self
+                        .field_names
+                        .entry(field.name().unwrap_or_default().to_string())
+                        .or_default() += 1;
+                }
+                depth + 1
+            }
+            TypeScriptPrimativeType::Array => depth + 1,
+            _ => depth,
+        };
+        self.max_depth = self.max_depth.max(depth);
+        for sub_item in node.sub_items() {
+            self.visit(sub_item, depth);
+        }
+    }
+}
+
+/// The shape of a tree to be rendered with `options`, for `--report json`: how many objects and
+/// distinct object shapes it holds, how deep it goes and the types it declares
+pub(crate) fn shape_summary(tree: &TypeScriptNode, options: &RenderOptions) -> Value {
+    let mut shaped = tree.clone();
+    shaped.calculate_hash();
+    let mut counts = Counts::default();
+    counts.visit(&shaped, 0);
+    let type_names = tree.type_names(options);
+    let declared: Vec<&str> = std::iter::once(options.root_name.as_str())
+        .chain(
+            tree.named_types(&type_names)
+                .into_iter()
+                .map(|(name, _)| name),
+        )
+        .collect();
+    json!({
+        "objects": counts.objects,
+        "shapes": counts.shapes.len(),
+        "maxDepth": counts.max_depth,
+        "types": declared,
+    })
+}
+
+/// Summarises the tree merged from the samples: how many object shapes it holds and how much
+/// declaring the repeated ones once saves, how deep it goes, how many fields its objects have
+/// and the `top` most common field names
+pub(crate) fn stats_report(tree: &TypeScriptNode, top: usize) -> String {
+    let mut shaped = tree.clone();
+    shaped.calculate_hash();
+    let mut counts = Counts::default();
+    counts.visit(&shaped, 0);
+
+    let options = RenderOptions::default();
+    let inline_lines = render_types(tree, &options).lines().count();
+    let mut squashed = tree.clone();
+    squashed.fold_recursive_objects();
+    squashed.unify_repeated_objects();
+    squashed.calculate_hash();
+    let declared = squashed.named_types(&squashed.type_names(&options)).len();
+    let squashed_lines = render_types(&squashed, &options).lines().count();
+
+    let mut report = String::new();
+    report.push_str(&format!(
+        "Objects: {} in {} distinct shapes\n",
+        counts.objects,
+        counts.shapes.len()
+    ));
+    report.push_str(&format!(
+        "Shared types: {}, {} lines instead of {} inline{}\n",
+        declared,
+        squashed_lines,
+        inline_lines,
+        if inline_lines > squashed_lines {
+            format!(
+                " ({}% fewer)",
+                (inline_lines - squashed_lines) * 100 / inline_lines
+            )
+        } else {
+            String::new()
+        }
+    ));
+    report.push_str(&format!("Max depth: {}\n", counts.max_depth));
+
+    report.push_str("Fields per object shape:\n");
+    let mut histogram: BTreeMap<usize, usize> = BTreeMap::new();
+    for fields in counts.shapes.values() {
+        *histogram.entry(bucket(*fields)).or_default() += 1;
+    }
+    if histogram.is_empty() {
+        report.push_str("  (none)\n");
+    }
+    let widest = histogram.values().copied().max().unwrap_or_default();
+    for (low, shapes) in &histogram {
+        let label = match low {
+            0 | 1 => low.to_string(),
+            low => format!("{}-{}", low, low * 2 - 1),
+        };
+        report.push_str(&format!(
+            "  {:>7} {:>4} {}\n",
+            label,
+            shapes,
+            "#".repeat((shapes * HISTOGRAM_WIDTH).div_ceil(widest))
+        ));
+    }
+
+    report.push_str("Most common field names:\n");
+    if counts.field_names.is_empty() {
+        report.push_str("  (none)\n");
+    }
+    for (name, seen) in counts
+        .field_names
+        .iter()
+        .sorted_by(|(a_name, a_seen), (b_name, b_seen)| b_seen.cmp(a_seen).then(a_name.cmp(b_name)))
+        .take(top)
+    {
+        report.push_str(&format!("  {} {}\n", name, seen));
+    }
+    report
+}
+
+/// The widest bar of the histogram
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// The lower bound of the power of two range a field count falls in, 0, 1, 2-3, 4-7 and so on
+fn bucket(fields: usize) -> usize {
+    match fields {
+        0 => 0,
+        fields => 1 << fields.ilog2(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stats_report;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn reports_stats() {
+        let val_tree = serde_json::from_str(
+            r#"{
+                "id": 1,
+                "billing": { "id": 2, "street": "a", "city": "b" },
+                "shipping": { "id": 3, "street": "c", "city": "d" },
+                "items": [{ "id": 4, "name": "e" }]
+            }"#,
+        )
+        .unwrap();
+        let tree = walk_value_tree(&val_tree, None);
+        assert_eq!(
+            stats_report(&tree, 2),
+            "Objects: 4 in 3 distinct shapes
+Shared types: 1, 15 lines instead of 17 inline (11% fewer)
+Max depth: 3
+Fields per object shape:
+      2-3    2 ########################################
+      4-7    1 ####################
+Most common field names:
+  id 4
+  city 2
+"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+
+use crate::declarations::{numbered, to_member_identifier, to_type_identifier, Declarations};
+use crate::naming::segment;
+use crate::typescript_node::{RenderOptions, TypeScriptNode, TypeScriptPrimativeType};
+
+const KEYWORDS: [&str; 42] = [
+    "Any",
+    "Self",
+    "as",
+    "associatedtype",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "deinit",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "fileprivate",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "switch",
+    "var",
+];
+
+const INDENT: &str = "    ";
+
+/// The type of values Swift has no type for: `null` on its own, arrays mixing types, unions and
+/// values below `--max-depth`
+const JSON_VALUE: &str = "JSONValue";
+
+/// The `Codable` enum declared when `JSONValue` is used
+const JSON_VALUE_DECLARATION: &str = "enum JSONValue: Codable {
+    case string(String)
+    case number(Double)
+    case bool(Bool)
+    case object([String: JSONValue])
+    case array([JSONValue])
+    case null
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.singleValueContainer()
+        if container.decodeNil() {
+            self = .null
+        } else if let value = try? container.decode(Bool.self) {
+            self = .bool(value)
+        } else if let value = try? container.decode(Double.self) {
+            self = .number(value)
+        } else if let value = try? container.decode(String.self) {
+            self = .string(value)
+        } else if let value = try? container.decode([JSONValue].self) {
+            self = .array(value)
+        } else {
+            self = .object(try container.decode([String: JSONValue].self))
+        }
+    }
+
+    func encode(to encoder: Encoder) throws {
+        var container = encoder.singleValueContainer()
+        switch self {
+        case .string(let value): try container.encode(value)
+        case .number(let value): try container.encode(value)
+        case .bool(let value): try container.encode(value)
+        case .object(let value): try container.encode(value)
+        case .array(let value): try container.encode(value)
+        case .null: try container.encodeNil()
+        }
+    }
+}
+";
+
+/// Renders the tree as Swift `Codable` structs, a struct per object type, named as the Python
+/// classes are. Properties are camelCase, with `CodingKeys` mapping them back wherever that
+/// isn't the JSON key. Properties which were missing or `null` in some samples are optionals.
+pub(crate) fn to_swift_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let mut renderer = Renderer {
+        structs: Declarations::new(node, options, &[JSON_VALUE]),
+        uses_json_value: false,
+    };
+    let mut output = String::new();
+    if node.type_signature() == &TypeScriptPrimativeType::Object {
+        renderer.declaration(node, options.root_name.clone(), true);
+    } else {
+        // only objects get a struct, so the type of the root is noted instead
+        let root_type = renderer.type_name(node, &options.root_name);
+        output.push_str(&format!("// {}: {}\n", options.root_name, root_type));
+    }
+    if renderer.uses_json_value {
+        renderer.structs.push(JSON_VALUE_DECLARATION.to_string());
+    }
+    for declaration in renderer.structs.iter() {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(declaration);
+    }
+    output
+}
+
+/// Keywords have to be escaped with backticks to be used as names
+fn escaped(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("`{}`", name)
+    } else {
+        name.to_string()
+    }
+}
+
+struct Renderer {
+    structs: Declarations,
+    uses_json_value: bool,
+}
+
+impl Renderer {
+    /// The type of a value, `?` when it was seen as `null`, `hint` naming the struct of an
+    /// inline object
+    fn type_name(&mut self, node: &TypeScriptNode, hint: &str) -> String {
+        let type_name = match node.type_signature() {
+            TypeScriptPrimativeType::String => "String".to_string(),
+            TypeScriptPrimativeType::Number if node.is_integer() => "Int".to_string(),
+            TypeScriptPrimativeType::Number => "Double".to_string(),
+            TypeScriptPrimativeType::Boolean => "Bool".to_string(),
+            TypeScriptPrimativeType::Object if node.is_self_reference() => {
+                self.structs.referenced_name(node).to_string()
+            }
+            TypeScriptPrimativeType::Object => match self.structs.emitted_name(node) {
+                Some(name) => name.to_string(),
+                None => self.declaration(node, hint.to_string(), false),
+            },
+            TypeScriptPrimativeType::Array => {
+                let elements: Vec<&TypeScriptNode> = node
+                    .elements()
+                    .iter()
+                    .filter(|element| element.type_signature() != &TypeScriptPrimativeType::Null)
+                    .collect();
+                let nullable_elements = elements.len() < node.elements().len()
+                    || elements.iter().any(|element| element.is_nullable());
+                // the structs of elements mixed with others aren't declared, as they end up as
+                // JSON values
+                let element_type = match elements.as_slice() {
+                    [element] => {
+                        let element_type = self.type_name(element, &format!("{}Item", hint));
+                        match element_type.strip_suffix('?') {
+                            Some(element_type) => element_type.to_string(),
+                            None => element_type,
+                        }
+                    }
+                    _ => self.json_value(),
+                };
+                if nullable_elements {
+                    format!("[{}?]", element_type)
+                } else {
+                    format!("[{}]", element_type)
+                }
+            }
+            TypeScriptPrimativeType::Null
+            | TypeScriptPrimativeType::Unknown
+            | TypeScriptPrimativeType::Union => self.json_value(),
+        };
+        if node.is_nullable() || node.type_signature() == &TypeScriptPrimativeType::Null {
+            format!("{}?", type_name)
+        } else {
+            type_name
+        }
+    }
+
+    fn json_value(&mut self) -> String {
+        self.uses_json_value = true;
+        JSON_VALUE.to_string()
+    }
+
+    /// Emits the struct of an object ahead of the structs it refers to, returning its name. The
+    /// root is named `hint`, common types by their type name and other objects after `hint`.
+    fn declaration(&mut self, node: &TypeScriptNode, hint: String, root: bool) -> String {
+        let name = self.structs.name(node, to_type_identifier(&hint), root);
+        let index = self.structs.reserve();
+        let mut property_names = HashSet::new();
+        let mut coding_keys = Vec::new();
+        let mut body = String::new();
+        for sub_item in node.sub_items() {
+            let key = sub_item.name().unwrap_or_default();
+            let property_name = numbered(&to_member_identifier(key), "", &property_names);
+            property_names.insert(property_name.clone());
+            let mut property_type = self.type_name(sub_item, &format!("{}{}", name, segment(key)));
+            // optionals are decoded as `nil` when the key is missing
+            if sub_item.is_optional() && !property_type.ends_with('?') {
+                property_type.push('?');
+            }
+            body.push_str(&format!(
+                "{}let {}: {}\n",
+                INDENT,
+                escaped(&property_name),
+                property_type
+            ));
+            coding_keys.push((property_name, key));
+        }
+        // the keys are only spelled out when one of them differs from its property
+        if coding_keys
+            .iter()
+            .any(|(property_name, key)| property_name != key)
+        {
+            body.push_str(&format!(
+                "\n{}enum CodingKeys: String, CodingKey {{\n",
+                INDENT
+            ));
+            for (property_name, key) in &coding_keys {
+                if property_name == key {
+                    body.push_str(&format!("{0}{0}case {1}\n", INDENT, escaped(property_name)));
+                } else {
+                    body.push_str(&format!(
+                        "{0}{0}case {1} = {2}\n",
+                        INDENT,
+                        escaped(property_name),
+                        serde_json::to_string(key).unwrap()
+                    ));
+                }
+            }
+            body.push_str(&format!("{}}}\n", INDENT));
+        }
+        // a struct can't hold itself, even through an optional
+        let keyword = if node.is_recursive() {
+            "final class"
+        } else {
+            "struct"
+        };
+        self.structs.fill(
+            index,
+            format!("{} {}: Codable {{\n{}}}\n", keyword, name, body),
+        );
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_swift_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::{walk_value_tree_with_options, InferOptions};
+
+    #[test]
+    fn renders_swift_structs() {
+        let val_tree = serde_json::from_str(
+            r#"{ "user_id": 1, "default": true, "lines": [{ "qty": 1.5, "note": null }, { "qty": 2 }], "tags": ["a", null], "meta": [1, "a"] }"#,
+        )
+        .unwrap();
+        let options = InferOptions {
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.calculate_hash();
+        let output = to_swift_string(&result, &RenderOptions::default());
+        assert!(output.ends_with(super::JSON_VALUE_DECLARATION));
+        assert_eq!(
+            output.strip_suffix(super::JSON_VALUE_DECLARATION).unwrap(),
+            "struct DefaultType: Codable {
+    let `default`: Bool
+    let lines: [DefaultType_0]
+    let meta: [JSONValue]
+    let tags: [String?]
+    let userId: Int
+
+    enum CodingKeys: String, CodingKey {
+        case `default`
+        case lines
+        case meta
+        case tags
+        case userId = \"user_id\"
+    }
+}
+
+struct DefaultType_0: Codable {
+    let note: JSONValue?
+    let qty: Double
+}
+
+"
+        );
+    }
+
+    #[test]
+    fn leaves_out_the_structs_of_mixed_elements() {
+        let val_tree = serde_json::from_str(
+            r#"{ "items": [{ "a": 1, "c": true }, { "b": "x" }], "lines": [{ "qty": 1 }, null] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree_with_options(&val_tree, None, &InferOptions::default());
+        result.calculate_hash();
+        let output = to_swift_string(&result, &RenderOptions::default());
+        assert_eq!(
+            output.strip_suffix(super::JSON_VALUE_DECLARATION).unwrap(),
+            "struct DefaultType: Codable {
+    let items: [JSONValue]
+    let lines: [DefaultTypeLinesItem?]
+}
+
+struct DefaultTypeLinesItem: Codable {
+    let qty: Double
+}
+
+"
+        );
+    }
+}
@@ -1,14 +1,54 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Write,
     hash::Hasher,
-    sync::{Arc, Mutex},
+    sync::Arc,
 };
 
-use crate::type_output_cache_entry::TypeOutputCacheEntry;
+use siphasher::sip128::{Hasher128, SipHasher13};
 
-#[derive(Debug)]
+use tracing::{event, Level};
+
+use crate::diagnostics::Diagnostics;
+use crate::formats::{
+    self, BigIntMode, DateType, FallbackType, NumberMode, StringFormat, StringFormatMode,
+};
+use crate::interner::Interner;
+use crate::literals::StringValues;
+use crate::naming::{self, NamingStrategy, TypeNamer};
+use crate::provenance::{add_sources, Source};
+
+/// The hash of a node, a fingerprint of its structure, see [`TypeScriptNode::calculate_hash`]
+pub(crate) type Fingerprint = u128;
+
+/// Writes a string to a hasher prefixed with its length, so consecutive strings can't run into
+/// each other
+fn write_field(hasher: &mut SipHasher13, bytes: &[u8]) {
+    hasher.write_usize(bytes.len());
+    hasher.write(bytes);
+}
+
+/// Rounds of unifying repeated objects before giving up on reaching a fixed point
+const MAX_UNIFY_ROUNDS: usize = 8;
+
+/// Distinct values sampled per primitive node
+const MAX_EXAMPLES: usize = 2;
+
+/// Characters of a sampled value shown in a comment before it is cut short
+const MAX_EXAMPLE_LENGTH: usize = 40;
+
+/// Fields which tag the objects of an array with their kind, tried in order when no
+/// discriminator is given
+const DISCRIMINATORS: [&str; 5] = ["type", "kind", "__typename", "tag", "event"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub(crate) enum TypeScriptPrimativeType {
     String,
     Boolean,
@@ -16,67 +56,1415 @@ pub(crate) enum TypeScriptPrimativeType {
     Object,
     Array,
     Null,
+    /// A value which wasn't walked, typed with the fallback type
+    Unknown,
+    /// Any of the member types held in its sub items, for roots whose samples disagree
+    Union,
 }
 
 impl TypeScriptPrimativeType {
-    fn as_bytes(&self) -> &'static [u8] {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
-            Self::String => b"string",
-            Self::Boolean => b"boolean",
-            Self::Number => b"number",
-            Self::Object => b"object",
-            Self::Array => b"array",
-            Self::Null => b"null",
+            Self::String => "string",
+            Self::Boolean => "boolean",
+            Self::Number => "number",
+            Self::Object => "object",
+            Self::Array => "array",
+            Self::Null => "null",
+            Self::Unknown => "unknown",
+            Self::Union => "union",
         }
     }
+
+    fn as_bytes(&self) -> &'static [u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+/// The part an object node plays in a type which refers to itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Recursion {
+    /// An object type which the objects nested in it refer back to
+    Type,
+    /// Stands for the nearest enclosing recursive object type
+    Reference,
 }
 
-#[derive(Debug)]
+/// How object types are declared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeclarationStyle {
+    /// `type Foo = { ... }`
+    #[default]
+    TypeAlias,
+    /// `interface Foo { ... }`, non-object types are still declared as type aliases
+    Interface,
+}
+
+/// Where the brace opening the body of a declaration goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum BraceStyle {
+    /// `type Foo = {`
+    #[default]
+    SameLine,
+    /// On a line of its own, below `type Foo =`
+    NextLine,
+}
+
+/// How the declarations are laid out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per level of indentation, unused when indenting with tabs
+    pub indent_width: usize,
+    pub use_tabs: bool,
+    /// End members and type aliases with `;`
+    pub semicolons: bool,
+    pub brace_style: BraceStyle,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 2,
+            use_tabs: false,
+            semicolons: true,
+            brace_style: BraceStyle::default(),
+        }
+    }
+}
+
+impl FormatOptions {
+    fn indent(&self, depth: usize) -> String {
+        let mut indent = String::new();
+        self.write_indent(&mut indent, depth);
+        indent
+    }
+
+    fn write_indent(&self, out: &mut String, depth: usize) {
+        if self.use_tabs {
+            out.extend(std::iter::repeat_n('\t', depth));
+        } else {
+            out.extend(std::iter::repeat_n(' ', self.indent_width * depth));
+        }
+    }
+
+    pub(crate) fn terminator(&self) -> &'static str {
+        if self.semicolons {
+            ";"
+        } else {
+            ""
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Name of the root type, which the names of the common types start from
+    pub root_name: String,
+    pub declaration_style: DeclarationStyle,
+    /// How the common types are named
+    pub naming: NamingStrategy,
+    /// Names the common types instead of `naming`, when set
+    pub type_namer: Option<Arc<dyn TypeNamer>>,
+    /// How strings recognised as dates are typed, plain `string` when not set
+    pub date_type: Option<DateType>,
+    /// How strings recognised as UUIDs, emails, URLs or IP addresses are typed, plain `string`
+    /// when not set
+    pub string_formats: Option<StringFormatMode>,
+    /// The alias strings recognised as base64 are typed as, plain `string` when not set
+    pub base64_type: Option<String>,
+    /// How numbers which were always integers are typed
+    pub number_mode: NumberMode,
+    /// How integers beyond `Number.MAX_SAFE_INTEGER` are typed
+    pub bigint_mode: BigIntMode,
+    /// Prefix every declaration with `export`
+    pub export: bool,
+    /// Document renamed properties with their wire name, `/** @originalName user_id */`
+    pub original_names: bool,
+    /// Document properties with the values sampled for them, `/** e.g. "paid" */`
+    pub examples: bool,
+    /// Declare every property `readonly` and every array `readonly T[]`
+    pub readonly: bool,
+    /// Type fields which were only ever null as `unknown`, and flag every field whose type
+    /// couldn't be worked out from the samples with a `// TODO` comment saying why
+    pub strict: bool,
+    /// How values which weren't walked, and the elements of empty arrays, are typed
+    pub fallback_type: FallbackType,
+    /// The type of arrays which were only ever empty, an array of the fallback type when not set
+    pub empty_array_type: Option<String>,
+    /// Object types with at most this many fields are kept inline, however often they're seen
+    pub inline_max_fields: Option<usize>,
+    /// Times an object type has to be seen to get a declaration of its own, at least 2
+    pub extract_min_occurrences: usize,
+    /// Also declare `{root_name}s` as an array of the root type, right after it, as a table of
+    /// rows is typed
+    pub list_alias: bool,
+    pub format: FormatOptions,
+    /// Collects the weak spots of the types as they are rendered, rather than logging them
+    pub diagnostics: Option<Arc<Diagnostics>>,
+}
+
+impl RenderOptions {
+    /// What every declaration starts with, `export ` when exporting them
+    pub(crate) fn declaration_prefix(&self) -> &'static str {
+        if self.export {
+            "export "
+        } else {
+            ""
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            root_name: "DefaultType".to_string(),
+            declaration_style: DeclarationStyle::default(),
+            naming: NamingStrategy::default(),
+            type_namer: None,
+            date_type: None,
+            string_formats: None,
+            base64_type: None,
+            number_mode: NumberMode::default(),
+            bigint_mode: BigIntMode::default(),
+            export: false,
+            original_names: false,
+            examples: false,
+            readonly: false,
+            strict: false,
+            fallback_type: FallbackType::default(),
+            empty_array_type: None,
+            inline_max_fields: None,
+            extract_min_occurrences: 2,
+            list_alias: false,
+            format: FormatOptions::default(),
+            diagnostics: None,
+        }
+    }
+}
+
+/// What rendering a tree needs besides the tree itself: the options and the names of the common
+/// types, worked out once up front
+pub(crate) struct RenderContext<'a> {
+    pub(crate) options: &'a RenderOptions,
+    pub(crate) type_names: HashMap<Fingerprint, String>,
+}
+
+impl<'a> RenderContext<'a> {
+    /// The context for rendering `node`, whose hashes have been calculated
+    pub(crate) fn new(node: &TypeScriptNode, options: &'a RenderOptions) -> Self {
+        RenderContext {
+            options,
+            type_names: node.type_names(options),
+        }
+    }
+}
+
+/// A rendered declaration, with the names of the other declarations it refers to
+struct Declaration {
+    #[cfg(feature = "cli")]
+    name: String,
+    text: String,
+    #[cfg(feature = "cli")]
+    references: BTreeSet<String>,
+}
+
+/// Serializes as the IR saved by `--emit ir`, without the hashes, which are recalculated
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct TypeScriptNode {
-    lookup_table: Arc<Mutex<HashMap<u64, usize>>>,
-    name: Option<String>,
+    /// Shared with every other node named after the same key, see [`crate::interner`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<Arc<str>>,
     nullable: bool,
     optional: bool,
     is_array: bool,
     root_node: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     sub_items: Vec<TypeScriptNode>,
+    #[serde(rename = "type")]
     type_signature: TypeScriptPrimativeType,
-    hash: u64,
+    #[serde(skip)]
+    hash: Fingerprint,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wire_name: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<Source>,
+    #[serde(default, skip_serializing_if = "StringValues::is_empty")]
+    string_values: StringValues,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    literals: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    string_format: Option<StringFormat>,
+    /// Whether every number observed was an integer, only tracked when asked for
+    #[serde(default)]
+    integer: bool,
+    /// Whether a number observed was an integer beyond `Number.MAX_SAFE_INTEGER`
+    #[serde(default)]
+    big: bool,
+    /// Name of a type declared elsewhere which this object node stands for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recursion: Option<Recursion>,
+    /// How many values of the tree the node stands for, more than one for the elements of an
+    /// array which were merged into it
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    occurrences: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+fn is_one(occurrences: &usize) -> bool {
+    *occurrences == 1
 }
 
 impl TypeScriptNode {
-    pub(crate) fn calculate_hash(&mut self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        let mut hash_seen_before = HashSet::<u64>::new();
+    /// Hashes the node from its type and its members, in any order, so objects whose keys were
+    /// observed in a different order share a type. The hash covers the whole subtree, so equal
+    /// objects share a type wherever they appear, arrays and their elements included. It's a
+    /// 128-bit fingerprint, and strings are written with their length, so distinct types
+    /// practically never share one; [`Self::type_names`] checks that they don't anyway.
+    pub(crate) fn calculate_hash(&mut self) -> Fingerprint {
+        let mut hasher = SipHasher13::new();
+        // without it every value without members, `{}` among them, would hash the same
+        write_field(&mut hasher, self.type_signature.as_bytes());
+        if let Some(reference) = &self.reference {
+            write_field(&mut hasher, reference.as_bytes());
+        }
+        for literal in self.literals.iter().flatten() {
+            write_field(&mut hasher, literal.as_bytes());
+        }
+        // a reference to a recursive type is given the hash of the type once that is known
+        if self.is_self_reference() {
+            write_field(&mut hasher, b"self");
+        }
+        // sorted rather than deduplicated, so members repeated in a union or an array count
+        let mut member_hashes = Vec::with_capacity(self.sub_items.len());
         for sub_item in &mut self.sub_items {
-            hasher.write(sub_item.type_signature.as_bytes());
-            hasher.write(sub_item.name.as_ref().unwrap_or(&"".to_string()).as_bytes());
-            let sub_node_hash = &sub_item.calculate_hash();
-            if hash_seen_before.contains(sub_node_hash) {
-                continue;
-            } else {
-                hasher.write(&sub_node_hash.to_le_bytes());
-                hash_seen_before.insert(*sub_node_hash);
+            let mut member_hasher = SipHasher13::new();
+            write_field(&mut member_hasher, sub_item.type_signature.as_bytes());
+            write_field(
+                &mut member_hasher,
+                sub_item.name.as_deref().unwrap_or_default().as_bytes(),
+            );
+            member_hasher.write(&[sub_item.optional as u8, sub_item.nullable as u8]);
+            if let Some(format) = sub_item.string_format {
+                write_field(&mut member_hasher, format.as_str().as_bytes());
             }
+            member_hasher.write(&[sub_item.integer as u8, sub_item.big as u8]);
+            member_hasher.write(&sub_item.calculate_hash().to_le_bytes());
+            member_hashes.push(member_hasher.finish128().as_u128());
         }
-        let hash = hasher.finish();
-        let mut table = self.lookup_table.lock().unwrap();
-        if !table.contains_key(&hash) {
-            table.insert(hash, 1);
-        } else {
-            let current_value = table[&hash];
-            table.insert(hash, current_value + 1);
+        member_hashes.sort_unstable();
+        for member_hash in member_hashes {
+            hasher.write(&member_hash.to_le_bytes());
         }
+        let hash = hasher.finish128().as_u128();
         self.hash = hash;
+        if self.recursion == Some(Recursion::Type) {
+            self.resolve_references(hash);
+        }
         hash
     }
 
-    pub(crate) fn is_array(&self) -> bool {
-        return self.is_array;
+    /// Whether the node has the structure of `other`, comparing what the hash covers as it is
+    /// rather than hashed, members in any order
+    fn has_structure_of(&self, other: &TypeScriptNode) -> bool {
+        if self.type_signature != other.type_signature
+            || self.reference != other.reference
+            || self.literals != other.literals
+            || self.is_self_reference() != other.is_self_reference()
+            || self.sub_items.len() != other.sub_items.len()
+        {
+            return false;
+        }
+        let mut unmatched: Vec<&TypeScriptNode> = other.sub_items.iter().collect();
+        self.sub_items.iter().all(|member| {
+            let matching = unmatched.iter().position(|candidate| {
+                member.name == candidate.name
+                    && member.optional == candidate.optional
+                    && member.nullable == candidate.nullable
+                    && member.string_format == candidate.string_format
+                    && member.integer == candidate.integer
+                    && member.big == candidate.big
+                    && member.has_structure_of(candidate)
+            });
+            matching.map(|index| unmatched.swap_remove(index)).is_some()
+        })
+    }
+
+    /// The hashes shared by object types of different structures, which would otherwise be
+    /// declared as one. Recursive types are left out, their references need them declared.
+    pub(crate) fn colliding_hashes(&self) -> HashSet<Fingerprint> {
+        let mut first_seen = HashMap::new();
+        let mut colliding = HashSet::new();
+        self.collect_colliding_hashes(&mut first_seen, &mut colliding);
+        colliding
+    }
+
+    fn collect_colliding_hashes<'a>(
+        &'a self,
+        first_seen: &mut HashMap<Fingerprint, &'a TypeScriptNode>,
+        colliding: &mut HashSet<Fingerprint>,
+    ) {
+        if self.type_signature == TypeScriptPrimativeType::Object
+            && self.reference.is_none()
+            && self.recursion.is_none()
+        {
+            let first = *first_seen.entry(self.hash).or_insert(self);
+            if !first.has_structure_of(self) {
+                colliding.insert(self.hash);
+            }
+        }
+        for sub_item in &self.sub_items {
+            sub_item.collect_colliding_hashes(first_seen, colliding);
+        }
+    }
+
+    /// Gives the references which stand for this recursive type its hash, leaving those of the
+    /// recursive types nested in it
+    fn resolve_references(&mut self, hash: Fingerprint) {
+        for sub_item in &mut self.sub_items {
+            match sub_item.recursion {
+                Some(Recursion::Reference) => sub_item.hash = hash,
+                Some(Recursion::Type) => (),
+                None => sub_item.resolve_references(hash),
+            }
+        }
+    }
+
+    /// Whether objects nested in the node refer back to it
+    #[cfg(feature = "cli")]
+    pub(crate) fn is_recursive(&self) -> bool {
+        self.recursion == Some(Recursion::Type)
+    }
+
+    /// Whether the node stands for the recursive object type enclosing it, sharing its hash
+    pub(crate) fn is_self_reference(&self) -> bool {
+        self.recursion == Some(Recursion::Reference)
+    }
+
+    /// Counts the values of every hash in the tree
+    fn count_hashes(&self, counts: &mut HashMap<Fingerprint, usize>) {
+        *counts.entry(self.hash).or_default() += self.occurrences;
+        for sub_item in &self.sub_items {
+            sub_item.count_hashes(counts);
+        }
+    }
+
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.root_node
+    }
+
+    #[cfg(feature = "cli")]
+    pub(crate) fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    #[cfg(feature = "cli")]
+    pub(crate) fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    pub(crate) fn type_signature(&self) -> &TypeScriptPrimativeType {
+        &self.type_signature
+    }
+
+    pub(crate) fn sub_items(&self) -> &[TypeScriptNode] {
+        &self.sub_items
+    }
+
+    /// The key as it appears in the input, for nodes whose name was renamed
+    pub(crate) fn wire_name(&self) -> Option<&str> {
+        self.wire_name.as_deref().or(self.name.as_deref())
+    }
+
+    /// Renames every object field with `rename`, remembering the original key. A field whose new
+    /// name another field of the object already has keeps its original key.
+    #[cfg(feature = "cli")]
+    pub(crate) fn rename_fields(&mut self, rename: &dyn Fn(&str) -> String) {
+        let is_object = self.type_signature == TypeScriptPrimativeType::Object;
+        let mut taken: HashSet<Arc<str>> = self
+            .sub_items
+            .iter()
+            .filter_map(|sub_item| sub_item.name.clone())
+            .collect();
+        for sub_item in &mut self.sub_items {
+            if let Some(name) = &sub_item.name {
+                let renamed: Arc<str> = rename(name).into();
+                if renamed != *name {
+                    if !is_object || taken.insert(renamed.clone()) {
+                        sub_item.wire_name = sub_item.name.replace(renamed);
+                    } else {
+                        event!(
+                            Level::WARN,
+                            "the field `{}` keeps its name, renaming it to `{}` would clash with another field",
+                            name,
+                            renamed
+                        );
+                    }
+                }
+            }
+            sub_item.rename_fields(rename);
+        }
+    }
+
+    /// Sorts the fields of every object by name
+    #[cfg(feature = "cli")]
+    pub(crate) fn sort_fields(&mut self) {
+        if self.type_signature == TypeScriptPrimativeType::Object {
+            self.sub_items.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        for sub_item in &mut self.sub_items {
+            sub_item.sort_fields();
+        }
+    }
+
+    /// Marks nullable object fields optional instead, so `field: T | null` becomes `field?: T`
+    #[cfg(feature = "cli")]
+    pub(crate) fn null_as_optional(&mut self) {
+        let is_object = self.type_signature == TypeScriptPrimativeType::Object;
+        for sub_item in &mut self.sub_items {
+            if is_object && sub_item.nullable {
+                sub_item.nullable = false;
+                sub_item.optional = true;
+            }
+            sub_item.null_as_optional();
+        }
+    }
+
+    /// Folds another observation of the same value into this node. Object fields missing from
+    /// either observation become optional, and `null` observations make the node nullable.
+    /// Observations of conflicting types become members of a union, at the root so do samples
+    /// of different shapes.
+    pub(crate) fn merge(self, other: TypeScriptNode) -> TypeScriptNode {
+        self.merge_observation(other, false)
+    }
+
+    /// Merges two observations, `repeated` when they are different values of the tree rather than
+    /// the same value seen in different samples
+    fn merge_observation(self, other: TypeScriptNode, repeated: bool) -> TypeScriptNode {
+        let occurrences = if repeated {
+            self.occurrences + other.occurrences
+        } else {
+            self.occurrences.max(other.occurrences)
+        };
+        let mut merged = self.merge_values(other, repeated);
+        merged.occurrences = occurrences;
+        merged
+    }
+
+    fn merge_values(mut self, mut other: TypeScriptNode, repeated: bool) -> TypeScriptNode {
+        let either_union = self.type_signature == TypeScriptPrimativeType::Union
+            || other.type_signature == TypeScriptPrimativeType::Union;
+        let same = if self.root_node {
+            self.has_shape_of(&other)
+        } else {
+            self.has_type_of(&other)
+        };
+        if either_union || !same {
+            return self.merge_into_union(other, repeated);
+        }
+        add_sources(&mut self.sources, std::mem::take(&mut other.sources));
+        match (&self.type_signature, &other.type_signature) {
+            (TypeScriptPrimativeType::Null, TypeScriptPrimativeType::Null) => (),
+            (TypeScriptPrimativeType::Null, _) => {
+                other.name = self.name;
+                other.wire_name = self.wire_name;
+                other.root_node = self.root_node;
+                other.optional |= self.optional;
+                other.nullable = true;
+                add_sources(&mut other.sources, self.sources);
+                return other;
+            }
+            (_, TypeScriptPrimativeType::Null) => self.nullable = true,
+            // an object where a recursive type refers to itself is taken to be another instance
+            // of the type
+            (TypeScriptPrimativeType::Object, TypeScriptPrimativeType::Object)
+                if self.is_self_reference() || other.is_self_reference() =>
+            {
+                self.recursion = Some(Recursion::Reference);
+                self.sub_items.clear();
+            }
+            (TypeScriptPrimativeType::Object, TypeScriptPrimativeType::Object) => {
+                let mut other_fields = other.sub_items;
+                let mut fields = Vec::new();
+                for mut field in std::mem::take(&mut self.sub_items) {
+                    match other_fields.iter().position(|o| o.name == field.name) {
+                        Some(index) => fields
+                            .push(field.merge_observation(other_fields.remove(index), repeated)),
+                        None => {
+                            field.optional = true;
+                            fields.push(field);
+                        }
+                    }
+                }
+                for mut field in other_fields {
+                    field.optional = true;
+                    fields.push(field);
+                }
+                self.sub_items = fields;
+            }
+            (TypeScriptPrimativeType::Array, TypeScriptPrimativeType::Array) => {
+                let mut alternatives = self.take_elements();
+                let mut others = other.take_elements();
+                // elements of the other sample are tagged as these were, or the other way round
+                if let Some(tag) =
+                    Self::tag_field(&alternatives).or_else(|| Self::tag_field(&others))
+                {
+                    for element in alternatives.iter_mut().chain(others.iter_mut()) {
+                        element.mark_tag(&tag);
+                    }
+                }
+                for element in others {
+                    Self::add_alternative(&mut alternatives, element, repeated);
+                }
+                self.set_elements(Self::consolidate_elements(alternatives, repeated));
+            }
+            (current, observed) if current == observed => {
+                for example in other.examples {
+                    if self.examples.len() < MAX_EXAMPLES && !self.examples.contains(&example) {
+                        self.examples.push(example);
+                    }
+                }
+                self.string_values.absorb(&other.string_values);
+                self.literals = match (self.literals.take(), other.literals) {
+                    (Some(literals), Some(other_literals)) => Some(
+                        literals
+                            .into_iter()
+                            .chain(other_literals)
+                            .sorted()
+                            .dedup()
+                            .collect(),
+                    ),
+                    _ => None,
+                };
+                if self.string_format != other.string_format {
+                    self.string_format = None;
+                }
+                self.integer &= other.integer;
+                self.big |= other.big;
+            }
+            // a value which wasn't walked describes any other
+            (TypeScriptPrimativeType::Unknown, _) => (),
+            (_, TypeScriptPrimativeType::Unknown) => {
+                other.name = self.name;
+                other.wire_name = self.wire_name;
+                other.root_node = self.root_node;
+                other.optional |= self.optional;
+                other.nullable |= self.nullable;
+                add_sources(&mut other.sources, self.sources);
+                return other;
+            }
+            // conflicting types were merged into a union above
+            _ => (),
+        }
+        self.optional |= other.optional;
+        self.nullable |= other.nullable;
+        self
+    }
+
+    /// Whether two observations describe the same type, rather than alternatives: they have the
+    /// same type, and objects share a key unless either is empty
+    fn has_shape_of(&self, other: &TypeScriptNode) -> bool {
+        match (&self.type_signature, &other.type_signature) {
+            (TypeScriptPrimativeType::Null, _) | (_, TypeScriptPrimativeType::Null) => true,
+            (TypeScriptPrimativeType::Unknown, _) | (_, TypeScriptPrimativeType::Unknown) => true,
+            (TypeScriptPrimativeType::Object, TypeScriptPrimativeType::Object) => {
+                self.sub_items.is_empty()
+                    || other.sub_items.is_empty()
+                    || self.shares_keys_with(other)
+            }
+            (current, observed) => current == observed,
+        }
+    }
+
+    /// Whether two observations have the same type, or one of them describes any type
+    fn has_type_of(&self, other: &TypeScriptNode) -> bool {
+        match (&self.type_signature, &other.type_signature) {
+            (TypeScriptPrimativeType::Null, _) | (_, TypeScriptPrimativeType::Null) => true,
+            (TypeScriptPrimativeType::Unknown, _) | (_, TypeScriptPrimativeType::Unknown) => true,
+            (current, observed) => current == observed,
+        }
+    }
+
+    /// Merges two observations of different types, or root observations of different shapes,
+    /// into a union of them, merging each into a member of the same type, or shape at the
+    /// root, where there is one
+    fn merge_into_union(mut self, mut other: TypeScriptNode, repeated: bool) -> TypeScriptNode {
+        // a value which wasn't walked describes any other, union or not
+        if other.type_signature == TypeScriptPrimativeType::Unknown {
+            std::mem::swap(&mut self, &mut other);
+        }
+        if self.type_signature == TypeScriptPrimativeType::Unknown {
+            self.optional |= other.optional;
+            self.nullable |= other.nullable;
+            add_sources(&mut self.sources, other.sources);
+            return self;
+        }
+        let mut union = if self.type_signature == TypeScriptPrimativeType::Union {
+            self
+        } else {
+            let mut union = TypeScriptNode::new(
+                TypeScriptPrimativeType::Union,
+                false,
+                false,
+                false,
+                self.root_node,
+            );
+            union.name = self.name.clone();
+            union.wire_name = self.wire_name.clone();
+            union.add_member(self, repeated);
+            union
+        };
+        if other.type_signature == TypeScriptPrimativeType::Union {
+            add_sources(&mut union.sources, other.sources);
+            union.optional |= other.optional;
+            union.nullable |= other.nullable;
+            for member in other.sub_items {
+                union.add_member(member, repeated);
+            }
+        } else {
+            union.add_member(other, repeated);
+        }
+        union
+    }
+
+    fn add_member(&mut self, mut member: TypeScriptNode, repeated: bool) {
+        add_sources(&mut self.sources, member.sources.iter().cloned());
+        self.optional |= std::mem::take(&mut member.optional);
+        self.nullable |= std::mem::take(&mut member.nullable);
+        if member.type_signature == TypeScriptPrimativeType::Null {
+            self.nullable = true;
+            return;
+        }
+        member.root_node = false;
+        let root_node = self.root_node;
+        let position = self.sub_items.iter().position(|m| {
+            if root_node {
+                m.has_shape_of(&member)
+            } else {
+                m.type_signature == member.type_signature
+            }
+        });
+        match position {
+            Some(index) => {
+                let existing = self.sub_items.remove(index);
+                self.sub_items
+                    .insert(index, existing.merge_observation(member, repeated));
+            }
+            None => self.sub_items.push(member),
+        }
+    }
+
+    /// Where in the input the node was observed
+    #[cfg(feature = "cli")]
+    pub(crate) fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    /// Attributes every observation in the tree which has no input file yet to `file`
+    #[cfg(feature = "cli")]
+    pub(crate) fn attribute_sources_to(&mut self, file: &Arc<str>) {
+        for source in &mut self.sources {
+            source.file.get_or_insert_with(|| file.clone());
+        }
+        for sub_item in &mut self.sub_items {
+            sub_item.attribute_sources_to(file);
+        }
+    }
+
+    pub(crate) fn hash(&self) -> Fingerprint {
+        self.hash
+    }
+
+    /// Names of the common object types which get their own declaration
+    pub(crate) fn type_names(&self, options: &RenderOptions) -> HashMap<Fingerprint, String> {
+        // the hashes of a tree which isn't squashed are never calculated, so all its objects
+        // would look alike
+        if self.hash == 0 {
+            return HashMap::new();
+        }
+        let mut counts = HashMap::new();
+        self.count_hashes(&mut counts);
+        let mut common_types = Vec::new();
+        self.collect_common_types(&counts, options, &mut common_types);
+        // objects whose hashes collide are kept inline, each with its own fields, rather than
+        // declared once with the fields of the first
+        if let Some(diagnostics) = &options.diagnostics {
+            diagnostics.inspect(self);
+        }
+        let colliding = self.colliding_hashes();
+        if !colliding.is_empty() {
+            if options.diagnostics.is_none() {
+                event!(
+                    Level::WARN,
+                    types = colliding.len(),
+                    "distinct object types share a hash, keeping them inline"
+                );
+            }
+            common_types.retain(|hash| !colliding.contains(hash));
+        }
+        let mut type_names = naming::name_types(
+            self,
+            &options.root_name,
+            options.naming,
+            options.type_namer.as_deref(),
+            &common_types,
+        );
+        // the references to a recursive root refer to it by the root name
+        if self.recursion == Some(Recursion::Type) {
+            type_names.insert(self.hash, options.root_name.clone());
+        }
+        type_names
+    }
+
+    /// The first node of every common object type with its name, in naming order, so each
+    /// type comes after the types it refers to
+    pub(crate) fn named_types<'a>(
+        &'a self,
+        type_names: &'a HashMap<Fingerprint, String>,
+    ) -> Vec<(&'a str, &'a TypeScriptNode)> {
+        let mut named = Vec::new();
+        self.collect_named_types(type_names, &mut named);
+        named
+    }
+
+    fn collect_named_types<'a>(
+        &'a self,
+        type_names: &'a HashMap<Fingerprint, String>,
+        named: &mut Vec<(&'a str, &'a TypeScriptNode)>,
+    ) {
+        if self.is_self_reference() {
+            return;
+        }
+        if self.type_signature == TypeScriptPrimativeType::Object && !self.is_recursive_root() {
+            if let Some(name) = type_names.get(&self.hash) {
+                if named.iter().any(|(existing, _)| *existing == name) {
+                    return;
+                }
+                for sub_item in &self.sub_items {
+                    sub_item.collect_named_types(type_names, named);
+                }
+                named.push((name, self));
+                return;
+            }
+        }
+        for sub_item in &self.sub_items {
+            sub_item.collect_named_types(type_names, named);
+        }
+    }
+
+    /// The hashes of the object types seen often enough to be declared, and with too many
+    /// fields to be kept inline, in the order the renderer reaches them. Recursive types and
+    /// the object members of a root union are always declared.
+    fn collect_common_types(
+        &self,
+        counts: &HashMap<Fingerprint, usize>,
+        options: &RenderOptions,
+        common_types: &mut Vec<Fingerprint>,
+    ) {
+        if self.is_self_reference()
+            || self.type_signature == TypeScriptPrimativeType::Object
+                && common_types.contains(&self.hash)
+        {
+            return;
+        }
+        for sub_item in &self.sub_items {
+            sub_item.collect_common_types(counts, options, common_types);
+            // the members of a root union are told apart by name
+            if self.type_signature == TypeScriptPrimativeType::Union
+                && self.root_node
+                && sub_item.type_signature == TypeScriptPrimativeType::Object
+                && !sub_item.sub_items.is_empty()
+                && sub_item.reference.is_none()
+                && !common_types.contains(&sub_item.hash)
+            {
+                common_types.push(sub_item.hash);
+            }
+        }
+        let recursive = self.recursion == Some(Recursion::Type);
+        if self.type_signature == TypeScriptPrimativeType::Object
+            && self.reference.is_none()
+            && !self.is_recursive_root()
+            && (recursive
+                || counts
+                    .get(&self.hash)
+                    .is_some_and(|count| *count >= options.extract_min_occurrences.max(2))
+                    && options
+                        .inline_max_fields
+                        .is_none_or(|max_fields| self.sub_items.len() > max_fields))
+        {
+            common_types.push(self.hash);
+        }
+    }
+
+    /// Whether the node is a root which objects nested in it refer back to, declared under the
+    /// root name
+    fn is_recursive_root(&self) -> bool {
+        self.root_node && self.recursion == Some(Recursion::Type)
+    }
+
+    /// Folds the objects nested in an object which have its keys, with fields of the same
+    /// types, into it, leaving references to it in their place. A comment whose `replies` hold
+    /// comments becomes one type referring to itself, rather than one type per level of
+    /// replies.
+    pub(crate) fn fold_recursive_objects(&mut self) {
+        if self.type_signature == TypeScriptPrimativeType::Object
+            && self.recursion.is_none()
+            && !self.sub_items.is_empty()
+        {
+            let fields = self.field_types();
+            let mut instances = Vec::new();
+            for sub_item in &mut self.sub_items {
+                sub_item.take_instances(&fields, &mut instances);
+            }
+            if !instances.is_empty() {
+                let placeholder =
+                    TypeScriptNode::new(TypeScriptPrimativeType::Null, false, false, false, false);
+                let mut folded = std::mem::replace(self, placeholder);
+                for mut instance in instances {
+                    instance.fold_recursive_objects();
+                    folded = folded.merge(instance);
+                }
+                folded.recursion = Some(Recursion::Type);
+                *self = folded;
+            }
+        }
+        for sub_item in &mut self.sub_items {
+            sub_item.fold_recursive_objects();
+        }
+    }
+
+    /// The names of the fields of an object with their types
+    fn field_types(&self) -> Vec<(Option<Arc<str>>, TypeScriptPrimativeType)> {
+        self.sub_items
+            .iter()
+            .map(|field| (field.name.clone(), field.type_signature.clone()))
+            .collect()
+    }
+
+    /// Takes the objects of the subtree which have the given fields out of it, each replaced by
+    /// a reference to the type they are folded into
+    fn take_instances(
+        &mut self,
+        fields: &[(Option<Arc<str>>, TypeScriptPrimativeType)],
+        instances: &mut Vec<TypeScriptNode>,
+    ) {
+        let describes_any = |type_signature: &TypeScriptPrimativeType| {
+            matches!(
+                type_signature,
+                TypeScriptPrimativeType::Null | TypeScriptPrimativeType::Unknown
+            )
+        };
+        let is_instance = self.type_signature == TypeScriptPrimativeType::Object
+            && self.recursion.is_none()
+            && self.sub_items.len() == fields.len()
+            && self.sub_items.iter().all(|field| {
+                fields.iter().any(|(name, type_signature)| {
+                    *name == field.name
+                        && (*type_signature == field.type_signature
+                            || describes_any(type_signature)
+                            || describes_any(&field.type_signature))
+                })
+            });
+        if is_instance {
+            let mut reference = TypeScriptNode::new(
+                TypeScriptPrimativeType::Object,
+                self.optional,
+                self.nullable,
+                false,
+                false,
+            );
+            reference.name = self.name.clone();
+            reference.wire_name = self.wire_name.clone();
+            reference.recursion = Some(Recursion::Reference);
+            instances.push(std::mem::replace(self, reference));
+            return;
+        }
+        for sub_item in &mut self.sub_items {
+            sub_item.take_instances(fields, instances);
+        }
+    }
+
+    /// Narrows string nodes to a union of their literal values when at most `limit` distinct
+    /// values were observed. Values are pooled across every occurrence of the same field in
+    /// objects of the same shape, as those end up sharing a type.
+    #[cfg(feature = "cli")]
+    pub(crate) fn infer_literals(&mut self, limit: usize) {
+        let mut pooled = HashMap::new();
+        self.pool_string_values("", "", &mut pooled);
+        self.assign_literals("", "", &pooled, limit);
+    }
+
+    #[cfg(feature = "cli")]
+    fn pool_string_values(
+        &self,
+        owner: &str,
+        path: &str,
+        pooled: &mut HashMap<(String, String), StringValues>,
+    ) {
+        match self.type_signature {
+            TypeScriptPrimativeType::String => pooled
+                .entry((owner.to_string(), path.to_string()))
+                .or_default()
+                .absorb(&self.string_values),
+            TypeScriptPrimativeType::Object => {
+                let shape = self.shape();
+                for sub_item in &self.sub_items {
+                    sub_item.pool_string_values(
+                        &shape,
+                        sub_item.name.as_deref().unwrap_or_default(),
+                        pooled,
+                    );
+                }
+            }
+            TypeScriptPrimativeType::Array => {
+                let element_path = format!("{}[*]", path);
+                for sub_item in &self.sub_items {
+                    sub_item.pool_string_values(owner, &element_path, pooled);
+                }
+            }
+            TypeScriptPrimativeType::Union => {
+                for sub_item in &self.sub_items {
+                    sub_item.pool_string_values(owner, path, pooled);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn assign_literals(
+        &mut self,
+        owner: &str,
+        path: &str,
+        pooled: &HashMap<(String, String), StringValues>,
+        limit: usize,
+    ) {
+        match self.type_signature {
+            // the tag of an object in a tagged union keeps its value
+            TypeScriptPrimativeType::String if self.literals.is_some() => (),
+            TypeScriptPrimativeType::String => {
+                self.literals = pooled[&(owner.to_string(), path.to_string())].literals(limit);
+            }
+            TypeScriptPrimativeType::Object => {
+                let shape = self.shape();
+                for sub_item in &mut self.sub_items {
+                    let name = sub_item.name.clone().unwrap_or_default();
+                    sub_item.assign_literals(&shape, &name, pooled, limit);
+                }
+            }
+            TypeScriptPrimativeType::Array => {
+                let element_path = format!("{}[*]", path);
+                for sub_item in &mut self.sub_items {
+                    sub_item.assign_literals(owner, &element_path, pooled, limit);
+                }
+            }
+            TypeScriptPrimativeType::Union => {
+                for sub_item in &mut self.sub_items {
+                    sub_item.assign_literals(owner, path, pooled, limit);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Merges the object elements of an array which share some keys into a single object type,
+    /// with the keys missing from some elements made optional. Arrays whose object elements all
+    /// have the same shape are left alone, as are elements whose shared keys have conflicting
+    /// types, which stay members of a union.
+    fn consolidate_elements(elements: Vec<TypeScriptNode>, repeated: bool) -> Vec<TypeScriptNode> {
+        let object_shapes = elements
+            .iter()
+            .filter(|element| element.type_signature == TypeScriptPrimativeType::Object)
+            .map(TypeScriptNode::shape)
+            .unique()
+            .count();
+        if object_shapes < 2 {
+            return elements;
+        }
+        let mut consolidated: Vec<TypeScriptNode> = Vec::new();
+        for element in elements {
+            let target = consolidated.iter().position(|existing| {
+                element.type_signature == TypeScriptPrimativeType::Object
+                    && existing.type_signature == TypeScriptPrimativeType::Object
+                    && existing.shares_keys_with(&element)
+                    && existing.can_merge_with(&element)
+            });
+            match target {
+                Some(index) => {
+                    let existing = consolidated.remove(index);
+                    consolidated.insert(index, existing.merge_observation(element, repeated));
+                }
+                None => consolidated.push(element),
+            }
+        }
+        consolidated
+    }
+
+    /// Merges an element into the alternative of the same shape, if there is one
+    fn add_alternative(
+        alternatives: &mut Vec<TypeScriptNode>,
+        element: TypeScriptNode,
+        repeated: bool,
+    ) {
+        let shape = element.shape();
+        match alternatives.iter().position(|e| e.shape() == shape) {
+            Some(index) => {
+                let existing = alternatives.remove(index);
+                alternatives.insert(index, existing.merge_observation(element, repeated));
+            }
+            None => alternatives.push(element),
+        }
+    }
+
+    /// The types the elements of an array node were observed with, the members of its element
+    /// when that is a union
+    pub(crate) fn elements(&self) -> &[TypeScriptNode] {
+        match self.sub_items.as_slice() {
+            [element] if element.type_signature == TypeScriptPrimativeType::Union => {
+                &element.sub_items
+            }
+            elements => elements,
+        }
+    }
+
+    fn take_elements(&mut self) -> Vec<TypeScriptNode> {
+        let mut elements = std::mem::take(&mut self.sub_items);
+        match elements.as_slice() {
+            [element] if element.type_signature == TypeScriptPrimativeType::Union => {
+                elements.pop().unwrap().sub_items
+            }
+            _ => elements,
+        }
+    }
+
+    /// The field the object elements of an array are tagged with: the given discriminator if
+    /// they hold it, otherwise the first of the usual tag fields, when the objects have
+    /// different shapes. Either has to hold at least two different strings.
+    fn discriminator(elements: &[TypeScriptNode], discriminator: Option<&str>) -> Option<String> {
+        let objects: Vec<&TypeScriptNode> = elements
+            .iter()
+            .filter(|element| element.type_signature == TypeScriptPrimativeType::Object)
+            .collect();
+        let is_tag = |field: &str| {
+            objects
+                .iter()
+                .filter_map(|object| object.tag(field))
+                .unique()
+                .count()
+                > 1
+        };
+        if let Some(field) = discriminator {
+            return Some(field.to_string()).filter(|field| is_tag(field));
+        }
+        if objects
+            .iter()
+            .map(|object| object.fields_shape())
+            .unique()
+            .count()
+            < 2
+        {
+            return None;
+        }
+        DISCRIMINATORS
+            .iter()
+            .find(|field| is_tag(field))
+            .map(|field| field.to_string())
+    }
+
+    /// The field the object elements of an array were tagged with, if they were
+    fn tag_field(elements: &[TypeScriptNode]) -> Option<String> {
+        elements
+            .iter()
+            .filter(|element| element.type_signature == TypeScriptPrimativeType::Object)
+            .flat_map(|element| &element.sub_items)
+            .find(|field| {
+                field.type_signature == TypeScriptPrimativeType::String
+                    && field
+                        .literals
+                        .as_ref()
+                        .is_some_and(|literals| literals.len() == 1)
+            })
+            .and_then(|field| field.name.as_deref().map(str::to_string))
+    }
+
+    /// The value an object holds in the string field `field`
+    fn tag(&self, field: &str) -> Option<&str> {
+        self.sub_items
+            .iter()
+            .find(|sub_item| sub_item.name.as_deref() == Some(field))
+            .filter(|sub_item| sub_item.type_signature == TypeScriptPrimativeType::String)
+            .and_then(|sub_item| sub_item.examples.first())
+            .and_then(Value::as_str)
+    }
+
+    /// Narrows the tag field of an object to the literal it holds
+    fn mark_tag(&mut self, field: &str) {
+        if let Some(tag) = self.tag(field).map(str::to_string) {
+            if let Some(sub_item) = self
+                .sub_items
+                .iter_mut()
+                .find(|sub_item| sub_item.name.as_deref() == Some(field))
+            {
+                sub_item.literals = Some(vec![tag]);
+            }
+        }
+    }
+
+    /// Makes the alternatives the element of an array node, as a union when there are several
+    fn set_elements(&mut self, mut alternatives: Vec<TypeScriptNode>) {
+        self.sub_items = if alternatives.len() > 1 {
+            let mut union =
+                TypeScriptNode::new(TypeScriptPrimativeType::Union, false, false, false, false);
+            union.occurrences = alternatives.iter().map(|a| a.occurrences).sum();
+            union.sub_items = std::mem::take(&mut alternatives);
+            vec![union]
+        } else {
+            alternatives
+        };
+    }
+
+    /// Unifies objects held by fields of the same name, or by arrays of the same name, whose
+    /// keys overlap and whose types agree, so they get squashed into one type with the fields
+    /// missing from some of them optional. Objects nested in an object of the same name are
+    /// left alone, unifying them would nest the tree one level deeper every round.
+    pub(crate) fn unify_repeated_objects(&mut self) {
+        // unifying outer objects merges the objects nested in them, which can leave those
+        // to be unified with objects elsewhere
+        for _ in 0..MAX_UNIFY_ROUNDS {
+            let mut candidates = HashMap::<String, Vec<TypeScriptNode>>::new();
+            self.collect_repeated_objects(None, &mut Vec::new(), &mut candidates);
+            candidates.retain(|_, objects| {
+                objects
+                    .iter()
+                    .map(TypeScriptNode::fields_shape)
+                    .unique()
+                    .count()
+                    > 1
+            });
+            if candidates.is_empty() {
+                return;
+            }
+            let unified = candidates
+                .into_iter()
+                .map(|(name, objects)| {
+                    let mut merged: Vec<TypeScriptNode> = Vec::new();
+                    for object in objects {
+                        match merged.iter().position(|existing| {
+                            existing.shares_keys_with(&object) && existing.can_merge_with(&object)
+                        }) {
+                            Some(index) => {
+                                let existing = merged.remove(index);
+                                merged.insert(index, existing.merge(object));
+                            }
+                            None => merged.push(object),
+                        }
+                    }
+                    (name, merged)
+                })
+                .collect();
+            if !self.replace_repeated_objects(None, &mut Vec::new(), &unified) {
+                return;
+            }
+        }
+    }
+
+    /// The name an object is known by, its field name or that of the array holding it
+    fn holder_name<'a>(&'a self, array_name: Option<&'a str>) -> Option<&'a str> {
+        if self.is_array {
+            None
+        } else {
+            self.name.as_deref().or(array_name)
+        }
+    }
+
+    /// The name the object is unified by, unless an object it is nested in goes by the same
+    /// one, as listed in `ancestors`
+    fn unified_name<'a>(
+        &'a self,
+        array_name: Option<&'a str>,
+        ancestors: &[String],
+    ) -> Option<&'a str> {
+        if self.type_signature != TypeScriptPrimativeType::Object || self.root_node {
+            return None;
+        }
+        self.holder_name(array_name)
+            .filter(|name| !ancestors.iter().any(|ancestor| ancestor == name))
+    }
+
+    fn collect_repeated_objects(
+        &self,
+        array_name: Option<&str>,
+        ancestors: &mut Vec<String>,
+        candidates: &mut HashMap<String, Vec<TypeScriptNode>>,
+    ) {
+        let unified_name = self.unified_name(array_name, ancestors);
+        if let Some(name) = unified_name {
+            candidates
+                .entry(name.to_string())
+                .or_default()
+                .push(self.clone());
+            ancestors.push(name.to_string());
+        }
+        let array_name = if self.is_array || self.type_signature == TypeScriptPrimativeType::Union {
+            self.name.as_deref().or(array_name)
+        } else {
+            None
+        };
+        for sub_item in &self.sub_items {
+            sub_item.collect_repeated_objects(array_name, ancestors, candidates);
+        }
+        if unified_name.is_some() {
+            ancestors.pop();
+        }
+    }
+
+    /// Replaces objects with their unified type, returning whether any changed
+    fn replace_repeated_objects(
+        &mut self,
+        array_name: Option<&str>,
+        ancestors: &mut Vec<String>,
+        unified: &HashMap<String, Vec<TypeScriptNode>>,
+    ) -> bool {
+        let unified_name = self.unified_name(array_name, ancestors).map(str::to_string);
+        if let Some(name) = &unified_name {
+            let replacement = unified.get(name).and_then(|merged| {
+                merged.iter().find(|candidate| {
+                    candidate.shares_keys_with(self) && candidate.can_merge_with(self)
+                })
+            });
+            if let Some(replacement) = replacement {
+                if replacement.fields_shape() != self.fields_shape() {
+                    self.sub_items = replacement.sub_items.clone();
+                    return true;
+                }
+            }
+            ancestors.push(name.clone());
+        }
+        let array_name = if self.is_array || self.type_signature == TypeScriptPrimativeType::Union {
+            self.name.clone().or_else(|| array_name.map(Arc::from))
+        } else {
+            None
+        };
+        let mut changed = false;
+        for sub_item in &mut self.sub_items {
+            changed |= sub_item.replace_repeated_objects(array_name.as_deref(), ancestors, unified);
+        }
+        if unified_name.is_some() {
+            ancestors.pop();
+        }
+        changed
+    }
+
+    fn shares_keys_with(&self, other: &TypeScriptNode) -> bool {
+        self.sub_items
+            .iter()
+            .any(|field| other.sub_items.iter().any(|o| o.name == field.name))
+    }
+
+    /// Whether merging would not have to drop a conflicting type
+    fn can_merge_with(&self, other: &TypeScriptNode) -> bool {
+        match (&self.type_signature, &other.type_signature) {
+            (TypeScriptPrimativeType::Null, _) | (_, TypeScriptPrimativeType::Null) => true,
+            (TypeScriptPrimativeType::Unknown, _) | (_, TypeScriptPrimativeType::Unknown) => true,
+            (TypeScriptPrimativeType::Object, TypeScriptPrimativeType::Object) => {
+                self.sub_items.iter().all(|field| {
+                    other
+                        .sub_items
+                        .iter()
+                        .find(|o| o.name == field.name)
+                        .is_none_or(|o| field.can_merge_with(o))
+                })
+            }
+            // objects tagged differently are different kinds of object
+            (TypeScriptPrimativeType::String, TypeScriptPrimativeType::String) => {
+                self.literals.is_none()
+                    || other.literals.is_none()
+                    || self.literals == other.literals
+            }
+            (current, observed) => current == observed,
+        }
+    }
+
+    /// Structural description of the node, independent of hashing, used to avoid accumulating
+    /// elements of a shape which was already observed when merging arrays. Like the hash it
+    /// doesn't depend on the order the fields were observed in.
+    fn shape(&self) -> String {
+        format!(
+            "{}{}{}{}{}{}{}({})",
+            self.type_signature.as_str(),
+            if self.is_self_reference() {
+                "@self"
+            } else {
+                ""
+            },
+            self.string_format
+                .map(|format| format!("@{}", format.as_str()))
+                .unwrap_or_default(),
+            if self.integer { "@integer" } else { "" },
+            if self.big { "@big" } else { "" },
+            self.literals
+                .as_ref()
+                .map(|literals| format!("={}", literals.join("|")))
+                .unwrap_or_default(),
+            if self.nullable { "|null" } else { "" },
+            self.fields_shape()
+        )
+    }
+
+    /// The shapes of the members, sorted by name, names quoted so that keys holding `:` or `,`
+    /// can't pass for other fields
+    fn fields_shape(&self) -> String {
+        self.sub_items
+            .iter()
+            .map(|sub_item| {
+                format!(
+                    "{}{}:{}",
+                    serde_json::to_string(sub_item.name.as_deref().unwrap_or_default()).unwrap(),
+                    if sub_item.optional { "?" } else { "" },
+                    sub_item.shape()
+                )
+            })
+            .sorted()
+            .join(",")
+    }
+
+    /// The string literals the node was narrowed to by `infer_literals`
+    #[cfg(feature = "cli")]
+    pub(crate) fn literals(&self) -> Option<&[String]> {
+        self.literals.as_deref()
+    }
+
+    /// The format recognised in the values of a string node
+    #[cfg(feature = "cli")]
+    pub(crate) fn string_format(&self) -> Option<StringFormat> {
+        self.string_format
+    }
+
+    /// Whether every number observed for the node was an integer
+    #[cfg(feature = "cli")]
+    pub(crate) fn is_integer(&self) -> bool {
+        self.integer
+    }
+
+    /// A value sampled from the input for primitive nodes
+    #[cfg(feature = "cli")]
+    pub(crate) fn example(&self) -> Option<&Value> {
+        self.examples.first()
     }
 
     pub fn new(
-        lookup_table: Arc<Mutex<HashMap<u64, usize>>>,
         type_name: TypeScriptPrimativeType,
         optional: bool,
         nullable: bool,
@@ -84,7 +1472,6 @@ impl TypeScriptNode {
         root_node: bool,
     ) -> Self {
         TypeScriptNode {
-            lookup_table,
             name: None,
             nullable,
             optional,
@@ -93,174 +1480,769 @@ impl TypeScriptNode {
             sub_items: Vec::new(),
             type_signature: type_name,
             hash: 0,
+            examples: Vec::new(),
+            wire_name: None,
+            sources: Vec::new(),
+            string_values: StringValues::default(),
+            literals: None,
+            string_format: None,
+            integer: false,
+            big: false,
+            reference: None,
+            recursion: None,
+            occurrences: 1,
         }
     }
 
-    fn newline_if_parent_not_array_node(array_node: bool) -> String {
-        if !array_node {
-            String::from("\n")
-        } else {
-            String::new()
+    /// Whether a rendered type is a union which needs parentheses before `[]` is appended, or
+    /// Flow's `?` prepended
+    pub(crate) fn has_top_level_union(type_string: &str) -> bool {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in type_string.chars() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '{' | '(' | '[' | '<' => depth += 1,
+                '}' | ')' | ']' | '>' => depth -= 1,
+                '|' if depth == 0 => return true,
+                _ => (),
+            }
         }
+        false
     }
 
-    fn semicolon_if_parent_array_node(array_node: bool) -> String {
-        if array_node {
-            String::from(";")
-        } else {
-            String::new()
-        }
+    #[cfg(test)]
+    pub(crate) fn to_type_string(node: &TypeScriptNode) -> String {
+        Self::to_type_string_with_options(node, &RenderOptions::default())
     }
 
-    fn space_if_parent_not_root_node(root_node: bool) -> String {
-        if !root_node {
-            String::from(" ")
-        } else {
-            String::new()
-        }
+    /// Renders the tree as a `{root_name}` declaration followed by the declarations of the
+    /// common types
+    pub(crate) fn to_type_string_with_options(
+        node: &TypeScriptNode,
+        options: &RenderOptions,
+    ) -> String {
+        Self::declarations(node, options)
+            .into_iter()
+            .map(|declaration| declaration.text)
+            .join("\n")
     }
 
-    fn string_is_alphanumeric(string: &str) -> bool {
-        string.chars().all(|c| c.is_alphanumeric() || c == '_')
+    /// Renders the tree as one file per declaration, `{name}.ts`, exporting it and importing the
+    /// declarations it refers to, with an `index.ts` re-exporting them all
+    #[cfg(feature = "cli")]
+    pub(crate) fn to_type_files(
+        node: &TypeScriptNode,
+        options: &RenderOptions,
+    ) -> Vec<(String, String)> {
+        let options = RenderOptions {
+            export: true,
+            ..options.clone()
+        };
+        let terminator = options.format.terminator();
+        let declarations = Self::declarations(node, &options);
+        let mut index = String::new();
+        let mut files = Vec::new();
+        for declaration in declarations {
+            let mut file = String::new();
+            for reference in &declaration.references {
+                let _ = writeln!(
+                    file,
+                    "import type {{ {} }} from \"./{}\"{}",
+                    reference, reference, terminator
+                );
+            }
+            if !file.is_empty() {
+                file.push('\n');
+            }
+            file.push_str(&declaration.text);
+            let _ = writeln!(
+                index,
+                "export * from \"./{}\"{}",
+                declaration.name, terminator
+            );
+            files.push((format!("{}.ts", declaration.name), file));
+        }
+        files.push(("index.ts".to_string(), index));
+        files
     }
 
-    pub(crate) fn to_type_string(node: TypeScriptNode, array_node: bool) -> String {
-        let mut type_output_cache = HashMap::<u64, TypeOutputCacheEntry>::new();
-        let mut type_string = String::new();
-        type_string.push_str("type DefaultType = ");
-        type_string.push_str(&Self::to_type_string_helper(
-            node,
-            array_node,
-            0,
-            &mut type_output_cache,
-        ));
-        type_output_cache
+    /// The declarations of the tree: the root's, the common types' and those of the types they
+    /// use, such as branded strings
+    fn declarations(node: &TypeScriptNode, options: &RenderOptions) -> Vec<Declaration> {
+        let root_name = options.root_name.as_str();
+        let format = &options.format;
+        let context = RenderContext::new(node, options);
+        let mut types = vec![(root_name, node)];
+        types.extend(node.named_types(&context.type_names));
+        let date_declaration = options
+            .date_type
+            .as_ref()
+            .filter(|_| node.uses_date_format())
+            .and_then(DateType::declaration);
+        let mut string_formats = BTreeSet::new();
+        node.collect_string_formats(&mut string_formats);
+        let base64_declaration = options
+            .base64_type
+            .as_deref()
+            .filter(|_| string_formats.contains(&StringFormat::Base64))
+            .map(formats::base64_declaration);
+        let string_format_declarations = string_formats.into_iter().filter_map(|format| {
+            options
+                .string_formats
+                .and_then(|mode| mode.declaration(format))
+        });
+        let integer_declaration = options
+            .number_mode
+            .declaration()
+            .filter(|_| node.uses_integer());
+        let fallback_declaration = options
+            .fallback_type
+            .declaration()
+            .filter(|_| node.uses_fallback(options));
+        let helpers: Vec<Declaration> = date_declaration
             .into_iter()
-            .sorted()
-            .for_each(|(_, value)| {
-                type_string.push_str(
-                    format!("\ntype {} = {}\n", &value.type_name, &value.output).as_str(),
-                );
-            });
-        type_string
+            .chain(integer_declaration)
+            .chain(fallback_declaration)
+            .chain(string_format_declarations)
+            .chain(base64_declaration)
+            .map(|declaration| {
+                #[cfg(feature = "cli")]
+                let name = declaration["type ".len()..]
+                    .split(' ')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let mut text = options.declaration_prefix().to_string();
+                if format.semicolons {
+                    text.push_str(&declaration);
+                } else {
+                    text.push_str(&declaration.replace(";\n", "\n"));
+                }
+                Declaration {
+                    #[cfg(feature = "cli")]
+                    name,
+                    text,
+                    #[cfg(feature = "cli")]
+                    references: BTreeSet::new(),
+                }
+            })
+            .collect();
+        #[cfg(feature = "cli")]
+        let helper_names: Vec<&str> = helpers.iter().map(|helper| helper.name.as_str()).collect();
+        let mut declarations: Vec<Declaration> = types
+            .into_iter()
+            .map(|(name, type_node)| {
+                let body = type_node.render_type(0, &context, true);
+                Declaration {
+                    #[cfg(feature = "cli")]
+                    name: name.to_string(),
+                    text: Self::declaration(name, type_node, &body, options),
+                    #[cfg(feature = "cli")]
+                    references: type_node.references(name, &context, &helper_names),
+                }
+            })
+            .collect();
+        if options.list_alias {
+            declarations.insert(
+                1,
+                Declaration {
+                    #[cfg(feature = "cli")]
+                    name: format!("{}s", root_name),
+                    text: format!(
+                        "{}type {}s = {}{}[]{}\n",
+                        options.declaration_prefix(),
+                        root_name,
+                        if options.readonly { "readonly " } else { "" },
+                        root_name,
+                        format.terminator()
+                    ),
+                    #[cfg(feature = "cli")]
+                    references: BTreeSet::from([root_name.to_string()]),
+                },
+            );
+        }
+        declarations.extend(helpers);
+        declarations
     }
 
-    fn to_type_string_helper(
-        node: TypeScriptNode,
-        parent_array_node: bool,
-        indent_size: usize,
-        type_output_cache: &mut HashMap<u64, TypeOutputCacheEntry>,
-    ) -> String {
-        let mut type_string = String::new();
-        let mut indent_string = String::new();
-        if !node.root_node && !parent_array_node {
-            for _ in 0..indent_size {
-                indent_string.push_str("  ");
-            }
-            type_string.push_str(&indent_string)
-        }
-        match node.name {
-            Some(name) => {
-                if Self::string_is_alphanumeric(&name.clone()) {
-                    type_string.push_str(&format!("{}: ", name));
+    /// The names of the other declarations the declaration `name` of the node refers to
+    #[cfg(feature = "cli")]
+    fn references(
+        &self,
+        name: &str,
+        context: &RenderContext,
+        helper_names: &[&str],
+    ) -> BTreeSet<String> {
+        let mut references = BTreeSet::new();
+        self.collect_references(context, helper_names, true, &mut references);
+        references.remove(name);
+        references
+    }
+
+    /// Collects the names of the declarations the rendered type of the node refers to, common
+    /// types by their hash and the helper types, which only leaves render, by name
+    #[cfg(feature = "cli")]
+    fn collect_references(
+        &self,
+        context: &RenderContext,
+        helper_names: &[&str],
+        declaration_body: bool,
+        references: &mut BTreeSet<String>,
+    ) {
+        match self.type_signature {
+            TypeScriptPrimativeType::Object => {
+                if let Some(reference) = &self.reference {
+                    references.insert(reference.clone());
+                } else if let (Some(name), false) =
+                    (context.type_names.get(&self.hash), declaration_body)
+                {
+                    references.insert(name.clone());
                 } else {
-                    type_string.push_str(&format!("\"{}\": ", name))
+                    for sub_item in &self.sub_items {
+                        sub_item.collect_references(context, helper_names, false, references);
+                    }
+                }
+            }
+            TypeScriptPrimativeType::Union => {
+                for member in &self.sub_items {
+                    member.collect_references(context, helper_names, false, references);
+                }
+            }
+            TypeScriptPrimativeType::Array if !self.elements().is_empty() => {
+                for element in self.elements() {
+                    element.collect_references(context, helper_names, false, references);
+                }
+            }
+            _ => {
+                let rendered = self.render_type(0, context, false);
+                for word in rendered.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                {
+                    if helper_names.contains(&word) {
+                        references.insert(word.to_string());
+                    }
                 }
             }
-            None => (),
         }
-        match node.type_signature {
-            TypeScriptPrimativeType::Boolean => type_string.push_str("boolean"),
-            TypeScriptPrimativeType::String => type_string.push_str("string"),
-            TypeScriptPrimativeType::Number => type_string.push_str("number"),
-            TypeScriptPrimativeType::Null => type_string.push_str("null"),
-            TypeScriptPrimativeType::Object => {
-                if type_output_cache.contains_key(&node.hash) {
-                    type_string.push_str(&type_output_cache[&node.hash].type_name);
-                } else {
-                    let mut object_type_string = String::new();
-                    object_type_string.push_str(&format!(
-                        "{{{}{}",
-                        &Self::newline_if_parent_not_array_node(parent_array_node),
-                        &Self::space_if_parent_not_root_node(node.root_node)
-                    ));
-                    for o in node.sub_items {
-                        object_type_string.push_str(&format!(
-                            "{}{}{}",
-                            TypeScriptNode::to_type_string_helper(
-                                o,
-                                parent_array_node,
-                                indent_size + 1,
-                                type_output_cache,
-                            ),
-                            &Self::space_if_parent_not_root_node(parent_array_node),
-                            &Self::semicolon_if_parent_array_node(parent_array_node)
-                        ));
+    }
+
+    /// Declares `name` as `rendered`, the type of `node`, as an interface when asked for and
+    /// the type is an object
+    fn declaration(
+        name: &str,
+        node: &TypeScriptNode,
+        rendered: &str,
+        options: &RenderOptions,
+    ) -> String {
+        let format = &options.format;
+        let prefix = options.declaration_prefix();
+        let is_body = rendered.starts_with('{');
+        let brace_break = if is_body && format.brace_style == BraceStyle::NextLine {
+            "\n"
+        } else {
+            " "
+        };
+        if options.declaration_style == DeclarationStyle::Interface
+            && node.type_signature == TypeScriptPrimativeType::Object
+            && node.reference.is_none()
+            && !node.nullable
+        {
+            format!("{}interface {}{}{}\n", prefix, name, brace_break, rendered)
+        } else {
+            format!(
+                "{}type {} ={}{}{}\n",
+                prefix,
+                name,
+                brace_break,
+                rendered,
+                format.terminator()
+            )
+        }
+    }
+
+    /// Renders the type of the node at the given depth of indentation. Common object types are
+    /// referred to by name, unless rendering the body of their declaration.
+    pub(crate) fn render_type(
+        &self,
+        depth: usize,
+        context: &RenderContext,
+        declaration_body: bool,
+    ) -> String {
+        let mut type_string = String::new();
+        self.write_type(&mut type_string, depth, context, declaration_body);
+        type_string
+    }
+
+    /// Writes the type of the node to `out`, the properties of objects and the members of
+    /// unions written in place rather than rendered to strings of their own
+    fn write_type(
+        &self,
+        out: &mut String,
+        depth: usize,
+        context: &RenderContext,
+        declaration_body: bool,
+    ) {
+        let options = context.options;
+        let format = &options.format;
+        match self.type_signature {
+            TypeScriptPrimativeType::Boolean => out.push_str("boolean"),
+            TypeScriptPrimativeType::String => match (&self.literals, self.string_format) {
+                (_, Some(format)) if format.is_date() && options.date_type.is_some() => {
+                    out.push_str(&options.date_type.as_ref().unwrap().render(format))
+                }
+                (_, Some(format))
+                    if format.is_string_format() && options.string_formats.is_some() =>
+                {
+                    out.push_str(options.string_formats.unwrap().render(format))
+                }
+                (_, Some(StringFormat::Base64)) if options.base64_type.is_some() => {
+                    out.push_str(options.base64_type.as_deref().unwrap())
+                }
+                (Some(literals), _) => {
+                    for (index, literal) in literals.iter().enumerate() {
+                        if index > 0 {
+                            out.push_str(" | ");
+                        }
+                        out.push_str(&serde_json::to_string(literal).unwrap());
                     }
-                    object_type_string.push_str(&format!(
-                        "{}{}}}",
-                        &Self::space_if_parent_not_root_node(node.root_node),
-                        indent_string
-                    ));
-                    let object_type_output = object_type_string.clone();
-                    let lookup_table = node.lookup_table.lock().unwrap();
-                    if lookup_table.contains_key(&node.hash) && lookup_table[&node.hash] > 1 {
-                        let len = type_output_cache.len();
-                        let type_name = format!("DefaultType_{}", len);
-                        type_output_cache.insert(
-                            node.hash,
-                            TypeOutputCacheEntry::new(
-                                type_name.clone(),
-                                object_type_output.clone(),
-                            ),
-                        );
-                        type_string.push_str(&type_name);
-                    } else {
-                        type_string.push_str(&object_type_string.clone())
+                }
+                (None, _) => out.push_str("string"),
+            },
+            TypeScriptPrimativeType::Number
+                if self.big && options.bigint_mode != BigIntMode::Number =>
+            {
+                out.push_str(options.bigint_mode.render())
+            }
+            TypeScriptPrimativeType::Number => {
+                out.push_str(options.number_mode.render(self.integer))
+            }
+            TypeScriptPrimativeType::Null => out.push_str("null"),
+            TypeScriptPrimativeType::Unknown => out.push_str(options.fallback_type.render()),
+            TypeScriptPrimativeType::Union => {
+                for (index, member) in self.sub_items.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(" | ");
                     }
+                    member.write_type(out, depth, context, false);
                 }
             }
+            TypeScriptPrimativeType::Object => match context.type_names.get(&self.hash) {
+                _ if self.reference.is_some() => out.push_str(self.reference.as_ref().unwrap()),
+                Some(type_name) if !declaration_body => out.push_str(type_name),
+                _ if self.sub_items.is_empty() => out.push_str("{}"),
+                _ => {
+                    out.push_str("{\n");
+                    for sub_item in &self.sub_items {
+                        let name = sub_item.name.as_deref().unwrap_or_default();
+                        out.push_str(&sub_item.doc_comment(depth + 1, options));
+                        format.write_indent(out, depth + 1);
+                        if options.readonly {
+                            out.push_str("readonly ");
+                        }
+                        if crate::is_identifier(name) {
+                            out.push_str(name);
+                        } else {
+                            out.push_str(&serde_json::to_string(name).unwrap());
+                        }
+                        if sub_item.optional {
+                            out.push('?');
+                        }
+                        out.push_str(": ");
+                        if options.strict
+                            && sub_item.type_signature == TypeScriptPrimativeType::Null
+                        {
+                            out.push_str("unknown");
+                        } else {
+                            sub_item.write_type(out, depth + 1, context, false);
+                        }
+                        out.push_str(format.terminator());
+                        if options.strict {
+                            let mut reasons = Vec::new();
+                            sub_item.undetermined(&mut reasons);
+                            if !reasons.is_empty() {
+                                let _ = write!(out, " // TODO: {}", reasons.join(", "));
+                            }
+                        }
+                        out.push('\n');
+                    }
+                    format.write_indent(out, depth);
+                    out.push('}');
+                }
+            },
             TypeScriptPrimativeType::Array => {
-                let mut array_types_seen = HashSet::<String>::new();
-                for a in node.sub_items {
-                    let array_type = TypeScriptNode::to_type_string_helper(
-                        a,
-                        true,
-                        indent_size + 1,
-                        type_output_cache,
-                    );
-                    array_types_seen.insert(array_type);
+                let element_types: Vec<String> = self
+                    .elements()
+                    .iter()
+                    .map(|element| element.render_type(depth, context, false))
+                    .unique()
+                    .sorted()
+                    .collect();
+                if options.readonly {
+                    out.push_str("readonly ");
                 }
-                let to_append = match array_types_seen.len() {
-                    0 => "any".to_string(),
-                    1 => format!("{}", array_types_seen.into_iter().next().unwrap()),
-                    _ => {
-                        format!("({})", &array_types_seen.iter().sorted().join(" | "))
+                let only_null = options.strict
+                    && self
+                        .elements()
+                        .iter()
+                        .all(|element| element.type_signature == TypeScriptPrimativeType::Null);
+                // writing to a string can't fail
+                let _ = match element_types.as_slice() {
+                    element_types if element_types.is_empty() || only_null => {
+                        match &options.empty_array_type {
+                            Some(empty_array_type) => out.write_str(empty_array_type),
+                            None => write!(out, "{}[]", options.fallback_type.render()),
+                        }
+                    }
+                    // `readonly readonly T[][]` isn't valid
+                    [element_type]
+                        if Self::has_top_level_union(element_type)
+                            || element_type.starts_with("readonly ") =>
+                    {
+                        write!(out, "({})[]", element_type)
                     }
+                    [element_type] => write!(out, "{}[]", element_type),
+                    _ => write!(out, "({})[]", element_types.join(" | ")),
                 };
-                type_string.push_str(&to_append);
-                type_string.push_str("[]");
             }
         }
-        if node.optional {
-            type_string.push_str("?");
+        if self.nullable {
+            out.push_str(" | null");
         }
-        if node.nullable {
-            type_string.push_str("null");
+    }
+
+    /// Why the type of a property couldn't be worked out from the samples, for the `// TODO`
+    /// comments of [`RenderOptions::strict`]. The properties of objects have comments of their
+    /// own.
+    fn undetermined(&self, reasons: &mut Vec<&'static str>) {
+        let is_null = |node: &TypeScriptNode| node.type_signature == TypeScriptPrimativeType::Null;
+        let reason = match self.type_signature {
+            TypeScriptPrimativeType::Null => "only ever null",
+            TypeScriptPrimativeType::Unknown => "nested below --max-depth",
+            TypeScriptPrimativeType::Array if self.elements().is_empty() => {
+                "only ever an empty array"
+            }
+            TypeScriptPrimativeType::Array if self.elements().iter().all(is_null) => {
+                "only ever an array of null"
+            }
+            TypeScriptPrimativeType::Array | TypeScriptPrimativeType::Union => {
+                let members = match self.type_signature {
+                    TypeScriptPrimativeType::Array => self.elements(),
+                    _ => &self.sub_items,
+                };
+                // `null` is a member of a union like any other
+                for member in members.iter().filter(|member| !is_null(member)) {
+                    member.undetermined(reasons);
+                }
+                return;
+            }
+            _ => return,
+        };
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
         }
-        if !parent_array_node {
-            type_string.push_str(";\n");
+    }
+
+    /// The JSDoc comment documenting a property, if any
+    fn doc_comment(&self, depth: usize, options: &RenderOptions) -> String {
+        let mut lines = Vec::new();
+        if options.examples && !self.examples.is_empty() {
+            lines.push(format!(
+                "e.g. {}",
+                self.examples
+                    .iter()
+                    .map(|example| {
+                        let example = example.to_string();
+                        if example.chars().count() > MAX_EXAMPLE_LENGTH {
+                            format!(
+                                "{}…",
+                                example.chars().take(MAX_EXAMPLE_LENGTH).collect::<String>()
+                            )
+                        } else {
+                            example
+                        }
+                    })
+                    .join(", ")
+            ));
+        }
+        if let (Some(StringFormatMode::Jsdoc), Some(format)) = (
+            options.string_formats,
+            self.string_format.filter(|f| f.is_string_format()),
+        ) {
+            lines.push(format!("@format {}", format.format_name()));
+        }
+        if let (Some(_), Some(StringFormat::Base64)) = (&options.base64_type, self.string_format) {
+            lines.push("Base64 encoded, decode it before use".to_string());
+        }
+        if let (true, Some(wire_name)) = (options.original_names, &self.wire_name) {
+            lines.push(format!("@originalName {}", wire_name));
+        }
+        let indent = options.format.indent(depth);
+        let lines: Vec<String> = lines
+            .iter()
+            .map(|line| line.replace("*/", "*\\/"))
+            .collect();
+        match lines.as_slice() {
+            [] => String::new(),
+            [line] => format!("{}/** {} */\n", indent, line),
+            _ => {
+                let mut comment = format!("{}/**\n", indent);
+                for line in &lines {
+                    let _ = writeln!(comment, "{} * {}", indent, line);
+                }
+                let _ = writeln!(comment, "{} */", indent);
+                comment
+            }
         }
-        type_string
     }
 
-    pub(crate) fn with_name(mut self, name: String) -> Self {
+    /// Shares one copy of every name in the tree, see [`crate::interner`]
+    pub(crate) fn intern_names(&mut self, interner: &mut Interner) {
+        if let Some(name) = &self.name {
+            self.name = Some(interner.intern(name));
+        }
+        for sub_item in &mut self.sub_items {
+            sub_item.intern_names(interner);
+        }
+    }
+
+    pub(crate) fn with_name(mut self, name: Arc<str>) -> Self {
         self.name = Some(name);
         self
     }
 
+    pub(crate) fn with_source(mut self, pointer: String) -> Self {
+        self.sources.push(Source {
+            file: None,
+            pointer,
+        });
+        self
+    }
+
+    pub(crate) fn with_string_value(mut self, value: &str) -> Self {
+        self.string_values.observe(value);
+        self
+    }
+
+    #[cfg(feature = "cli")]
+    pub(crate) fn with_optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    pub(crate) fn with_nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    #[cfg(feature = "cli")]
+    pub(crate) fn with_literals(mut self, literals: Vec<String>) -> Self {
+        self.literals = Some(literals);
+        self
+    }
+
+    /// Makes an object node stand for the type declared as `name`
+    #[cfg(feature = "cli")]
+    pub(crate) fn with_reference(mut self, name: String) -> Self {
+        self.reference = Some(name);
+        self
+    }
+
+    pub(crate) fn with_string_format(mut self, string_format: Option<StringFormat>) -> Self {
+        self.string_format = string_format;
+        self
+    }
+
+    fn uses_date_format(&self) -> bool {
+        self.string_format.is_some_and(|format| format.is_date())
+            || self.sub_items.iter().any(Self::uses_date_format)
+    }
+
+    /// The formats other than dates recognised anywhere in the tree, those declared alongside
+    /// the types
+    fn collect_string_formats(&self, formats: &mut BTreeSet<StringFormat>) {
+        if let Some(format) = self.string_format.filter(|format| !format.is_date()) {
+            formats.insert(format);
+        }
+        for sub_item in &self.sub_items {
+            sub_item.collect_string_formats(formats);
+        }
+    }
+
+    pub(crate) fn with_integer(mut self, integer: bool) -> Self {
+        self.integer = integer;
+        self
+    }
+
+    pub(crate) fn with_big(mut self, big: bool) -> Self {
+        self.big = big;
+        self
+    }
+
+    /// Whether a number anywhere in the tree was an integer beyond `Number.MAX_SAFE_INTEGER`
+    #[cfg(feature = "cli")]
+    pub(crate) fn uses_big_integer(&self) -> bool {
+        self.big || self.sub_items.iter().any(Self::uses_big_integer)
+    }
+
+    fn uses_integer(&self) -> bool {
+        self.integer || self.sub_items.iter().any(Self::uses_integer)
+    }
+
+    fn uses_fallback(&self, options: &RenderOptions) -> bool {
+        match self.type_signature {
+            TypeScriptPrimativeType::Unknown => true,
+            TypeScriptPrimativeType::Array if self.sub_items.is_empty() => {
+                options.empty_array_type.is_none()
+            }
+            _ => self
+                .sub_items
+                .iter()
+                .any(|sub_item| sub_item.uses_fallback(options)),
+        }
+    }
+
+    pub(crate) fn with_example(mut self, example: Value) -> Self {
+        self.examples.push(example);
+        self
+    }
+
     pub(crate) fn with_sub_items(mut self, sub_items: Vec<TypeScriptNode>) -> Self {
         self.sub_items = sub_items;
         self
     }
+
+    /// Sets the elements of an array node, merging elements of the same shape, and object
+    /// elements with overlapping keys, into one element, which is a union of those left.
+    /// Objects tagged with different values of the discriminator stay apart, each with its tag
+    /// as a literal.
+    pub(crate) fn with_elements(
+        mut self,
+        mut elements: Vec<TypeScriptNode>,
+        discriminator: Option<&str>,
+    ) -> Self {
+        if let Some(tag) = Self::discriminator(&elements, discriminator) {
+            for element in &mut elements {
+                element.mark_tag(&tag);
+            }
+        }
+        // every element is a value of its own, whatever the shapes of the others
+        let mut alternatives = Vec::new();
+        for element in elements {
+            Self::add_alternative(&mut alternatives, element, true);
+        }
+        self.set_elements(Self::consolidate_elements(alternatives, true));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RenderOptions, TypeScriptNode};
+    use crate::walk_value_tree;
+    #[cfg(feature = "cli")]
+    use {
+        super::TypeScriptPrimativeType,
+        crate::{walk_value_tree_with_options, InferOptions, KeyOrder},
+    };
+
+    #[test]
+    fn keeps_objects_with_colliding_hashes_inline() {
+        let val_tree = serde_json::json!({
+            "billing": { "city": "a" },
+            "shipping": { "city": "b" },
+            "owner": { "name": "c" },
+            "payer": { "name": "d" },
+        });
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions::default();
+        assert_eq!(result.type_names(&options).len(), 2);
+        // as though the two shapes hashed the same
+        let hash = result.sub_items[0].hash;
+        for sub_item in &mut result.sub_items {
+            sub_item.hash = hash;
+        }
+        assert!(result.type_names(&options).is_empty());
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  billing: {\n    city: string;\n  };\n  owner: {\n    name: string;\n  };\n  payer: {\n    name: string;\n  };\n  shipping: {\n    city: string;\n  };\n};\n"
+        );
+    }
+
+    #[test]
+    fn flags_undetermined_fields_when_strict() {
+        let val_tree = serde_json::json!({
+            "tags": [],
+            "note": null,
+            "flags": [null],
+            "history": [{ "at": null }, { "at": null }],
+            "names": ["a", null],
+        });
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let options = RenderOptions {
+            strict: true,
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            TypeScriptNode::to_type_string_with_options(&result, &options),
+            "type DefaultType = {
+  flags: unknown[]; // TODO: only ever an array of null
+  history: DefaultType_0[];
+  names: (null | string)[];
+  note: unknown; // TODO: only ever null
+  tags: unknown[]; // TODO: only ever an empty array
+};
+
+type DefaultType_0 = {
+  at: unknown; // TODO: only ever null
+};
+"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn shares_types_whatever_the_key_order() {
+        let val_tree = serde_json::json!({
+            "x": [{ "z": "a", "a": 1 }, { "z": "b", "a": 1 }, { "z": "a", "a": 1 }],
+            "y": [{ "a": 2, "z": "b" }, { "a": 2, "z": "a" }, { "a": 2, "z": "c" }],
+        });
+        let options = InferOptions {
+            key_order: KeyOrder::Insertion,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.infer_literals(5);
+        result.calculate_hash();
+        let [x, y] =
+            [&result.sub_items[0], &result.sub_items[1]].map(|array| array.elements()[0].hash);
+        assert_eq!(x, y);
+        assert_eq!(
+            TypeScriptNode::to_type_string(&result),
+            "type DefaultType = {\n  x: DefaultType_0[];\n  y: DefaultType_0[];\n};\n\ntype DefaultType_0 = {\n  z: \"a\" | \"b\" | \"c\";\n  a: number;\n};\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn hashes_literals_apart() {
+        let literals = |literals: &[&str]| {
+            TypeScriptNode::new(TypeScriptPrimativeType::String, false, false, false, false)
+                .with_literals(literals.iter().map(|literal| literal.to_string()).collect())
+                .calculate_hash()
+        };
+        assert_ne!(literals(&["ab"]), literals(&["a", "b"]));
+        assert_eq!(literals(&["a", "b"]), literals(&["a", "b"]));
+    }
 }
@@ -0,0 +1,608 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use anyhow::bail;
+
+use crate::errors::ErrorKind;
+use crate::schema::{child_path, FieldSchema, SchemaBuilder};
+use crate::Result;
+
+/// Just enough of the TypeScript type grammar to read back the declarations myrrh generates
+#[derive(Debug, PartialEq)]
+enum TsType {
+    Primitive(String),
+    Object(Vec<TsMember>),
+    Array(Box<TsType>),
+    Union(Vec<TsType>),
+    Reference(String),
+}
+
+#[derive(Debug, PartialEq)]
+struct TsMember {
+    key: String,
+    optional: bool,
+    value: TsType,
+    /// Line the member is declared on
+    line: usize,
+}
+
+struct Declaration {
+    name: String,
+    ts_type: TsType,
+    line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+/// Parses a generated TypeScript file into the field schema of its first declaration, or of the
+/// `Raw` one declared alongside it when the fields were renamed
+pub(crate) fn parse_schema(source: &str) -> Result<FieldSchema> {
+    Ok(parse_schema_lines(source)?.0)
+}
+
+/// Parses a generated TypeScript file like [`parse_schema`], along with the line declaring each
+/// field. Fields of a type declared once and referred to from several places share its lines.
+pub(crate) fn parse_schema_lines(source: &str) -> Result<(FieldSchema, BTreeMap<String, usize>)> {
+    let declarations = parse_declarations(source)?;
+    let first = declarations
+        .first()
+        .ok_or_else(|| ErrorKind::Parse.because("no type declarations found"))?;
+    let by_name: HashMap<&str, &Declaration> = declarations
+        .iter()
+        .map(|declaration| (declaration.name.as_str(), declaration))
+        .collect();
+    let root = by_name
+        .get(format!("Raw{}", first.name).as_str())
+        .copied()
+        .unwrap_or(first);
+    let mut reader = SchemaReader {
+        declarations: by_name,
+        visiting: HashSet::new(),
+        builder: SchemaBuilder::default(),
+        lines: BTreeMap::from([("$".to_string(), root.line)]),
+    };
+    reader.add_type(&root.ts_type, "$", false);
+    Ok((reader.builder.finish(), reader.lines))
+}
+
+struct SchemaReader<'a> {
+    declarations: HashMap<&'a str, &'a Declaration>,
+    /// Declarations being read, which references back to are recursive
+    visiting: HashSet<&'a str>,
+    builder: SchemaBuilder,
+    lines: BTreeMap<String, usize>,
+}
+
+impl<'a> SchemaReader<'a> {
+    fn add_type(&mut self, ts_type: &'a TsType, path: &str, optional: bool) {
+        match ts_type {
+            TsType::Primitive(name) if name == "any" || name == "unknown" => (),
+            TsType::Primitive(name) => self.builder.add(path, name, optional),
+            TsType::Object(members) => {
+                self.builder.add(path, "object", optional);
+                for member in members {
+                    let member_path = child_path(path, &member.key);
+                    self.lines.entry(member_path.clone()).or_insert(member.line);
+                    self.add_type(&member.value, &member_path, member.optional);
+                }
+            }
+            TsType::Array(element) => {
+                self.builder.add(path, "array", optional);
+                self.add_type(element, &format!("{}[*]", path), false);
+            }
+            TsType::Union(members) => {
+                for member in members {
+                    self.add_type(member, path, optional);
+                }
+            }
+            TsType::Reference(name) => match self.declarations.get(name.as_str()).copied() {
+                Some(declaration) if !self.visiting.contains(name.as_str()) => {
+                    self.visiting.insert(&declaration.name);
+                    self.add_type(&declaration.ts_type, path, optional);
+                    self.visiting.remove(declaration.name.as_str());
+                }
+                Some(_) => self.builder.add(path, "object", optional),
+                // how dates and big integers are typed doesn't change what the JSON holds
+                None if name == "Date" => self.builder.add(path, "string", optional),
+                None if name == "bigint" => self.builder.add(path, "number", optional),
+                None => self.builder.add(path, name, optional),
+            },
+        }
+    }
+}
+
+fn parse_declarations(source: &str) -> Result<Vec<Declaration>> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+    let mut declarations = Vec::new();
+    while parser.peek().is_some() {
+        if parser.eat_ident("export") {
+            continue;
+        }
+        if parser.eat_ident("function") {
+            parser.skip_function()?;
+            continue;
+        }
+        if parser.eat_ident("import") {
+            while !parser.eat(';') {
+                parser
+                    .next()
+                    .ok_or_else(|| ErrorKind::Parse.because("unterminated import"))?;
+            }
+            continue;
+        }
+        let line = parser.line();
+        if parser.eat_ident("type") {
+            let name = parser.ident()?;
+            parser.expect('=')?;
+            let ts_type = parser.parse_type()?;
+            parser.eat(';');
+            declarations.push(Declaration {
+                name,
+                ts_type,
+                line,
+            });
+        } else if parser.eat_ident("interface") {
+            let name = parser.ident()?;
+            parser.expect('{')?;
+            declarations.push(Declaration {
+                name,
+                ts_type: TsType::Object(parser.parse_members()?),
+                line,
+            });
+        } else {
+            bail!(
+                ErrorKind::Parse.because(format!("unexpected token {:?}", parser.peek().unwrap()))
+            );
+        }
+    }
+    Ok(declarations)
+}
+
+/// The characters of the source, counting the lines they are on
+struct SourceChars<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+
+impl SourceChars<'_> {
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+}
+
+impl Iterator for SourceChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+}
+
+/// Splits the source into tokens, each with the line it starts on
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = SourceChars {
+        chars: source.chars().peekable(),
+        line: 1,
+    };
+    while let Some(&c) = chars.peek() {
+        let line = chars.line;
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '/' {
+            chars.next();
+            match chars.next() {
+                Some('/') => {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some('*') => {
+                    let mut previous = ' ';
+                    for c in chars.by_ref() {
+                        if previous == '*' && c == '/' {
+                            break;
+                        }
+                        previous = c;
+                    }
+                }
+                _ => bail!(ErrorKind::Parse.because("unexpected `/`")),
+            }
+        } else if c == '"' || c == '\'' || c == '`' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(escaped) => value.push(escaped),
+                        None => bail!(ErrorKind::Parse.because("unterminated string")),
+                    },
+                    Some(end) if end == c => break,
+                    Some(other) => value.push(other),
+                    None => bail!(ErrorKind::Parse.because("unterminated string")),
+                }
+            }
+            tokens.push((Token::Str(value), line));
+        } else if c.is_alphanumeric() || c == '_' || c == '$' || c == '-' || c == '.' {
+            let mut value = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' || c == '$' || c == '-' || c == '.' {
+                    value.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((Token::Ident(value), line));
+        } else {
+            chars.next();
+            tokens.push((Token::Punct(c), line));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.position + n).map(|(token, _)| token)
+    }
+
+    /// Line of the next token
+    fn line(&self) -> usize {
+        self.tokens.get(self.position).map_or(0, |&(_, line)| line)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        if self.peek() == Some(&Token::Punct(punct)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(value)) if value == ident) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: char) -> Result<()> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            bail!(ErrorKind::Parse.because(format!(
+                "expected `{}` but found {:?}",
+                punct,
+                self.peek()
+            )))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Ident(value)) => Ok(value),
+            other => {
+                bail!(ErrorKind::Parse
+                    .because(format!("expected an identifier but found {:?}", other)))
+            }
+        }
+    }
+
+    /// Skips a function declaration, from its parameters to the end of its body
+    fn skip_function(&mut self) -> Result<()> {
+        while !self.eat('{') {
+            self.next()
+                .ok_or_else(|| ErrorKind::Parse.because("unterminated function declaration"))?;
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Punct('{')) => depth += 1,
+                Some(Token::Punct('}')) => depth -= 1,
+                Some(_) => (),
+                None => bail!(ErrorKind::Parse.because("unterminated function body")),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_type(&mut self) -> Result<TsType> {
+        self.eat('|');
+        let mut members = vec![self.parse_intersection()?];
+        while self.eat('|') {
+            members.push(self.parse_intersection()?);
+        }
+        Ok(if members.len() == 1 {
+            members.pop().unwrap()
+        } else {
+            TsType::Union(members)
+        })
+    }
+
+    /// Reads a branded type, `number & { readonly __brand: "Integer" }`, as its base type
+    fn parse_intersection(&mut self) -> Result<TsType> {
+        let mut ts_type = self.parse_postfix()?;
+        while self.eat('&') {
+            let other = self.parse_postfix()?;
+            if is_brand(&ts_type) {
+                ts_type = other;
+            }
+        }
+        Ok(ts_type)
+    }
+
+    fn parse_postfix(&mut self) -> Result<TsType> {
+        let mut ts_type = self.parse_primary()?;
+        while self.eat('[') {
+            self.expect(']')?;
+            ts_type = TsType::Array(Box::new(ts_type));
+        }
+        Ok(ts_type)
+    }
+
+    fn parse_primary(&mut self) -> Result<TsType> {
+        match self.next() {
+            Some(Token::Punct('{')) => {
+                // flow's exact objects, `{| ... |}`
+                self.eat('|');
+                Ok(TsType::Object(self.parse_members()?))
+            }
+            // flow's maybe types, `?string`
+            Some(Token::Punct('?')) => Ok(TsType::Union(vec![
+                self.parse_postfix()?,
+                TsType::Primitive("null".to_string()),
+            ])),
+            Some(Token::Punct('(')) => {
+                let ts_type = self.parse_type()?;
+                self.expect(')')?;
+                Ok(ts_type)
+            }
+            Some(Token::Str(_)) => Ok(TsType::Primitive("string".to_string())),
+            Some(Token::Ident(value)) if value == "readonly" => self.parse_postfix(),
+            Some(Token::Ident(value)) => {
+                let mut arguments = Vec::new();
+                if self.eat('<') {
+                    arguments.push(self.parse_type()?);
+                    while self.eat(',') {
+                        arguments.push(self.parse_type()?);
+                    }
+                    self.expect('>')?;
+                }
+                Ok(match value.as_str() {
+                    "Array" | "ReadonlyArray" | "$ReadOnlyArray" if arguments.len() == 1 => {
+                        TsType::Array(Box::new(arguments.pop().unwrap()))
+                    }
+                    "string" | "number" | "boolean" | "null" | "any" | "unknown" => {
+                        TsType::Primitive(value)
+                    }
+                    "mixed" => TsType::Primitive("unknown".to_string()),
+                    "true" | "false" => TsType::Primitive("boolean".to_string()),
+                    _ if value.parse::<f64>().is_ok() => TsType::Primitive("number".to_string()),
+                    _ => TsType::Reference(value),
+                })
+            }
+            other => {
+                bail!(ErrorKind::Parse.because(format!("expected a type but found {:?}", other)))
+            }
+        }
+    }
+
+    /// Parses object members up to and including the closing brace
+    fn parse_members(&mut self) -> Result<Vec<TsMember>> {
+        let mut members = Vec::new();
+        while !self.eat('}') {
+            if self.peek() == Some(&Token::Punct('|'))
+                && self.peek_nth(1) == Some(&Token::Punct('}'))
+            {
+                self.position += 2;
+                break;
+            }
+            self.eat_ident("readonly");
+            let line = self.line();
+            let key = match self.next() {
+                Some(Token::Ident(value)) | Some(Token::Str(value)) => value,
+                other => bail!(ErrorKind::Parse
+                    .because(format!("expected a property name but found {:?}", other))),
+            };
+            let optional = self.eat('?');
+            self.expect(':')?;
+            let value = self.parse_type()?;
+            if !self.eat(';') {
+                self.eat(',');
+            }
+            members.push(TsMember {
+                key,
+                optional,
+                value,
+                line,
+            });
+        }
+        Ok(members)
+    }
+}
+
+fn is_brand(ts_type: &TsType) -> bool {
+    matches!(ts_type, TsType::Object(members) if members.iter().all(|member| member.key == "__brand"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_schema;
+    use crate::formats::{BigIntMode, DateType, NumberMode, StringFormatMode};
+    use crate::rename::to_camel_case;
+    use crate::schema::FieldSchema;
+    use crate::typescript_node::RenderOptions;
+    use crate::{
+        asserts, factories, flow, guards, normalizers, walk_value_tree,
+        walk_value_tree_with_options, InferOptions, TypeScriptNode,
+    };
+
+    const SAMPLE: &str = r#"{
+        "user_id": 1,
+        "total": 12345678901234567890,
+        "created": "2024-01-31T12:00:00Z",
+        "id": "0f8fad5b-d9cb-469f-a165-70867728950e",
+        "email": "a@b.io",
+        "billing": { "street_name": "a", "city": "b" },
+        "shipping": { "street_name": "c", "city": "d" },
+        "replies": [{ "text": "a", "replies": [{ "text": "b", "replies": [] }] }],
+        "note": null
+    }"#;
+
+    /// Checks the types rendered from `SAMPLE` read back as the schema it was inferred with
+    fn assert_round_trips(render: impl Fn(&TypeScriptNode, &RenderOptions) -> String) {
+        let val_tree = serde_json::from_str(SAMPLE).unwrap();
+        let options = InferOptions {
+            detect_dates: true,
+            detect_string_formats: true,
+            detect_integers: true,
+            ..InferOptions::default()
+        };
+        let mut result = walk_value_tree_with_options(&val_tree, None, &options);
+        result.fold_recursive_objects();
+        result.unify_repeated_objects();
+        result.calculate_hash();
+        let output = render(&result, &RenderOptions::default());
+        assert_eq!(
+            parse_schema(&output).unwrap(),
+            FieldSchema::from_node(&result),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn generated_output_round_trips() {
+        let json = r#"{
+            "payments": [{ "amount": 1, "currency": "USD" }, { "amount": 2, "currency": "GBP" }],
+            "woah lol": { "test": ["woaher", { "test": "example" }], "empty": [] },
+            "refunded": false,
+            "note": null
+        }"#;
+        let val_tree = serde_json::from_str(json).unwrap();
+        let expected = FieldSchema::from_node(&walk_value_tree(&val_tree, None));
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        let output_string = TypeScriptNode::to_type_string(&result);
+        assert_eq!(parse_schema(&output_string).unwrap(), expected);
+    }
+
+    #[test]
+    fn output_round_trips_whatever_the_render_options() {
+        let with = |options: RenderOptions| {
+            move |node: &TypeScriptNode, _: &RenderOptions| {
+                TypeScriptNode::to_type_string_with_options(node, &options)
+            }
+        };
+        assert_round_trips(with(RenderOptions::default()));
+        assert_round_trips(with(RenderOptions {
+            number_mode: NumberMode::Branded,
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            number_mode: NumberMode::Jsdoc,
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            string_formats: Some(StringFormatMode::Branded),
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            string_formats: Some(StringFormatMode::Jsdoc),
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            date_type: Some(DateType::Date),
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            date_type: Some(DateType::Comment),
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            date_type: Some(DateType::Branded("IsoDate".to_string())),
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            bigint_mode: BigIntMode::Bigint,
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(with(RenderOptions {
+            readonly: true,
+            export: true,
+            examples: true,
+            ..RenderOptions::default()
+        }));
+        assert_round_trips(flow::to_flow_string);
+    }
+
+    #[test]
+    fn output_round_trips_alongside_generated_functions() {
+        assert_round_trips(|node, options| {
+            let mut output = TypeScriptNode::to_type_string_with_options(node, options);
+            output.push_str(&guards::to_guard_string(node, options));
+            output.push_str(&asserts::to_assert_string(node, options));
+            output.push_str(&factories::to_factory_string(node, options));
+            output
+        });
+    }
+
+    #[test]
+    fn renamed_output_round_trips_through_its_raw_types() {
+        assert_round_trips(|raw, options| {
+            let mut node = raw.clone();
+            node.rename_fields(&to_camel_case);
+            node.calculate_hash();
+            let mut output = TypeScriptNode::to_type_string_with_options(&node, options);
+            output.push('\n');
+            output.push_str(&TypeScriptNode::to_type_string_with_options(
+                raw,
+                &RenderOptions {
+                    root_name: format!("Raw{}", options.root_name),
+                    ..options.clone()
+                },
+            ));
+            output.push_str(&normalizers::to_normalizer_string(
+                &node,
+                &options.root_name,
+            ));
+            output
+        });
+    }
+}
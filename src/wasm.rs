@@ -0,0 +1,16 @@
+use wasm_bindgen::prelude::*;
+
+use crate::request::{infer_types as infer, RequestOptions};
+
+/// Infers the TypeScript declarations of a JSON document, exported to JavaScript as
+/// `inferTypes(json, options)`
+#[wasm_bindgen(js_name = inferTypes)]
+pub fn infer_types(json: &str, options: JsValue) -> std::result::Result<String, JsError> {
+    let options = if options.is_undefined() || options.is_null() {
+        RequestOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|error| JsError::new(&format!("invalid options: {}", error)))?
+    };
+    infer(json, options).map_err(|error| JsError::new(&format!("{:#}", error)))
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode};
+
+/// How long to wait after a change for the rest of an editor's save to land
+pub(crate) const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The directory to watch for an `--input`. Files are watched through their directory, as
+/// editors often save by replacing the file, and globs through the directory they start in.
+pub(crate) fn watched_path(input: &str) -> (PathBuf, RecursiveMode) {
+    let path = absolute(Path::new(input));
+    if is_glob(input) {
+        let root: PathBuf = path
+            .components()
+            .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+            .collect();
+        let mode = if input.contains("**") {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        (root, mode)
+    } else if path.is_dir() {
+        (path, RecursiveMode::Recursive)
+    } else {
+        let parent = path.parent().map(Path::to_path_buf).unwrap_or(path);
+        (parent, RecursiveMode::NonRecursive)
+    }
+}
+
+/// Whether the event changed one of the inputs
+pub(crate) fn is_relevant(event: &Event, inputs: &[String]) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|path| {
+        inputs.iter().any(|input| {
+            let input_path = absolute(Path::new(input));
+            if is_glob(input) {
+                glob::Pattern::new(&input_path.to_string_lossy())
+                    .is_ok_and(|pattern| pattern.matches_path(path))
+            } else if input_path.is_dir() {
+                path.starts_with(&input_path) && path.extension().is_some_and(|ext| ext == "json")
+            } else {
+                *path == input_path
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::event::{AccessKind, ModifyKind};
+    use notify::{Event, EventKind};
+
+    use super::is_relevant;
+
+    #[test]
+    fn only_changes_to_inputs_are_relevant() {
+        let inputs = ["fixtures/*.json".to_string(), "order.json".to_string()];
+        let event =
+            |kind, path: &str| Event::new(kind).add_path(std::path::absolute(path).unwrap());
+        let modify = EventKind::Modify(ModifyKind::Any);
+        assert!(is_relevant(&event(modify, "fixtures/a.json"), &inputs));
+        assert!(is_relevant(&event(modify, "order.json"), &inputs));
+        assert!(!is_relevant(&event(modify, "order.ts"), &inputs));
+        assert!(!is_relevant(&event(modify, "fixtures/a.ts"), &inputs));
+        let access = EventKind::Access(AccessKind::Any);
+        assert!(!is_relevant(&event(access, "order.json"), &inputs));
+    }
+}
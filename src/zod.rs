@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::formats::StringFormat;
+use crate::typescript_node::{
+    Fingerprint, RenderContext, RenderOptions, TypeScriptNode, TypeScriptPrimativeType,
+};
+
+pub(crate) const ZOD_IMPORT: &str = "import { z } from \"zod\";\n";
+
+/// Renders the tree as Zod schemas, `{root_name}Schema` for the root and `{name}Schema` for
+/// each common type, with a `z.infer` type exported alongside every schema. Recursive types
+/// can't be inferred, so their TypeScript type is declared instead, with a lazy schema.
+pub(crate) fn to_zod_string(node: &TypeScriptNode, options: &RenderOptions) -> String {
+    let context = RenderContext::new(node, options);
+    let type_names = &context.type_names;
+    let mut output = String::from(ZOD_IMPORT);
+    for (name, named_node) in node.named_types(type_names) {
+        let schema = object_schema(named_node, type_names, 0);
+        output.push_str(&if named_node.is_recursive() {
+            recursive_declaration(name, named_node, &schema, &context)
+        } else {
+            declaration(name, &schema)
+        });
+    }
+    let schema = schema(node, type_names, 0, true);
+    output.push_str(&if node.is_recursive() {
+        recursive_declaration(&options.root_name, node, &schema, &context)
+    } else {
+        declaration(&options.root_name, &schema)
+    });
+    output
+}
+
+fn declaration(name: &str, schema: &str) -> String {
+    format!(
+        "\nexport const {name}Schema = {schema};\nexport type {name} = z.infer<typeof {name}Schema>;\n",
+        name = name,
+        schema = schema
+    )
+}
+
+fn recursive_declaration(
+    name: &str,
+    node: &TypeScriptNode,
+    schema: &str,
+    context: &RenderContext,
+) -> String {
+    format!(
+        "\nexport type {name} = {type_body};\nexport const {name}Schema: z.ZodType<{name}> = z.lazy(() => {schema});\n",
+        name = name,
+        type_body = node.render_type(0, context, true),
+        schema = schema
+    )
+}
+
+fn schema(
+    node: &TypeScriptNode,
+    type_names: &HashMap<Fingerprint, String>,
+    indent: usize,
+    root: bool,
+) -> String {
+    let mut schema = match node.type_signature() {
+        TypeScriptPrimativeType::String => match (node.string_format(), node.literals()) {
+            (Some(StringFormat::Date), _) => "z.string().date()".to_string(),
+            (Some(StringFormat::DateTime), _) => {
+                "z.string().datetime({ offset: true })".to_string()
+            }
+            (Some(StringFormat::Uuid), _) => "z.string().uuid()".to_string(),
+            (Some(StringFormat::Email), _) => "z.string().email()".to_string(),
+            (Some(StringFormat::Url), _) => "z.string().url()".to_string(),
+            (Some(StringFormat::Ipv4), _) => "z.string().ip({ version: \"v4\" })".to_string(),
+            (Some(StringFormat::Ipv6), _) => "z.string().ip({ version: \"v6\" })".to_string(),
+            (Some(StringFormat::Base64), _) => "z.string().base64()".to_string(),
+            (None, Some(literals)) => format!(
+                "z.enum([{}])",
+                literals
+                    .iter()
+                    .map(|literal| serde_json::to_string(literal).unwrap())
+                    .join(", ")
+            ),
+            (None, None) => "z.string()".to_string(),
+        },
+        TypeScriptPrimativeType::Number if node.is_integer() => "z.number().int()".to_string(),
+        TypeScriptPrimativeType::Number => "z.number()".to_string(),
+        TypeScriptPrimativeType::Boolean => "z.boolean()".to_string(),
+        TypeScriptPrimativeType::Null => "z.null()".to_string(),
+        TypeScriptPrimativeType::Unknown => "z.unknown()".to_string(),
+        TypeScriptPrimativeType::Union => format!(
+            "z.union([{}])",
+            node.sub_items()
+                .iter()
+                .map(|member| schema(member, type_names, indent, false))
+                .join(", ")
+        ),
+        TypeScriptPrimativeType::Object => match type_names.get(&node.hash()) {
+            Some(name) if !root => format!("{}Schema", name),
+            _ => object_schema(node, type_names, indent),
+        },
+        TypeScriptPrimativeType::Array => {
+            let elements: Vec<String> = node
+                .elements()
+                .iter()
+                .map(|element| schema(element, type_names, indent, false))
+                .unique()
+                .sorted()
+                .collect();
+            match elements.as_slice() {
+                [] => "z.array(z.unknown())".to_string(),
+                [element] => format!("z.array({})", element),
+                _ => format!("z.array(z.union([{}]))", elements.join(", ")),
+            }
+        }
+    };
+    if node.is_nullable() {
+        schema.push_str(".nullable()");
+    }
+    if node.is_optional() {
+        schema.push_str(".optional()");
+    }
+    schema
+}
+
+fn object_schema(
+    node: &TypeScriptNode,
+    type_names: &HashMap<Fingerprint, String>,
+    indent: usize,
+) -> String {
+    if node.sub_items().is_empty() {
+        return "z.object({})".to_string();
+    }
+    let pad = "  ".repeat(indent + 1);
+    let fields: String = node
+        .sub_items()
+        .iter()
+        .map(|sub_item| {
+            let key = sub_item.name().unwrap_or_default();
+            let key = if crate::is_identifier(key) {
+                key.to_string()
+            } else {
+                serde_json::to_string(key).unwrap()
+            };
+            format!(
+                "{}{}: {},\n",
+                pad,
+                key,
+                schema(sub_item, type_names, indent + 1, false)
+            )
+        })
+        .collect();
+    format!("z.object({{\n{}{}}})", fields, "  ".repeat(indent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_zod_string;
+    use crate::typescript_node::RenderOptions;
+    use crate::walk_value_tree;
+
+    #[test]
+    fn renders_zod_schemas() {
+        let val_tree = serde_json::from_str(
+            r#"{ "payments": [{ "amount": 1, "currency": "USD" }, { "amount": 2, "currency": "GBP" }], "woah lol": [null, "a"] }"#,
+        )
+        .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.calculate_hash();
+        assert_eq!(
+            to_zod_string(&result, &RenderOptions::default()),
+            "import { z } from \"zod\";
+
+export const DefaultType_0Schema = z.object({
+  amount: z.number(),
+  currency: z.string(),
+});
+export type DefaultType_0 = z.infer<typeof DefaultType_0Schema>;
+
+export const DefaultTypeSchema = z.object({
+  payments: z.array(DefaultType_0Schema),
+  \"woah lol\": z.array(z.union([z.null(), z.string()])),
+});
+export type DefaultType = z.infer<typeof DefaultTypeSchema>;
+"
+        );
+    }
+
+    #[test]
+    fn declares_recursive_schemas_lazily() {
+        let val_tree =
+            serde_json::from_str(r#"{ "id": 1, "children": [{ "id": 2, "children": [] }] }"#)
+                .unwrap();
+        let mut result = walk_value_tree(&val_tree, None);
+        result.fold_recursive_objects();
+        result.calculate_hash();
+        assert_eq!(
+            to_zod_string(&result, &RenderOptions::default()),
+            "import { z } from \"zod\";
+
+export type DefaultType = {
+  children: DefaultType[];
+  id: number;
+};
+export const DefaultTypeSchema: z.ZodType<DefaultType> = z.lazy(() => z.object({
+  children: z.array(DefaultTypeSchema),
+  id: z.number(),
+}));
+"
+        );
+    }
+}